@@ -0,0 +1,65 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pokbotcom::kaart::{
+    evalueer_zeven,
+    opzoektabel::{self, CompacteKaart},
+    Hand, Kaart,
+};
+use rand::prelude::*;
+
+fn willekeurige_handen<const N: usize>(aantal: usize) -> Vec<[Kaart; N]> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut deck = Kaart::maak_deck();
+    (0..aantal)
+        .map(|_| {
+            deck.shuffle(&mut rng);
+            deck[..N].try_into().unwrap()
+        })
+        .collect()
+}
+
+fn vijf_kaarten(c: &mut Criterion) {
+    let handen = willekeurige_handen::<5>(1000);
+    let compact: Vec<_> = handen.iter().map(|h| h.map(CompacteKaart::from)).collect();
+
+    let mut groep = c.benchmark_group("vijf kaarten");
+    groep.bench_function("Hand::new", |b| {
+        b.iter(|| {
+            for hand in &handen {
+                black_box(Hand::new(*hand));
+            }
+        })
+    });
+    groep.bench_function("opzoektabel::rang5", |b| {
+        b.iter(|| {
+            for hand in &compact {
+                black_box(opzoektabel::rang5(*hand));
+            }
+        })
+    });
+    groep.finish();
+}
+
+fn zeven_kaarten(c: &mut Criterion) {
+    let handen = willekeurige_handen::<7>(1000);
+    let compact: Vec<_> = handen.iter().map(|h| h.map(CompacteKaart::from)).collect();
+
+    let mut groep = c.benchmark_group("zeven kaarten");
+    groep.bench_function("evalueer_zeven", |b| {
+        b.iter(|| {
+            for hand in &handen {
+                black_box(evalueer_zeven(hand));
+            }
+        })
+    });
+    groep.bench_function("opzoektabel::rang", |b| {
+        b.iter(|| {
+            for hand in &compact {
+                black_box(opzoektabel::rang(hand));
+            }
+        })
+    });
+    groep.finish();
+}
+
+criterion_group!(benches, vijf_kaarten, zeven_kaarten);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+use pokbotcom::opslag::sqlite::SqliteOpslag;
+use pokbotcom::server::{self, WebhookInstellingen};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let adres = std::env::var("POKBOTCOM_ADRES").unwrap_or_else(|_| "127.0.0.1:3000".to_string());
+    let databank = std::env::var("POKBOTCOM_DB").unwrap_or_else(|_| "pokbotcom.db".to_string());
+    let app = server::router_met_opslag(
+        SqliteOpslag::open(&databank)?,
+        WebhookInstellingen::default(),
+    )?;
+
+    let listener = tokio::net::TcpListener::bind(&adres).await?;
+    println!("pokbotcom luistert op {adres}, met databank {databank}");
+
+    axum::serve(listener, app).await?;
+    Ok(())
+}
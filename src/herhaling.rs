@@ -0,0 +1,407 @@
+// Een spel opnieuw spelen vanaf zijn seed en de acties die de spelers stuurden, in volgorde.
+// Het deck hangt enkel van de seed af, dus na elke actie komt er exact dezelfde toestand uit:
+// zo kan een bug die met een bot gevonden werd, stap voor stap nagespeeld worden. Elk spel
+// houdt zijn log bij in `Spel::log`; `Centrale::spel_log` geeft die samen met de seed en de
+// spelers aan het begin van het spel.
+//
+// Chips die een speler intussen in een ander spel won of verloor, zitten niet in de log.
+
+use std::fmt::Display;
+
+use anyhow::Result;
+use itertools::Itertools;
+
+use crate::historie::Stoel;
+use crate::kaart::Kaart;
+use crate::{
+    Actie, BetStructuur, BlindNiveau, Centrale, PokbotcomError, Pot, SpelId, SpelStatus, SpelerId,
+    Tafel, VerplichteInzetten,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpelerToestand {
+    pub speler_id: SpelerId,
+    pub chips: u64,
+    pub inzet: u64,
+    pub hand: Option<(Kaart, Kaart)>,
+}
+
+// De toestand van een spel na een actie, zie `Centrale::momentopname`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Momentopname {
+    pub spelers: Vec<SpelerToestand>, // in de volgorde van de tafel
+    pub potten: Vec<Pot>,
+    pub tafel: Tafel,
+    pub huidige_dealer: usize,
+    pub aan_de_beurt: usize,
+    pub kaarten_in_deck: usize,
+    pub huidige_inzet: u64,
+    pub laatste_actionabele_speler: Option<SpelerId>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub laatste_verhoging: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub verhogingen: u32,
+    pub status: SpelStatus,
+}
+
+impl Momentopname {
+    // wat er anders is aan de andere momentopname, in woorden
+    pub fn verschillen(&self, ander: &Momentopname) -> Vec<String> {
+        let mut verschillen = Vec::new();
+        if self.spelers.len() != ander.spelers.len() {
+            verschillen.push("spelers".to_string());
+        }
+        for (a, b) in self.spelers.iter().zip(&ander.spelers) {
+            let id = a.speler_id;
+            for (veld, anders) in [
+                ("speler", a.speler_id != b.speler_id),
+                ("chips", a.chips != b.chips),
+                ("inzet", a.inzet != b.inzet),
+                ("hand", a.hand != b.hand),
+            ] {
+                if anders {
+                    verschillen.push(format!("{veld} van {id:?}"));
+                }
+            }
+        }
+        for (veld, anders) in [
+            ("potten", self.potten != ander.potten),
+            ("tafel", self.tafel != ander.tafel),
+            (
+                "huidige_dealer",
+                self.huidige_dealer != ander.huidige_dealer,
+            ),
+            ("aan_de_beurt", self.aan_de_beurt != ander.aan_de_beurt),
+            (
+                "kaarten_in_deck",
+                self.kaarten_in_deck != ander.kaarten_in_deck,
+            ),
+            ("huidige_inzet", self.huidige_inzet != ander.huidige_inzet),
+            (
+                "laatste_actionabele_speler",
+                self.laatste_actionabele_speler != ander.laatste_actionabele_speler,
+            ),
+            (
+                "laatste_verhoging",
+                self.laatste_verhoging != ander.laatste_verhoging,
+            ),
+            ("verhogingen", self.verhogingen != ander.verhogingen),
+            ("status", self.status != ander.status),
+        ] {
+            if anders {
+                verschillen.push(veld.to_string());
+            }
+        }
+        verschillen
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Uitkomst {
+    pub fout: Option<String>, // de fout als de actie geweigerd werd
+    pub toestand: Momentopname,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GelogdeActie {
+    pub speler_id: SpelerId,
+    pub actie: Actie,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub uitkomst: Option<Uitkomst>, // niet bekend als de log met de hand gemaakt werd
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpelLog {
+    pub spel_id: SpelId,
+    pub seed: u64,
+    pub stoelen: Vec<Stoel>, // de spelers en hun chips toen het spel startte
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bet_structuur: BetStructuur,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub niveau: BlindNiveau,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub verplichte_inzetten: VerplichteInzetten,
+    pub acties: Vec<GelogdeActie>,
+}
+
+impl SpelLog {
+    // Een log met enkel de acties, bv. uit een bugrapport.
+    pub fn new(
+        spel_id: SpelId,
+        seed: u64,
+        stoelen: Vec<Stoel>,
+        acties: impl IntoIterator<Item = (SpelerId, Actie)>,
+    ) -> Self {
+        SpelLog {
+            spel_id,
+            seed,
+            stoelen,
+            bet_structuur: BetStructuur::NoLimit,
+            niveau: BlindNiveau::default(),
+            verplichte_inzetten: VerplichteInzetten::default(),
+            acties: acties
+                .into_iter()
+                .map(|(speler_id, actie)| GelogdeActie {
+                    speler_id,
+                    actie,
+                    uitkomst: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Centrale {
+    // De log van een gestart spel.
+    pub fn spel_log(&self, spel_id: SpelId) -> Option<SpelLog> {
+        let spel = self
+            .spellen
+            .iter()
+            .find(|s| s.id.get().unwrap() == &spel_id)?;
+        Some(SpelLog {
+            spel_id,
+            seed: spel.seed,
+            stoelen: spel.historie.first()?.stoelen.clone(),
+            bet_structuur: spel.bet_structuur,
+            niveau: spel.historie.first()?.niveau,
+            verplichte_inzetten: spel.verplichte_inzetten,
+            acties: spel.log.clone(),
+        })
+    }
+}
+
+// Een spel dat actie per actie opnieuw gespeeld wordt in een eigen `Centrale`.
+#[derive(Debug)]
+pub struct Herhaling<'a> {
+    log: &'a SpelLog,
+    centrale: Centrale,
+    stap: usize,
+}
+
+impl<'a> Herhaling<'a> {
+    // Start het spel opnieuw; er is nog geen enkele actie gespeeld.
+    pub fn new(log: &'a SpelLog) -> Result<Self> {
+        if let Some(actie) = log
+            .acties
+            .iter()
+            .find(|a| log.stoelen.iter().all(|s| s.speler_id != a.speler_id))
+        {
+            return Err(PokbotcomError::NietInSpel(actie.speler_id, log.spel_id).into());
+        }
+        if log.stoelen.len() < 2 {
+            return Err(PokbotcomError::TeWeinigSpelers.into());
+        }
+
+        let mut centrale = Centrale::aan_tafel(&log.stoelen, log.spel_id, Some(log.seed));
+        centrale.zet_bet_structuur(log.spel_id, log.bet_structuur)?;
+        centrale.zet_verplichte_inzetten(log.spel_id, log.niveau, log.verplichte_inzetten)?;
+        centrale.start_spel(log.spel_id)?;
+        Ok(Herhaling {
+            log,
+            centrale,
+            stap: 0,
+        })
+    }
+
+    pub fn centrale(&self) -> &Centrale {
+        &self.centrale
+    }
+
+    // het aantal acties dat al gespeeld is
+    pub fn stap(&self) -> usize {
+        self.stap
+    }
+
+    // Speelt de volgende actie uit de log en geeft de uitkomst, of niets als de log op is.
+    pub fn volgende(&mut self) -> Option<Uitkomst> {
+        let gelogd = self.log.acties.get(self.stap)?;
+        self.stap += 1;
+        // een geweigerde actie staat ook in de log
+        let _ = self
+            .centrale
+            .ontvang_actie(self.log.spel_id, gelogd.speler_id, gelogd.actie);
+        self.centrale
+            .get_spel(self.log.spel_id)
+            .log
+            .last()?
+            .uitkomst
+            .clone()
+    }
+
+    pub fn into_centrale(self) -> Centrale {
+        self.centrale
+    }
+}
+
+// De `Centrale` na de eerste `stappen` acties uit de log.
+pub fn herhaal(log: &SpelLog, stappen: usize) -> Result<Centrale> {
+    let mut herhaling = Herhaling::new(log)?;
+    while herhaling.stap() < stappen && herhaling.volgende().is_some() {}
+    Ok(herhaling.into_centrale())
+}
+
+// De eerste actie waarna het opnieuw gespeelde spel niet meer overeenkomt met de log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Afwijking {
+    pub stap: usize, // index in `SpelLog::acties`
+    pub speler_id: SpelerId,
+    pub actie: Actie,
+    pub verwacht: Uitkomst,
+    pub gevonden: Uitkomst,
+}
+
+impl Display for Afwijking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "actie {} ({:?}: {:?}) wijkt af:",
+            self.stap + 1,
+            self.speler_id,
+            self.actie
+        )?;
+        if self.verwacht.fout != self.gevonden.fout {
+            write!(
+                f,
+                " fout {:?} in plaats van {:?};",
+                self.gevonden.fout, self.verwacht.fout
+            )?;
+        }
+        let verschillen = self.verwacht.toestand.verschillen(&self.gevonden.toestand);
+        if !verschillen.is_empty() {
+            write!(f, " andere {}", verschillen.iter().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// Speelt het spel uit de log opnieuw en vergelijkt na elke actie de uitkomst met die in de
+// log. Acties zonder gelogde uitkomst worden enkel gespeeld.
+pub fn verifieer(log: &SpelLog) -> Result<Option<Afwijking>> {
+    let mut herhaling = Herhaling::new(log)?;
+    while let Some(gevonden) = herhaling.volgende() {
+        let gelogd = &log.acties[herhaling.stap() - 1];
+        match &gelogd.uitkomst {
+            Some(verwacht) if *verwacht != gevonden => {
+                return Ok(Some(Afwijking {
+                    stap: herhaling.stap() - 1,
+                    speler_id: gelogd.speler_id,
+                    actie: gelogd.actie,
+                    verwacht: verwacht.clone(),
+                    gevonden,
+                }))
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Speler;
+
+    // twee handen met drie spelers, met een paar geweigerde acties tussen
+    fn gespeeld_spel() -> (Centrale, SpelId) {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let [a, b, c] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), None).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+
+        // B is niet aan de beurt en A moet de big blind nog callen
+        let mut acties = vec![
+            (b, Actie::Call),
+            (a, Actie::Check),
+            (a, Actie::Bet(40)),
+            (b, Actie::Call),
+            (c, Actie::Call),
+        ];
+        acties.extend([b, c, a].repeat(3).into_iter().map(|id| (id, Actie::Check)));
+        acties.extend([(b, Actie::AllIn), (c, Actie::Fold), (a, Actie::Call)]);
+        for (id, actie) in acties {
+            let _ = centrale.ontvang_actie(spel_id, id, actie);
+        }
+        (centrale, spel_id)
+    }
+
+    #[test]
+    fn spel_opnieuw_spelen() {
+        let (centrale, spel_id) = gespeeld_spel();
+        let c = centrale.spelers[2].id.get().copied();
+        let log = centrale.spel_log(spel_id).unwrap();
+        assert_eq!(log.acties.len(), 17);
+        let fouten = log
+            .acties
+            .iter()
+            .filter_map(|a| a.uitkomst.as_ref().unwrap().fout.as_ref())
+            .collect_vec();
+        assert_eq!(
+            fouten,
+            [
+                &PokbotcomError::NietJouwBeurt.to_string(),
+                &PokbotcomError::NietGenoegChips.to_string(),
+            ]
+        );
+        // een geweigerde actie laat het spel zoals het was
+        let toestand = |i: usize| &log.acties[i].uitkomst.as_ref().unwrap().toestand;
+        assert_eq!(toestand(1), toestand(0));
+        assert_eq!(toestand(1).laatste_actionabele_speler, c);
+        assert_eq!(verifieer(&log).unwrap(), None);
+
+        let mut herhaling = Herhaling::new(&log).unwrap();
+        for gelogd in &log.acties {
+            assert_eq!(herhaling.volgende().as_ref(), gelogd.uitkomst.as_ref());
+        }
+        assert_eq!(herhaling.volgende(), None);
+        assert_eq!(
+            herhaling.centrale().hand_historieen(spel_id),
+            centrale.hand_historieen(spel_id)
+        );
+
+        // enkel de seed en de acties zijn genoeg
+        let kaal = SpelLog::new(
+            spel_id,
+            log.seed,
+            log.stoelen.clone(),
+            log.acties.iter().map(|a| (a.speler_id, a.actie)),
+        );
+        let na_vijf = herhaal(&kaal, 5).unwrap();
+        assert_eq!(
+            Some(na_vijf.momentopname(spel_id)),
+            log.acties[4].uitkomst.as_ref().map(|u| u.toestand.clone())
+        );
+        assert_eq!(verifieer(&kaal).unwrap(), None);
+    }
+
+    #[test]
+    fn eerste_afwijking() {
+        let (centrale, spel_id) = gespeeld_spel();
+        let mut log = centrale.spel_log(spel_id).unwrap();
+        log.acties[2].actie = Actie::Bet(60);
+
+        let afwijking = verifieer(&log).unwrap().unwrap();
+        assert_eq!(afwijking.stap, 2);
+        assert_eq!(afwijking.gevonden.fout, None);
+        assert_eq!(
+            afwijking.to_string(),
+            "actie 3 (SpelerId(0): Bet(60)) wijkt af: andere chips van SpelerId(0), \
+             inzet van SpelerId(0), huidige_inzet, laatste_verhoging"
+        );
+
+        log.seed += 1;
+        assert_eq!(verifieer(&log).unwrap().unwrap().stap, 0);
+
+        log.acties[0].speler_id = SpelerId(7);
+        assert!(verifieer(&log).is_err());
+    }
+}
@@ -0,0 +1,418 @@
+// Wat er in elke gespeelde hand gebeurd is, zodat achteraf na te gaan valt waarom een bot
+// deed wat hij deed. `Centrale` houdt per spel de historie van alle handen bij; op te vragen
+// met `Centrale::hand_historie`. Een historie kan geschreven worden in het formaat van
+// `phh` of `pokerstars` om ze met bestaande pokertools te bekijken, en een hand in
+// PHH-formaat kan met `Centrale::speel_na` opnieuw gespeeld worden.
+
+use anyhow::Result;
+use itertools::Itertools;
+
+use crate::kaart::{Hand, Kaart};
+use crate::{
+    Actie, Bedragen, BetStructuur, BlindNiveau, Centrale, PokbotcomError, Pot, Spel, SpelId,
+    SpelStatus, Speler, SpelerId, Tafel, VerplichteInzetten,
+};
+
+pub mod phh;
+pub mod pokerstars;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Straat {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl Straat {
+    pub fn van(tafel: &Tafel) -> Self {
+        match tafel {
+            (None, _, _) => Straat::Preflop,
+            (Some(_), None, _) => Straat::Flop,
+            (Some(_), Some(_), None) => Straat::Turn,
+            (Some(_), Some(_), Some(_)) => Straat::River,
+        }
+    }
+
+    // de straat waarin er zoveel kaarten op tafel liggen
+    pub fn met_kaarten(aantal: usize) -> Option<Self> {
+        match aantal {
+            0 => Some(Straat::Preflop),
+            3 => Some(Straat::Flop),
+            4 => Some(Straat::Turn),
+            5 => Some(Straat::River),
+            _ => None,
+        }
+    }
+
+    pub fn aantal_kaarten(self) -> usize {
+        match self {
+            Straat::Preflop => 0,
+            Straat::Flop => 3,
+            Straat::Turn => 4,
+            Straat::River => 5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stoel {
+    pub speler_id: SpelerId,
+    pub naam: String,
+    pub chips: u64, // bij het begin van de hand, voor de blinds
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandHistorie {
+    pub spel_id: SpelId,
+    pub handnummer: u64,     // de eerste hand van een spel heeft nummer 1
+    pub stoelen: Vec<Stoel>, // in de volgorde van de tafel
+    pub dealer: SpelerId,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bet_structuur: BetStructuur,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub niveau: BlindNiveau,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub verplichte_inzetten: VerplichteInzetten,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub antes: Vec<(SpelerId, u64)>, // in de volgorde van de tafel
+    pub blinds: Vec<(SpelerId, u64)>, // de small blind, de big blind en eventueel de straddle
+    pub hole_cards: Vec<(SpelerId, Kaart, Kaart)>,
+    pub acties: Vec<(Straat, SpelerId, Actie)>,
+    pub tafel: Vec<Kaart>,
+    pub showdown: Vec<(SpelerId, Hand)>, // de beste hand van elke speler die niet gefold had
+    pub potten: Vec<Pot>,
+    pub winsten: Vec<(SpelerId, u64)>, // per pot en per winnaar, in de volgorde van uitbetalen
+}
+
+impl HandHistorie {
+    // De antes en blinds die de engine aan deze tafel zou innen, met de dealer op plaats
+    // `dealer`.
+    pub(crate) fn verplichte_bedragen(
+        &self,
+        dealer: usize,
+    ) -> (Bedragen<SpelerId>, Bedragen<SpelerId>) {
+        let stacks = self.stoelen.iter().map(|s| s.chips).collect_vec();
+        let (antes, blinds) = self
+            .verplichte_inzetten
+            .bereken(self.niveau, &stacks, dealer);
+        let met_id = |bedragen: Vec<(usize, u64)>| {
+            bedragen
+                .into_iter()
+                .map(|(plaats, chips)| (self.stoelen[plaats].speler_id, chips))
+                .collect_vec()
+        };
+        (met_id(antes), met_id(blinds))
+    }
+
+    pub(crate) fn leeg(spel_id: SpelId) -> Self {
+        HandHistorie {
+            spel_id,
+            handnummer: 1,
+            stoelen: Vec::new(),
+            dealer: SpelerId(0),
+            bet_structuur: BetStructuur::NoLimit,
+            niveau: BlindNiveau::default(),
+            verplichte_inzetten: VerplichteInzetten::default(),
+            antes: Vec::new(),
+            blinds: Vec::new(),
+            hole_cards: Vec::new(),
+            acties: Vec::new(),
+            tafel: Vec::new(),
+            showdown: Vec::new(),
+            potten: Vec::new(),
+            winsten: Vec::new(),
+        }
+    }
+
+    // of de hand al uitgespeeld is
+    pub fn is_klaar(&self) -> bool {
+        !self.winsten.is_empty()
+    }
+
+    pub fn acties_in(&self, straat: Straat) -> impl Iterator<Item = (SpelerId, Actie)> + '_ {
+        self.acties
+            .iter()
+            .filter(move |(s, _, _)| *s == straat)
+            .map(|(_, speler, actie)| (*speler, *actie))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Centrale, Speler, BIG_BLIND, SMALL_BLIND};
+
+    #[test]
+    fn hele_hand() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let [id_a, id_b, id_c] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(3)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        let handen = centrale
+            .spelers
+            .iter()
+            .map(|s| s.hand.unwrap())
+            .collect::<Vec<_>>();
+
+        let mut acties = vec![
+            (id_a, Actie::Bet(40)),
+            (id_b, Actie::Call),
+            (id_c, Actie::Call),
+        ];
+        acties.extend(
+            [id_b, id_c, id_a]
+                .repeat(3)
+                .into_iter()
+                .map(|id| (id, Actie::Check)),
+        );
+        for (id, actie) in acties {
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+        assert!(hand.is_klaar());
+        assert_eq!(hand.dealer, id_a);
+        assert_eq!(
+            hand.stoelen
+                .iter()
+                .map(|s| (s.naam.as_str(), s.chips))
+                .collect::<Vec<_>>(),
+            [("Aart", 1000), ("Bart", 1000), ("Cart", 1000)]
+        );
+        assert_eq!(hand.blinds, [(id_b, SMALL_BLIND), (id_c, BIG_BLIND)]);
+        for (i, (id, a, b)) in hand.hole_cards.iter().enumerate() {
+            assert_eq!((*id, (*a, *b)), (ids[i], handen[i]));
+        }
+
+        assert_eq!(hand.acties_in(Straat::Preflop).count(), 3);
+        for straat in [Straat::Flop, Straat::Turn, Straat::River] {
+            assert_eq!(
+                hand.acties_in(straat).collect::<Vec<_>>(),
+                [
+                    (id_b, Actie::Check),
+                    (id_c, Actie::Check),
+                    (id_a, Actie::Check)
+                ]
+            );
+        }
+        assert_eq!(hand.tafel.len(), 5);
+        assert_eq!(hand.showdown.len(), 3);
+
+        let pot = 3 * (BIG_BLIND + 40);
+        assert_eq!(hand.potten.iter().map(|p| p.chips).sum::<u64>(), pot);
+        assert_eq!(
+            hand.winsten.iter().map(|(_, chips)| chips).sum::<u64>(),
+            pot
+        );
+
+        // de volgende hand is al gedeeld, met de chips van na de vorige
+        let volgende = centrale.hand_historie(spel_id, 2).unwrap();
+        assert!(!volgende.is_klaar());
+        assert_eq!(volgende.dealer, id_b);
+        assert_eq!(volgende.stoelen.iter().map(|s| s.chips).sum::<u64>(), 3000);
+        assert_eq!(centrale.hand_historieen(spel_id).len(), 2);
+        assert!(centrale.hand_historie(spel_id, 3).is_none());
+        assert!(centrale.hand_historie(SpelId(9), 1).is_none());
+    }
+}
+
+// Wat een actie in chips betekende, zoals andere formaten ze noteren.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Zet {
+    pub betaald: u64,      // wat de speler er bij deze actie bij legde
+    pub tot: u64,          // de inzet van de speler in deze straat na de actie
+    pub vorige_inzet: u64, // de hoogste inzet in deze straat voor de actie
+    pub verhoogd: bool,
+    pub all_in: bool,
+}
+
+// Speelt de inzetten van een hand na zoals `Centrale` ze verwerkt, plaats per plaats in de
+// volgorde van `HandHistorie::stoelen`.
+#[derive(Clone, Debug)]
+pub(crate) struct Verloop {
+    pub plaatsen: Vec<SpelerId>,
+    pub stacks: Vec<u64>,
+    pub inzet: Vec<u64>,  // in de huidige straat
+    pub totaal: Vec<u64>, // over de hele hand, zonder wat teruggegeven werd
+    pub huidige_inzet: u64,
+    pub straat: Straat,
+}
+
+impl Verloop {
+    pub fn new(hand: &HandHistorie) -> Self {
+        let mut verloop = Verloop {
+            plaatsen: hand.stoelen.iter().map(|s| s.speler_id).collect(),
+            stacks: hand.stoelen.iter().map(|s| s.chips).collect(),
+            inzet: vec![0; hand.stoelen.len()],
+            totaal: vec![0; hand.stoelen.len()],
+            huidige_inzet: hand
+                .verplichte_inzetten
+                .preflop_inzet(hand.niveau, hand.stoelen.len()),
+            straat: Straat::Preflop,
+        };
+        for (id, ante) in &hand.antes {
+            if let Some(plaats) = verloop.plaats(*id) {
+                let ante = (*ante).min(verloop.stacks[plaats]);
+                verloop.stacks[plaats] -= ante;
+                verloop.totaal[plaats] += ante;
+            }
+        }
+        for (id, blind) in &hand.blinds {
+            if let Some(plaats) = verloop.plaats(*id) {
+                verloop.betaal(plaats, *blind);
+            }
+        }
+        verloop
+    }
+
+    pub fn plaats(&self, speler_id: SpelerId) -> Option<usize> {
+        self.plaatsen.iter().position(|id| *id == speler_id)
+    }
+
+    fn betaal(&mut self, plaats: usize, chips: u64) {
+        let chips = chips.min(self.stacks[plaats]);
+        self.stacks[plaats] -= chips;
+        self.inzet[plaats] += chips;
+        self.totaal[plaats] += chips;
+    }
+
+    pub fn speel(&mut self, plaats: usize, actie: Actie) -> Zet {
+        let vorige_inzet = self.huidige_inzet;
+        let betaald = match actie {
+            Actie::Fold | Actie::Check => 0,
+            Actie::Call => self.huidige_inzet.saturating_sub(self.inzet[plaats]),
+            Actie::Bet(extra) => self.huidige_inzet - self.inzet[plaats] + extra,
+            Actie::AllIn => self.stacks[plaats],
+        }
+        .min(self.stacks[plaats]);
+        self.betaal(plaats, betaald);
+        self.huidige_inzet = self.huidige_inzet.max(self.inzet[plaats]);
+
+        Zet {
+            betaald,
+            tot: self.inzet[plaats],
+            vorige_inzet,
+            verhoogd: self.inzet[plaats] > vorige_inzet,
+            all_in: betaald > 0 && self.stacks[plaats] == 0,
+        }
+    }
+
+    // Sluit de huidige straat af. Een inzet die door niemand gevolgd werd, gaat terug naar de
+    // speler; die plaats en het bedrag worden teruggegeven.
+    pub fn sluit_straat(&mut self) -> Option<(usize, u64)> {
+        let volgorde = (0..self.inzet.len())
+            .sorted_by_key(|&i| std::cmp::Reverse(self.inzet[i]))
+            .collect_vec();
+        let terug = match volgorde[..] {
+            [hoogste, tweede, ..] if self.inzet[hoogste] > self.inzet[tweede] => {
+                let teveel = self.inzet[hoogste] - self.inzet[tweede];
+                self.stacks[hoogste] += teveel;
+                self.totaal[hoogste] -= teveel;
+                Some((hoogste, teveel))
+            }
+            _ => None,
+        };
+
+        self.inzet.iter_mut().for_each(|inzet| *inzet = 0);
+        self.huidige_inzet = 0;
+        self.straat = match self.straat {
+            Straat::Preflop => Straat::Flop,
+            Straat::Flop => Straat::Turn,
+            _ => Straat::River,
+        };
+        terug
+    }
+}
+
+impl Centrale {
+    // Een nieuwe `Centrale` met enkel deze spelers, met hun ids, namen en chips, en een spel
+    // dat nog niet gestart is waarin ze in deze volgorde aan tafel zitten.
+    pub(crate) fn aan_tafel(stoelen: &[Stoel], spel_id: SpelId, rng_seed: Option<u64>) -> Centrale {
+        let mut centrale = Centrale::new();
+        for stoel in stoelen {
+            let speler = Speler::new_zonder_id(stoel.naam.clone());
+            speler.id.set(stoel.speler_id).unwrap();
+            centrale.spelers.push(Speler {
+                chips: stoel.chips,
+                ..speler
+            });
+        }
+        let volgende_speler_id = stoelen.iter().map(|s| s.speler_id.0 + 1).max();
+        centrale.volgende_geldige_speler_id = volgende_speler_id.unwrap_or(0).into();
+        centrale.volgende_geldige_spel_id = (spel_id.0 + 1).into();
+
+        let mut spel = Spel::new(spel_id, rng_seed);
+        spel.spelers = stoelen.iter().map(|s| s.speler_id).collect();
+        centrale.spellen.push(spel);
+        centrale
+    }
+
+    // Speelt een hand uit een historie opnieuw in een nieuwe `Centrale`, met dezelfde spelers,
+    // stacks, kaarten en acties. De historie van de nagespeelde hand, met de potten en wie ze
+    // won, is daarna op te vragen met `hand_historie`. Een actie die de engine weigert, geeft
+    // een fout.
+    pub fn speel_na(historie: &HandHistorie) -> Result<Centrale> {
+        let ongeldig = |reden: &str| PokbotcomError::OngeldigeHistorie(reden.to_string());
+        let dealer = historie
+            .stoelen
+            .iter()
+            .position(|s| s.speler_id == historie.dealer)
+            .ok_or_else(|| ongeldig("de dealer zit niet aan tafel"))?;
+        if historie.stoelen.len() < 2 {
+            return Err(PokbotcomError::TeWeinigSpelers.into());
+        }
+        let (antes, blinds) = historie.verplichte_bedragen(dealer);
+        if historie.blinds != blinds || historie.antes != antes {
+            return Err(ongeldig("andere blinds of antes dan die van de engine").into());
+        }
+
+        // het deck wordt van achteren af gedeeld: eerst de hole cards in de volgorde van de
+        // tafel, dan de kaarten op tafel
+        let mut gedeeld = Vec::new();
+        for stoel in &historie.stoelen {
+            let (_, a, b) = historie
+                .hole_cards
+                .iter()
+                .find(|(id, _, _)| *id == stoel.speler_id)
+                .ok_or_else(|| ongeldig("een speler heeft geen hole cards"))?;
+            gedeeld.extend([*a, *b]);
+        }
+        gedeeld.extend(&historie.tafel);
+        if let Some(dubbel) = gedeeld.iter().duplicates().next() {
+            return Err(PokbotcomError::DubbeleKaart(*dubbel).into());
+        }
+        let mut deck = Kaart::maak_deck()
+            .into_iter()
+            .filter(|k| !gedeeld.contains(k))
+            .collect_vec();
+        deck.extend(gedeeld.into_iter().rev());
+
+        let mut centrale = Centrale::aan_tafel(&historie.stoelen, historie.spel_id, Some(0));
+        let spel = centrale.get_mut_spel(historie.spel_id);
+        spel.huidige_dealer = dealer;
+        spel.bet_structuur = historie.bet_structuur;
+        spel.niveau = historie.niveau;
+        spel.verplichte_inzetten = historie.verplichte_inzetten;
+        spel.status = SpelStatus::Lopend;
+        centrale.deel_hand(historie.spel_id, Some(deck))?;
+        centrale.spellen[0].historie[0].handnummer = historie.handnummer;
+
+        for (_, speler_id, actie) in &historie.acties {
+            centrale.ontvang_actie(historie.spel_id, *speler_id, *actie)?;
+        }
+        Ok(centrale)
+    }
+}
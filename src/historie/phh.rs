@@ -0,0 +1,740 @@
+// Het Poker Hand History-formaat (PHH, zie https://phh.readthedocs.io): één hand per bestand,
+// in TOML. De spelers staan in volgorde van plaats vanaf de small blind, zodat de dealer de
+// laatste is, en worden in de acties aangeduid als `p1`, `p2`, ... De ids van de spelers
+// staan in het eigen veld `_speler_ids`. Heads-up is de dealer de small blind en staat de big
+// blind dus eerst; zoals in PHH afgesproken blijven `blinds_or_straddles` dan toch de small
+// blind en de big blind, in die volgorde.
+//
+// Fixed limit wordt geschreven als variant `FT`, met het maximale aantal bets en raises per
+// ronde in `_max_verhogingen`. PHH kent geen pot limit Texas hold'em: zo'n hand is een `NT`
+// met `_pot_limit = true`.
+//
+// Bij het inlezen worden enkel de stoelen, blinds, kaarten en acties overgenomen; de
+// showdown, potten en winsten volgen uit `Centrale::speel_na`.
+
+use itertools::Itertools;
+
+use super::{HandHistorie, Stoel, Straat, Verloop};
+use crate::kaart::notatie::lees_kaarten;
+use crate::{
+    Actie, BetStructuur, BlindNiveau, PokbotcomError, SpelId, SpelerId, VerplichteInzetten,
+};
+
+pub fn schrijf(hand: &HandHistorie) -> String {
+    let aantal = hand.stoelen.len();
+    let dealer = hand
+        .stoelen
+        .iter()
+        .position(|s| s.speler_id == hand.dealer)
+        .unwrap_or(0);
+    let volgorde = (1..=aantal).map(|i| (dealer + i) % aantal).collect_vec();
+    let speler = |plaats: usize| volgorde.iter().position(|p| *p == plaats).unwrap() + 1;
+
+    let mut acties = Vec::new();
+    for &plaats in &volgorde {
+        let id = hand.stoelen[plaats].speler_id;
+        if let Some((_, a, b)) = hand.hole_cards.iter().find(|(s, _, _)| *s == id) {
+            acties.push(format!("d dh p{} {a:#}{b:#}", speler(plaats)));
+        }
+    }
+
+    let mut verloop = Verloop::new(hand);
+    for (straat, speler_id, actie) in &hand.acties {
+        deel_tafel(hand, &mut verloop, *straat, &mut acties);
+        let Some(plaats) = verloop.plaats(*speler_id) else {
+            continue;
+        };
+        let zet = verloop.speel(plaats, *actie);
+        acties.push(match actie {
+            Actie::Fold => format!("p{} f", speler(plaats)),
+            _ if zet.verhoogd => format!("p{} cbr {}", speler(plaats), zet.tot),
+            _ => format!("p{} cc", speler(plaats)),
+        });
+    }
+
+    let mut finishing_stacks = None;
+    if hand.is_klaar() {
+        deel_tafel(hand, &mut verloop, Straat::River, &mut acties);
+        verloop.sluit_straat();
+        for &plaats in &volgorde {
+            let id = hand.stoelen[plaats].speler_id;
+            if hand.showdown.iter().any(|(s, _)| *s == id) {
+                if let Some((_, a, b)) = hand.hole_cards.iter().find(|(s, _, _)| *s == id) {
+                    acties.push(format!("p{} sm {a:#}{b:#}", speler(plaats)));
+                }
+            }
+        }
+
+        let mut stacks = verloop.stacks.clone();
+        for (id, chips) in &hand.winsten {
+            if let Some(plaats) = verloop.plaats(*id) {
+                stacks[plaats] += chips;
+            }
+        }
+        finishing_stacks = Some(volgorde.iter().map(|&p| stacks[p]).collect_vec());
+    }
+
+    // PHH schrijft de blinds en antes zoals ze op dit niveau horen, ook als een speler er
+    // te weinig chips voor had
+    let mut blinds = volgorde
+        .iter()
+        .map(|&p| {
+            let id = hand.stoelen[p].speler_id;
+            match hand.blinds.iter().position(|(s, _)| *s == id) {
+                Some(0) => hand.niveau.small_blind,
+                Some(1) => hand.niveau.big_blind,
+                Some(_) => 2 * hand.niveau.big_blind,
+                None => 0,
+            }
+        })
+        .collect_vec();
+    if aantal == 2 {
+        blinds.reverse();
+    }
+    let antes = volgorde
+        .iter()
+        .map(|&p| {
+            let id = hand.stoelen[p].speler_id;
+            match hand.antes.iter().any(|(s, _)| *s == id) {
+                true => hand.niveau.ante,
+                false => 0,
+            }
+        })
+        .collect_vec();
+
+    let mut tekst = String::new();
+    let mut veld = |naam: &str, waarde: String| tekst.push_str(&format!("{naam} = {waarde}\n"));
+    let variant = match hand.bet_structuur {
+        BetStructuur::FixedLimit { .. } => "FT",
+        _ => "NT",
+    };
+    veld("variant", tekst_waarde(variant));
+    veld("antes", lijst(antes));
+    veld("blinds_or_straddles", lijst(blinds));
+    match hand.bet_structuur {
+        BetStructuur::FixedLimit {
+            kleine_bet,
+            grote_bet,
+            ..
+        } => {
+            veld("small_bet", kleine_bet.to_string());
+            veld("big_bet", grote_bet.to_string());
+        }
+        _ => veld("min_bet", hand.niveau.big_blind.to_string()),
+    }
+    veld(
+        "starting_stacks",
+        lijst(volgorde.iter().map(|&p| hand.stoelen[p].chips)),
+    );
+    veld(
+        "actions",
+        format!(
+            "[\n{}]",
+            acties
+                .iter()
+                .map(|a| format!("  {},\n", tekst_waarde(a)))
+                .join("")
+        ),
+    );
+    veld(
+        "players",
+        lijst(
+            volgorde
+                .iter()
+                .map(|&p| tekst_waarde(&hand.stoelen[p].naam)),
+        ),
+    );
+    veld("table", hand.spel_id.0.to_string());
+    veld("hand", hand.handnummer.to_string());
+    if let Some(stacks) = finishing_stacks {
+        veld("finishing_stacks", lijst(stacks));
+    }
+    veld(
+        "_speler_ids",
+        lijst(volgorde.iter().map(|&p| hand.stoelen[p].speler_id.0)),
+    );
+    match hand.bet_structuur {
+        BetStructuur::NoLimit => {}
+        BetStructuur::PotLimit => veld("_pot_limit", true.to_string()),
+        BetStructuur::FixedLimit {
+            max_verhogingen, ..
+        } => veld("_max_verhogingen", max_verhogingen.to_string()),
+    }
+    tekst
+}
+
+// Deelt de kaarten op tafel tot en met de gegeven straat.
+fn deel_tafel(hand: &HandHistorie, verloop: &mut Verloop, tot: Straat, acties: &mut Vec<String>) {
+    while verloop.straat < tot {
+        let van = verloop.straat.aantal_kaarten();
+        verloop.sluit_straat();
+        if let Some(kaarten) = hand.tafel.get(van..verloop.straat.aantal_kaarten()) {
+            acties.push(format!(
+                "d db {}",
+                kaarten.iter().map(|k| format!("{k:#}")).join("")
+            ));
+        }
+    }
+}
+
+fn lijst<T: ToString>(waarden: impl IntoIterator<Item = T>) -> String {
+    format!(
+        "[{}]",
+        waarden.into_iter().map(|w| w.to_string()).join(", ")
+    )
+}
+
+fn tekst_waarde(tekst: &str) -> String {
+    format!("\"{}\"", tekst.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn lees(tekst: &str) -> Result<HandHistorie, PokbotcomError> {
+    let velden = Lezer { rest: tekst }.velden()?;
+    let veld = |naam: &str| velden.iter().find(|(n, _)| n == naam).map(|(_, v)| v);
+    let verplicht = |naam: &str| veld(naam).ok_or_else(|| ongeldig(format!("geen {naam}")));
+
+    let bet_structuur = match verplicht("variant")?.tekst()?.as_str() {
+        "NT" if veld("_pot_limit").map_or(Ok(false), Veld::waarheid)? => BetStructuur::PotLimit,
+        "NT" => BetStructuur::NoLimit,
+        "FT" => BetStructuur::FixedLimit {
+            kleine_bet: verplicht("small_bet")?.getal()?,
+            grote_bet: verplicht("big_bet")?.getal()?,
+            max_verhogingen: match veld("_max_verhogingen") {
+                Some(aantal) => aantal.getal()? as u32,
+                None => 4,
+            },
+        },
+        _ => {
+            return Err(ongeldig(
+                "enkel Texas hold'em, no-limit (NT) of fixed limit (FT), wordt ondersteund",
+            ))
+        }
+    };
+    let stacks = verplicht("starting_stacks")?.getallen()?;
+    let aantal = stacks.len();
+    let blinds = verplicht("blinds_or_straddles")?.getallen()?;
+    let antes = veld("antes").map_or(Ok(vec![0; aantal]), Veld::getallen)?;
+    let namen = match veld("players") {
+        Some(namen) => namen.lijst()?.iter().map(Veld::tekst).try_collect()?,
+        None => (1..=aantal).map(|i| format!("p{i}")).collect_vec(),
+    };
+    let ids = match veld("_speler_ids") {
+        Some(ids) => ids.getallen()?,
+        None => (0..aantal as u64).collect_vec(),
+    };
+    if [blinds.len(), antes.len(), namen.len(), ids.len()] != [aantal; 4] {
+        return Err(ongeldig("niet evenveel waarden als spelers"));
+    }
+    if aantal < 2 {
+        return Err(PokbotcomError::TeWeinigSpelers);
+    }
+    // een straddle is de derde blind; een ante die enkel de big blind betaalt, is een big
+    // blind ante
+    let straddle = blinds.get(2).is_some_and(|straddle| *straddle > 0);
+    if blinds
+        .iter()
+        .skip(if straddle { 3 } else { 2 })
+        .any(|b| *b > 0)
+        || straddle && blinds[2] != 2 * blinds[1]
+    {
+        return Err(ongeldig(
+            "enkel de blinds en een straddle van twee big blinds worden ondersteund",
+        ));
+    }
+    let ante = antes.iter().copied().max().unwrap_or(0);
+    let big_blind = if aantal == 2 { 0 } else { 1 };
+    let big_blind_ante = ante > 0 && antes.iter().filter(|a| **a > 0).count() == 1;
+    if big_blind_ante && antes[big_blind] == 0
+        || !big_blind_ante && antes.iter().any(|a| *a != ante)
+    {
+        return Err(ongeldig(
+            "enkel gelijke antes of een big blind ante worden ondersteund",
+        ));
+    }
+
+    let mut hand = HandHistorie {
+        handnummer: veld("hand").map_or(Ok(1), Veld::getal)?,
+        stoelen: (0..aantal)
+            .map(|i| Stoel {
+                speler_id: SpelerId(ids[i]),
+                naam: namen[i].clone(),
+                chips: stacks[i],
+            })
+            .collect(),
+        dealer: SpelerId(ids[aantal - 1]),
+        bet_structuur,
+        niveau: BlindNiveau::new(blinds[0], blinds[1], ante),
+        verplichte_inzetten: VerplichteInzetten {
+            big_blind_ante,
+            straddle,
+        },
+        ..HandHistorie::leeg(SpelId(veld("table").map_or(Ok(0), Veld::getal)?))
+    };
+    (hand.antes, hand.blinds) = hand.verplichte_bedragen(aantal - 1);
+
+    let mut hole_cards = vec![None; aantal];
+    let mut verloop = Verloop::new(&hand);
+    for actie in verplicht("actions")?.lijst()? {
+        let actie = actie.tekst()?;
+        let delen = actie
+            .split('#')
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .collect_vec();
+        let speler = |deel: &str| {
+            deel.strip_prefix('p')
+                .and_then(|nummer| nummer.parse::<usize>().ok())
+                .filter(|nummer| (1..=aantal).contains(nummer))
+                .map(|nummer| nummer - 1)
+                .ok_or_else(|| ongeldig(format!("onbekende speler in \"{actie}\"")))
+        };
+
+        match delen[..] {
+            ["d", "dh", p, kaarten] => match lees_kaarten(kaarten)?[..] {
+                [a, b] => hole_cards[speler(p)?] = Some((a, b)),
+                _ => return Err(ongeldig(format!("geen twee hole cards in \"{actie}\""))),
+            },
+            ["d", "db", kaarten] => {
+                hand.tafel.extend(lees_kaarten(kaarten)?);
+                let straat = Straat::met_kaarten(hand.tafel.len())
+                    .ok_or_else(|| ongeldig(format!("onverwachte kaarten in \"{actie}\"")))?;
+                while verloop.straat < straat {
+                    verloop.sluit_straat();
+                }
+            }
+            [p, "f"] => speel(&mut hand, &mut verloop, speler(p)?, Actie::Fold),
+            [p, "cc"] => {
+                let plaats = speler(p)?;
+                let actie = if verloop.inzet[plaats] < verloop.huidige_inzet {
+                    Actie::Call
+                } else {
+                    Actie::Check
+                };
+                speel(&mut hand, &mut verloop, plaats, actie);
+            }
+            [p, "cbr", tot] => {
+                let plaats = speler(p)?;
+                let tot = tot
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|tot| *tot > verloop.huidige_inzet)
+                    .ok_or_else(|| ongeldig(format!("ongeldige inzet in \"{actie}\"")))?;
+                let actie = if tot >= verloop.inzet[plaats] + verloop.stacks[plaats] {
+                    Actie::AllIn
+                } else {
+                    Actie::Bet(tot - verloop.huidige_inzet)
+                };
+                speel(&mut hand, &mut verloop, plaats, actie);
+            }
+            // de showdown volgt uit de hole cards
+            [_, "sm", ..] => {}
+            _ => return Err(ongeldig(format!("onbekende actie \"{actie}\""))),
+        }
+    }
+
+    for (i, kaarten) in hole_cards.into_iter().enumerate() {
+        let (a, b) = kaarten.ok_or_else(|| ongeldig(format!("geen hole cards voor p{}", i + 1)))?;
+        hand.hole_cards.push((SpelerId(ids[i]), a, b));
+    }
+    Ok(hand)
+}
+
+fn speel(hand: &mut HandHistorie, verloop: &mut Verloop, plaats: usize, actie: Actie) {
+    verloop.speel(plaats, actie);
+    hand.acties
+        .push((verloop.straat, verloop.plaatsen[plaats], actie));
+}
+
+fn ongeldig(reden: impl Into<String>) -> PokbotcomError {
+    PokbotcomError::OngeldigeHistorie(reden.into())
+}
+
+// Het deel van TOML dat in PHH gebruikt wordt: teksten, gehele getallen, booleans en lijsten.
+#[derive(Clone, Debug, PartialEq)]
+enum Veld {
+    Tekst(String),
+    Getal(u64),
+    Waarheid(bool),
+    Lijst(Vec<Veld>),
+}
+
+impl Veld {
+    fn tekst(&self) -> Result<String, PokbotcomError> {
+        match self {
+            Veld::Tekst(tekst) => Ok(tekst.clone()),
+            _ => Err(ongeldig(format!("geen tekst: {self:?}"))),
+        }
+    }
+
+    fn getal(&self) -> Result<u64, PokbotcomError> {
+        match self {
+            Veld::Getal(getal) => Ok(*getal),
+            _ => Err(ongeldig(format!("geen geheel getal: {self:?}"))),
+        }
+    }
+
+    fn waarheid(&self) -> Result<bool, PokbotcomError> {
+        match self {
+            Veld::Waarheid(waarheid) => Ok(*waarheid),
+            _ => Err(ongeldig(format!("geen boolean: {self:?}"))),
+        }
+    }
+
+    fn lijst(&self) -> Result<&[Veld], PokbotcomError> {
+        match self {
+            Veld::Lijst(lijst) => Ok(lijst),
+            _ => Err(ongeldig(format!("geen lijst: {self:?}"))),
+        }
+    }
+
+    fn getallen(&self) -> Result<Vec<u64>, PokbotcomError> {
+        self.lijst()?.iter().map(Veld::getal).collect()
+    }
+}
+
+struct Lezer<'a> {
+    rest: &'a str,
+}
+
+impl Lezer<'_> {
+    // slaat witruimte, nieuwe regels en commentaar over
+    fn sla_over(&mut self) {
+        loop {
+            self.rest = self.rest.trim_start();
+            if !self.rest.starts_with('#') {
+                break;
+            }
+            self.rest = self.rest.find('\n').map_or("", |i| &self.rest[i..]);
+        }
+    }
+
+    fn neem(&mut self, teken: char) -> bool {
+        match self.rest.strip_prefix(teken) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn woord(&mut self) -> &str {
+        let einde = self
+            .rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(self.rest.len());
+        let (woord, rest) = self.rest.split_at(einde);
+        self.rest = rest;
+        woord
+    }
+
+    fn velden(mut self) -> Result<Vec<(String, Veld)>, PokbotcomError> {
+        let mut velden = Vec::new();
+        loop {
+            self.sla_over();
+            if self.rest.is_empty() {
+                return Ok(velden);
+            }
+            let naam = self.woord().to_string();
+            self.sla_over();
+            if naam.is_empty() || !self.neem('=') {
+                let regel = self.rest.lines().next().unwrap_or_default();
+                return Err(ongeldig(format!(
+                    "verwachtte `naam = waarde` bij \"{naam}{regel}\""
+                )));
+            }
+            velden.push((naam, self.veld()?));
+        }
+    }
+
+    fn veld(&mut self) -> Result<Veld, PokbotcomError> {
+        self.sla_over();
+        if self.neem('"') {
+            let mut tekst = String::new();
+            let mut tekens = self.rest.char_indices();
+            loop {
+                match tekens.next() {
+                    Some((i, '"')) => {
+                        self.rest = &self.rest[i + 1..];
+                        return Ok(Veld::Tekst(tekst));
+                    }
+                    Some((_, '\\')) => match tekens.next() {
+                        Some((_, 'n')) => tekst.push('\n'),
+                        Some((_, 't')) => tekst.push('\t'),
+                        Some((_, c @ ('"' | '\\'))) => tekst.push(c),
+                        _ => return Err(ongeldig("ongeldige escape in tekst")),
+                    },
+                    Some((_, c)) => tekst.push(c),
+                    None => return Err(ongeldig("tekst zonder einde")),
+                }
+            }
+        }
+
+        if self.neem('[') {
+            let mut lijst = Vec::new();
+            loop {
+                self.sla_over();
+                if self.neem(']') {
+                    return Ok(Veld::Lijst(lijst));
+                }
+                lijst.push(self.veld()?);
+                self.sla_over();
+                if !self.neem(',') {
+                    self.sla_over();
+                    return match self.neem(']') {
+                        true => Ok(Veld::Lijst(lijst)),
+                        false => Err(ongeldig("lijst zonder einde")),
+                    };
+                }
+            }
+        }
+
+        match self.woord() {
+            "true" => Ok(Veld::Waarheid(true)),
+            "false" => Ok(Veld::Waarheid(false)),
+            woord => woord
+                .replace('_', "")
+                .parse()
+                .map(Veld::Getal)
+                .map_err(|_| ongeldig(format!("onbekende waarde \"{woord}\""))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Centrale, Speler};
+
+    fn gespeelde_hand() -> HandHistorie {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart \"C\""].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let [id_a, id_b, id_c] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(5)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        for (id, actie) in [
+            (id_a, Actie::Bet(40)),
+            (id_b, Actie::Fold),
+            (id_c, Actie::AllIn),
+            (id_a, Actie::Call),
+        ] {
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+        centrale.hand_historie(spel_id, 1).unwrap().clone()
+    }
+
+    #[test]
+    fn schrijven_en_nagespelen() {
+        let hand = gespeelde_hand();
+        assert!(hand.is_klaar());
+        let tekst = schrijf(&hand);
+
+        let regels = tekst.lines().collect_vec();
+        assert_eq!(regels[0], "variant = \"NT\"");
+        assert_eq!(regels[2], "blinds_or_straddles = [10, 20, 0]");
+        assert!(tekst.contains("players = [\"Bart\", \"Cart \\\"C\\\"\", \"Aart\"]"));
+        assert!(tekst.contains("_speler_ids = [1, 2, 0]"));
+        for actie in ["p3 cbr 60", "p1 f", "p2 cbr 1000", "p3 cc", "p2 sm"] {
+            assert!(tekst.contains(&format!("\"{actie}")), "{actie} ontbreekt");
+        }
+        assert_eq!(tekst.matches("d db").count(), 3);
+
+        let ingelezen = lees(&tekst).unwrap();
+        assert_eq!(ingelezen.acties, hand.acties);
+        assert_eq!(ingelezen.tafel, hand.tafel);
+
+        // met dezelfde kaarten en acties speelt de engine precies dezelfde hand
+        let centrale = Centrale::speel_na(&ingelezen).unwrap();
+        let nagespeeld = centrale.hand_historie(hand.spel_id, 1).unwrap();
+        assert_eq!(nagespeeld.stoelen.len(), 3);
+        assert_eq!(nagespeeld.dealer, SpelerId(0));
+        assert_eq!(
+            nagespeeld.stoelen.iter().map(|s| s.speler_id).collect_vec(),
+            [SpelerId(1), SpelerId(2), SpelerId(0)]
+        );
+        assert_eq!(
+            nagespeeld.hole_cards.iter().sorted().collect_vec(),
+            hand.hole_cards.iter().sorted().collect_vec()
+        );
+        assert_eq!(nagespeeld.acties, hand.acties);
+        assert_eq!(nagespeeld.tafel, hand.tafel);
+        // de tafel begint nu bij de small blind, dus de gerechtigden staan anders gesorteerd
+        let pot = |h: &HandHistorie| h.potten.iter().map(|p| p.chips).collect_vec();
+        assert_eq!(pot(nagespeeld), pot(&hand));
+        assert_eq!(nagespeeld.winsten, hand.winsten);
+        assert_eq!(schrijf(nagespeeld), tekst);
+    }
+
+    #[test]
+    fn lezen() {
+        let hand = lees(
+            r#"
+            # een hand uit een andere bron
+            variant = "NT"
+            antes = [0, 0]
+            blinds_or_straddles = [10, 20]
+            min_bet = 20
+            starting_stacks = [1_000, 500]
+            actions = [
+              "d dh p1 AsAh",
+              "d dh p2 7c2d",  # de slechtste hand
+              "p2 cc", "p1 cc",
+              "d db Kd9s4c",
+              "p1 cbr 40", "p2 cbr 120", "p1 f",
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            hand.stoelen
+                .iter()
+                .map(|s| (s.naam.as_str(), s.chips))
+                .collect_vec(),
+            [("p1", 1000), ("p2", 500)]
+        );
+        assert_eq!(hand.dealer, SpelerId(1));
+        // heads-up zit de dealer op de small blind
+        assert_eq!(hand.blinds, [(SpelerId(1), 10), (SpelerId(0), 20)]);
+        assert_eq!(
+            hand.acties,
+            [
+                (Straat::Preflop, SpelerId(1), Actie::Call),
+                (Straat::Preflop, SpelerId(0), Actie::Check),
+                (Straat::Flop, SpelerId(0), Actie::Bet(40)),
+                (Straat::Flop, SpelerId(1), Actie::Bet(80)),
+                (Straat::Flop, SpelerId(0), Actie::Fold),
+            ]
+        );
+        assert_eq!(hand.tafel.len(), 3);
+
+        for fout in [
+            "variant = \"FT\"",
+            "variant = \"NT\"\nstarting_stacks = [1000]\nblinds_or_straddles = [10, 20]",
+            "variant = \"NT\"\nstarting_stacks = [100, 100]\nblinds_or_straddles = [10, 20]\nactions = [\"p3 f\"]",
+            "variant = \"NT\"\nstarting_stacks = [100, 100]\nblinds_or_straddles = [10, 20]\nactions = [\"d dh p1 As\"]",
+            "variant = \"NT\"\nstarting_stacks = [100, 100]\nblinds_or_straddles = [10, 20]\nactions = [\"d dh p1 AsAh\"]",
+            "variant = \"NT",
+            "actions = [\"p1 f\"",
+        ] {
+            assert!(lees(fout).is_err(), "{fout}");
+        }
+    }
+
+    #[test]
+    fn fixed_limit() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(2)).unwrap();
+        let structuur = BetStructuur::FixedLimit {
+            kleine_bet: 20,
+            grote_bet: 40,
+            max_verhogingen: 3,
+        };
+        centrale.zet_bet_structuur(spel_id, structuur).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        for (id, actie) in [(ids[0], Actie::Bet(20)), (ids[1], Actie::Call)] {
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+
+        let tekst = schrijf(hand);
+        assert!(tekst.starts_with("variant = \"FT\""));
+        for regel in ["small_bet = 20", "big_bet = 40", "_max_verhogingen = 3"] {
+            assert!(tekst.contains(regel), "{regel} ontbreekt");
+        }
+        let ingelezen = lees(&tekst).unwrap();
+        assert_eq!(ingelezen.bet_structuur, structuur);
+        let centrale = Centrale::speel_na(&ingelezen).unwrap();
+        assert_eq!(centrale.spellen[0].bet_structuur, structuur);
+    }
+
+    #[test]
+    fn antes() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(3)).unwrap();
+        centrale.get_mut_spel(spel_id).niveau = BlindNiveau::new(25, 50, 5);
+        centrale.start_spel(spel_id).unwrap();
+        for (id, actie) in [
+            (ids[0], Actie::Call),
+            (ids[1], Actie::Call),
+            (ids[2], Actie::Check),
+        ] {
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+
+        let tekst = schrijf(hand);
+        for regel in [
+            "antes = [5, 5, 5]",
+            "blinds_or_straddles = [25, 50, 0]",
+            "min_bet = 50",
+        ] {
+            assert!(tekst.contains(regel), "{regel} ontbreekt");
+        }
+        let ingelezen = lees(&tekst).unwrap();
+        assert_eq!(ingelezen.niveau, hand.niveau);
+        let centrale = Centrale::speel_na(&ingelezen).unwrap();
+        let nagespeeld = centrale.hand_historie(spel_id, 1).unwrap();
+        assert_eq!(nagespeeld.antes.len(), 3);
+        assert_eq!(nagespeeld.acties, hand.acties);
+    }
+
+    #[test]
+    fn straddle_en_big_blind_ante() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart", "Dart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(4)).unwrap();
+        let regels = VerplichteInzetten {
+            big_blind_ante: true,
+            straddle: true,
+        };
+        centrale
+            .zet_verplichte_inzetten(spel_id, BlindNiveau::new(10, 20, 20), regels)
+            .unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        for (id, actie) in [
+            (ids[0], Actie::Call),
+            (ids[1], Actie::Fold),
+            (ids[2], Actie::Call),
+            (ids[3], Actie::Check),
+        ] {
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+
+        let tekst = schrijf(hand);
+        for regel in [
+            "antes = [0, 20, 0, 0]",
+            "blinds_or_straddles = [10, 20, 40, 0]",
+        ] {
+            assert!(tekst.contains(regel), "{regel} ontbreekt");
+        }
+        let ingelezen = lees(&tekst).unwrap();
+        assert_eq!(ingelezen.verplichte_inzetten, regels);
+        let centrale = Centrale::speel_na(&ingelezen).unwrap();
+        let nagespeeld = centrale.hand_historie(spel_id, 1).unwrap();
+        assert_eq!(nagespeeld.acties, hand.acties);
+        assert_eq!(schrijf(nagespeeld), tekst);
+
+        let ongelijk = tekst.replace("antes = [0, 20, 0, 0]", "antes = [5, 20, 0, 0]");
+        assert!(lees(&ongelijk).is_err());
+    }
+}
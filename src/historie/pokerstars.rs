@@ -0,0 +1,309 @@
+// Een hand als tekst in de stijl van de hand histories van PokerStars, die de meeste
+// pokertools kunnen inlezen. Er wordt niets gerapporteerd over tijd of rake, en elke speler
+// krijgt een regel `Dealt to` met zijn hole cards.
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use super::{HandHistorie, Straat, Verloop};
+use crate::kaart::Kaart;
+use crate::{Actie, BetStructuur};
+
+pub fn schrijf(hand: &HandHistorie) -> String {
+    let mut tekst = String::new();
+    let naam = |plaats: usize| hand.stoelen[plaats].naam.as_str();
+    let dealer = hand
+        .stoelen
+        .iter()
+        .position(|s| s.speler_id == hand.dealer)
+        .unwrap_or(0);
+
+    // het handnummer komt achter het nummer van het spel, zodat het uniek is
+    // bij fixed limit staan de kleine en de grote bet in de titel in plaats van de blinds
+    let niveau = hand.niveau;
+    let spel = match hand.bet_structuur {
+        BetStructuur::NoLimit => format!("No Limit ({}/{})", niveau.small_blind, niveau.big_blind),
+        BetStructuur::PotLimit => {
+            format!("Pot Limit ({}/{})", niveau.small_blind, niveau.big_blind)
+        }
+        BetStructuur::FixedLimit {
+            kleine_bet,
+            grote_bet,
+            ..
+        } => format!("Limit ({kleine_bet}/{grote_bet})"),
+    };
+    let _ = writeln!(
+        tekst,
+        "PokerStars Hand #{}{:06}:  Hold'em {spel}",
+        hand.spel_id.0, hand.handnummer
+    );
+    let _ = writeln!(
+        tekst,
+        "Table 'pokbotcom {}' {}-max Seat #{} is the button",
+        hand.spel_id.0,
+        hand.stoelen.len(),
+        dealer + 1
+    );
+    for (i, stoel) in hand.stoelen.iter().enumerate() {
+        let _ = writeln!(
+            tekst,
+            "Seat {}: {} ({} in chips)",
+            i + 1,
+            stoel.naam,
+            stoel.chips
+        );
+    }
+
+    let mut verloop = Verloop::new(hand);
+    for (id, chips) in &hand.antes {
+        let Some(plaats) = verloop.plaats(*id) else {
+            continue;
+        };
+        let all_in = if *chips == hand.stoelen[plaats].chips {
+            " and is all-in"
+        } else {
+            ""
+        };
+        let _ = writeln!(tekst, "{}: posts the ante {chips}{all_in}", naam(plaats));
+    }
+    for (i, (id, chips)) in hand.blinds.iter().enumerate() {
+        let Some(plaats) = verloop.plaats(*id) else {
+            continue;
+        };
+        let blind = match i {
+            0 => "small blind",
+            1 => "big blind",
+            _ => "straddle",
+        };
+        let all_in = if verloop.stacks[plaats] == 0 {
+            " and is all-in"
+        } else {
+            ""
+        };
+        let _ = writeln!(tekst, "{}: posts {blind} {chips}{all_in}", naam(plaats));
+    }
+
+    let _ = writeln!(tekst, "*** HOLE CARDS ***");
+    for (id, a, b) in &hand.hole_cards {
+        if let Some(plaats) = verloop.plaats(*id) {
+            let _ = writeln!(tekst, "Dealt to {} [{a:#} {b:#}]", naam(plaats));
+        }
+    }
+
+    let mut gefold = vec![None; hand.stoelen.len()];
+    for (straat, speler_id, actie) in &hand.acties {
+        deel_tafel(hand, &mut verloop, *straat, &mut tekst);
+        let Some(plaats) = verloop.plaats(*speler_id) else {
+            continue;
+        };
+        let zet = verloop.speel(plaats, *actie);
+        let all_in = if zet.all_in { " and is all-in" } else { "" };
+        let _ = match actie {
+            Actie::Fold => {
+                gefold[plaats] = Some(*straat);
+                writeln!(tekst, "{}: folds", naam(plaats))
+            }
+            _ if zet.verhoogd && zet.vorige_inzet == 0 => {
+                writeln!(tekst, "{}: bets {}{all_in}", naam(plaats), zet.tot)
+            }
+            _ if zet.verhoogd => writeln!(
+                tekst,
+                "{}: raises {} to {}{all_in}",
+                naam(plaats),
+                zet.tot - zet.vorige_inzet,
+                zet.tot
+            ),
+            _ if zet.betaald > 0 => {
+                writeln!(tekst, "{}: calls {}{all_in}", naam(plaats), zet.betaald)
+            }
+            _ => writeln!(tekst, "{}: checks", naam(plaats)),
+        };
+    }
+
+    if !hand.is_klaar() {
+        return tekst;
+    }
+    deel_tafel(hand, &mut verloop, Straat::River, &mut tekst);
+    if let Some((plaats, chips)) = verloop.sluit_straat() {
+        let _ = writeln!(tekst, "Uncalled bet ({chips}) returned to {}", naam(plaats));
+    }
+
+    // wie wint omdat iedereen anders gefold heeft, toont zijn hand niet
+    let showdown = !hand.showdown.is_empty();
+    if showdown {
+        let _ = writeln!(tekst, "*** SHOW DOWN ***");
+    }
+    let getoond = |plaats: usize| {
+        let id = hand.stoelen[plaats].speler_id;
+        hand.showdown.iter().any(|(s, _)| *s == id).then(|| {
+            let (_, a, b) = hand.hole_cards.iter().find(|(s, _, _)| *s == id).unwrap();
+            format!("[{a:#} {b:#}]")
+        })
+    };
+    for plaats in 0..hand.stoelen.len() {
+        if let Some(kaarten) = getoond(plaats) {
+            let _ = writeln!(tekst, "{}: shows {kaarten}", naam(plaats));
+        }
+    }
+    let gewonnen = |plaats: usize| -> u64 {
+        let id = hand.stoelen[plaats].speler_id;
+        hand.winsten
+            .iter()
+            .filter(|(s, _)| *s == id)
+            .map(|(_, chips)| chips)
+            .sum()
+    };
+    for plaats in 0..hand.stoelen.len() {
+        if gewonnen(plaats) > 0 {
+            let _ = writeln!(
+                tekst,
+                "{} collected {} from pot",
+                naam(plaats),
+                gewonnen(plaats)
+            );
+            if !showdown {
+                let _ = writeln!(tekst, "{}: doesn't show hand", naam(plaats));
+            }
+        }
+    }
+
+    let _ = writeln!(tekst, "*** SUMMARY ***");
+    let pot: u64 = hand.potten.iter().map(|p| p.chips).sum();
+    let _ = writeln!(tekst, "Total pot {pot} | Rake 0");
+    let _ = writeln!(tekst, "Board {}", kaarten(&hand.tafel));
+    let aantal = hand.stoelen.len();
+    for (plaats, gefold) in gefold.into_iter().enumerate() {
+        let rol = match (plaats + aantal - dealer) % aantal {
+            0 => " (button)",
+            1 => " (small blind)",
+            2 => " (big blind)",
+            _ => "",
+        };
+        let uitkomst = match (gefold, getoond(plaats)) {
+            (Some(Straat::Preflop), _) => "folded before Flop".to_string(),
+            (Some(straat), _) => format!("folded on the {}", straat_naam(straat)),
+            (None, Some(kaarten)) if gewonnen(plaats) > 0 => {
+                format!("showed {kaarten} and won ({})", gewonnen(plaats))
+            }
+            (None, Some(kaarten)) => format!("showed {kaarten} and lost"),
+            (None, None) if gewonnen(plaats) > 0 => format!("collected ({})", gewonnen(plaats)),
+            (None, None) => "mucked".to_string(),
+        };
+        let _ = writeln!(
+            tekst,
+            "Seat {}: {}{rol} {uitkomst}",
+            plaats + 1,
+            naam(plaats)
+        );
+    }
+    tekst
+}
+
+// Schrijft de kaarten op tafel tot en met de gegeven straat.
+fn deel_tafel(hand: &HandHistorie, verloop: &mut Verloop, tot: Straat, tekst: &mut String) {
+    while verloop.straat < tot {
+        let van = verloop.straat.aantal_kaarten();
+        if let Some((plaats, chips)) = verloop.sluit_straat() {
+            let naam = &hand.stoelen[plaats].naam;
+            let _ = writeln!(tekst, "Uncalled bet ({chips}) returned to {naam}");
+        }
+        let tot = verloop.straat.aantal_kaarten();
+        if hand.tafel.len() < tot {
+            continue;
+        }
+        let naam = straat_naam(verloop.straat).to_uppercase();
+        let _ = match van {
+            0 => writeln!(tekst, "*** {naam} *** {}", kaarten(&hand.tafel[..tot])),
+            _ => writeln!(
+                tekst,
+                "*** {naam} *** {} {}",
+                kaarten(&hand.tafel[..van]),
+                kaarten(&hand.tafel[van..tot])
+            ),
+        };
+    }
+}
+
+fn straat_naam(straat: Straat) -> &'static str {
+    match straat {
+        Straat::Preflop => "Preflop",
+        Straat::Flop => "Flop",
+        Straat::Turn => "Turn",
+        Straat::River => "River",
+    }
+}
+
+fn kaarten(kaarten: &[Kaart]) -> String {
+    format!("[{}]", kaarten.iter().map(|k| format!("{k:#}")).join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Centrale, Speler};
+
+    #[test]
+    fn hand_als_tekst() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let [id_a, id_b, id_c] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(5)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        for (id, actie) in [
+            (id_a, Actie::Bet(40)),
+            (id_b, Actie::Call),
+            (id_c, Actie::Fold),
+            (id_b, Actie::Bet(100)),
+            (id_a, Actie::Fold),
+        ] {
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+        let tekst = schrijf(hand);
+        // na de laatste fold wordt er niets meer gedeeld
+        let [a, b, c] = hand.tafel[..] else {
+            panic!("geen flop op tafel: {:?}", hand.tafel);
+        };
+        let [(_, a1, a2), (_, b1, b2), (_, c1, c2)] = hand.hole_cards[..] else {
+            panic!("niet drie keer hole cards: {:?}", hand.hole_cards);
+        };
+
+        let verwacht = format!(
+            "\
+PokerStars Hand #0000001:  Hold'em No Limit (10/20)
+Table 'pokbotcom 0' 3-max Seat #1 is the button
+Seat 1: Aart (1000 in chips)
+Seat 2: Bart (1000 in chips)
+Seat 3: Cart (1000 in chips)
+Bart: posts small blind 10
+Cart: posts big blind 20
+*** HOLE CARDS ***
+Dealt to Aart [{a1:#} {a2:#}]
+Dealt to Bart [{b1:#} {b2:#}]
+Dealt to Cart [{c1:#} {c2:#}]
+Aart: raises 40 to 60
+Bart: calls 50
+Cart: folds
+*** FLOP *** [{a:#} {b:#} {c:#}]
+Bart: bets 100
+Aart: folds
+Uncalled bet (100) returned to Bart
+Bart collected 140 from pot
+Bart: doesn't show hand
+*** SUMMARY ***
+Total pot 140 | Rake 0
+Board [{a:#} {b:#} {c:#}]
+Seat 1: Aart (button) folded on the Flop
+Seat 2: Bart (small blind) collected (140)
+Seat 3: Cart (big blind) folded before Flop
+"
+        );
+        assert_eq!(tekst, verwacht);
+    }
+}
@@ -0,0 +1,438 @@
+// Winstkansen van gekende hole cards tegen elkaar, eventueel met een deel van de tafel al
+// open en met dode kaarten die niet meer kunnen vallen.
+
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use rand::prelude::*;
+
+use super::opzoektabel::{self, CompacteKaart};
+use super::range::Range;
+use super::Kaart;
+use crate::{PokbotcomError, Tafel};
+
+// Tot zoveel mogelijke tafels rekenen we alles exact uit, daarboven simuleren we.
+pub const MAX_EXACTE_TAFELS: u64 = 500_000;
+pub const STANDAARD_SIMULATIES: usize = 100_000;
+
+// Alle waarden zijn percentages; `aandeel` is het deel van de pot dat de speler gemiddeld
+// wint, waarbij een gedeelde pot evenredig verdeeld wordt.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Equity {
+    pub winst: f64,
+    pub gelijkspel: f64,
+    pub verlies: f64,
+    pub aandeel: f64,
+}
+
+#[derive(Clone, Debug)]
+struct Teller {
+    winst: Vec<u64>,
+    gelijkspel: Vec<u64>,
+    aandeel: Vec<f64>,
+    tafels: u64,
+}
+
+impl Teller {
+    fn new(spelers: usize) -> Self {
+        Teller {
+            winst: vec![0; spelers],
+            gelijkspel: vec![0; spelers],
+            aandeel: vec![0.0; spelers],
+            tafels: 0,
+        }
+    }
+
+    fn tel(&mut self, rangen: &[u16]) {
+        let beste = *rangen.iter().max().unwrap();
+        let winnaars = rangen.iter().filter(|&&r| r == beste).count();
+
+        for (i, &rang) in rangen.iter().enumerate() {
+            if rang == beste {
+                if winnaars == 1 {
+                    self.winst[i] += 1;
+                } else {
+                    self.gelijkspel[i] += 1;
+                }
+                self.aandeel[i] += 1.0 / winnaars as f64;
+            }
+        }
+        self.tafels += 1;
+    }
+
+    fn resultaat(&self) -> Vec<Equity> {
+        let totaal = self.tafels as f64;
+        (0..self.winst.len())
+            .map(|i| Equity {
+                winst: 100.0 * self.winst[i] as f64 / totaal,
+                gelijkspel: 100.0 * self.gelijkspel[i] as f64 / totaal,
+                verlies: 100.0 * (self.tafels - self.winst[i] - self.gelijkspel[i]) as f64 / totaal,
+                aandeel: 100.0 * self.aandeel[i] / totaal,
+            })
+            .collect()
+    }
+}
+
+struct Situatie {
+    handen: Vec<[CompacteKaart; 2]>,
+    tafel: Vec<CompacteKaart>,
+    rest: Vec<CompacteKaart>,
+}
+
+impl Situatie {
+    fn new(handen: &[(Kaart, Kaart)], tafel: &Tafel, dode_kaarten: &[Kaart]) -> Result<Self> {
+        let mut tafel_kaarten = Vec::new();
+        if let Some((a, b, c)) = tafel.0 {
+            tafel_kaarten.extend([a, b, c]);
+        }
+        tafel_kaarten.extend(tafel.1);
+        tafel_kaarten.extend(tafel.2);
+
+        let bekend = handen
+            .iter()
+            .flat_map(|&(a, b)| [a, b])
+            .chain(tafel_kaarten.iter().copied())
+            .chain(dode_kaarten.iter().copied())
+            .collect_vec();
+        if let Some(kaart) = bekend.iter().duplicates().next() {
+            return Err(PokbotcomError::DubbeleKaart(*kaart).into());
+        }
+
+        let situatie = Situatie {
+            handen: handen.iter().map(|&(a, b)| [a.into(), b.into()]).collect(),
+            tafel: tafel_kaarten.into_iter().map(CompacteKaart::from).collect(),
+            rest: Kaart::maak_deck()
+                .into_iter()
+                .filter(|k| !bekend.contains(k))
+                .map(CompacteKaart::from)
+                .collect(),
+        };
+        if situatie.rest.len() < situatie.ontbrekend() {
+            bail!(
+                "Er blijven {} kaarten over, te weinig om de tafel met {} kaarten aan te vullen.",
+                situatie.rest.len(),
+                situatie.ontbrekend()
+            );
+        }
+        Ok(situatie)
+    }
+
+    fn ontbrekend(&self) -> usize {
+        5 - self.tafel.len()
+    }
+
+    fn aantal_tafels(&self) -> u64 {
+        let (n, k) = (self.rest.len() as u64, self.ontbrekend() as u64);
+        (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+    }
+
+    fn evalueer(&self, extra: &[CompacteKaart], teller: &mut Teller) {
+        self.evalueer_handen(&self.handen, extra, teller);
+    }
+
+    fn evalueer_handen(
+        &self,
+        handen: &[[CompacteKaart; 2]],
+        extra: &[CompacteKaart],
+        teller: &mut Teller,
+    ) {
+        let mut kaarten = [self.rest[0]; 7];
+        kaarten[2..2 + self.tafel.len()].copy_from_slice(&self.tafel);
+        kaarten[2 + self.tafel.len()..].copy_from_slice(extra);
+
+        let rangen = handen
+            .iter()
+            .map(|hand| {
+                kaarten[..2].copy_from_slice(hand);
+                opzoektabel::rang(&kaarten)
+            })
+            .collect_vec();
+        teller.tel(&rangen);
+    }
+}
+
+// Berekent de equity exact wanneer er niet meer dan `MAX_EXACTE_TAFELS` mogelijke tafels
+// zijn, en anders met `STANDAARD_SIMULATIES` simulaties.
+pub fn bereken_equity(
+    handen: &[(Kaart, Kaart)],
+    tafel: &Tafel,
+    dode_kaarten: &[Kaart],
+    rng_seed: Option<u64>,
+) -> Result<Vec<Equity>> {
+    controleer_aantal(handen)?;
+    let situatie = Situatie::new(handen, tafel, dode_kaarten)?;
+    if situatie.aantal_tafels() <= MAX_EXACTE_TAFELS {
+        Ok(exact(&situatie))
+    } else {
+        Ok(monte_carlo(&situatie, STANDAARD_SIMULATIES, rng_seed))
+    }
+}
+
+pub fn exacte_equity(
+    handen: &[(Kaart, Kaart)],
+    tafel: &Tafel,
+    dode_kaarten: &[Kaart],
+) -> Result<Vec<Equity>> {
+    controleer_aantal(handen)?;
+    Ok(exact(&Situatie::new(handen, tafel, dode_kaarten)?))
+}
+
+pub fn monte_carlo_equity(
+    handen: &[(Kaart, Kaart)],
+    tafel: &Tafel,
+    dode_kaarten: &[Kaart],
+    simulaties: usize,
+    rng_seed: Option<u64>,
+) -> Result<Vec<Equity>> {
+    controleer_aantal(handen)?;
+    if simulaties == 0 {
+        bail!("Er is minstens één simulatie nodig om equity te berekenen.");
+    }
+    let situatie = Situatie::new(handen, tafel, dode_kaarten)?;
+    Ok(monte_carlo(&situatie, simulaties, rng_seed))
+}
+
+fn controleer_aantal(handen: &[(Kaart, Kaart)]) -> Result<()> {
+    if handen.len() < 2 {
+        bail!("Er zijn minstens twee handen nodig om equity te berekenen.");
+    }
+    Ok(())
+}
+
+// Simuleert de equity van een gekende hand tegen de ranges van de tegenstanders. Per
+// simulatie krijgt elke tegenstander een combinatie uit zijn range die niet botst met de
+// andere kaarten.
+pub fn equity_tegen_ranges(
+    hand: (Kaart, Kaart),
+    ranges: &[Range],
+    tafel: &Tafel,
+    dode_kaarten: &[Kaart],
+    simulaties: usize,
+    rng_seed: Option<u64>,
+) -> Result<Equity> {
+    let situatie = Situatie::new(&[hand], tafel, dode_kaarten)?;
+    if situatie.rest.len() < situatie.ontbrekend() + 2 * ranges.len() {
+        bail!("Er blijven te weinig kaarten over voor de tegenstanders en de rest van de tafel.");
+    }
+    let mut bekend = vec![hand.0, hand.1];
+    if let Some((a, b, c)) = tafel.0 {
+        bekend.extend([a, b, c]);
+    }
+    bekend.extend(tafel.1);
+    bekend.extend(tafel.2);
+    bekend.extend_from_slice(dode_kaarten);
+
+    let ranges = ranges
+        .iter()
+        .map(|r| r.zonder_geblokkeerde(&bekend))
+        .collect_vec();
+    // een range met enkel gewicht 0 is even leeg als een range zonder combinaties
+    if let Some(i) = ranges
+        .iter()
+        .position(|r| r.combos().all(|(_, gewicht)| gewicht <= 0.0))
+    {
+        bail!("De range van tegenstander {i} bevat geen mogelijke combinaties meer.");
+    }
+
+    let mut rng = match rng_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut teller = Teller::new(1 + ranges.len());
+    let mut handen = Vec::with_capacity(1 + ranges.len());
+
+    'simulatie: for _ in 0..simulaties {
+        handen.clear();
+        handen.push(situatie.handen[0]);
+
+        for range in &ranges {
+            let mut pogingen = 0;
+            let (a, b) = loop {
+                let (a, b) = range.trek(&mut rng).unwrap();
+                let (a, b) = (CompacteKaart::from(a), CompacteKaart::from(b));
+                if !handen.iter().flatten().any(|k| *k == a || *k == b) {
+                    break (a, b);
+                }
+                pogingen += 1;
+                if pogingen == 100 {
+                    continue 'simulatie;
+                }
+            };
+            handen.push([a, b]);
+        }
+
+        let mut rest = situatie
+            .rest
+            .iter()
+            .filter(|k| !handen.iter().flatten().any(|h| h == *k))
+            .copied()
+            .collect_vec();
+        let (extra, _) = rest.partial_shuffle(&mut rng, situatie.ontbrekend());
+        situatie.evalueer_handen(&handen, extra, &mut teller);
+    }
+
+    if teller.tafels == 0 {
+        bail!("Er kon geen enkele simulatie uitgevoerd worden met deze ranges.");
+    }
+    Ok(teller.resultaat()[0])
+}
+
+fn exact(situatie: &Situatie) -> Vec<Equity> {
+    let mut teller = Teller::new(situatie.handen.len());
+    for extra in situatie
+        .rest
+        .iter()
+        .copied()
+        .combinations(situatie.ontbrekend())
+    {
+        situatie.evalueer(&extra, &mut teller);
+    }
+    teller.resultaat()
+}
+
+fn monte_carlo(situatie: &Situatie, simulaties: usize, rng_seed: Option<u64>) -> Vec<Equity> {
+    let mut rng = match rng_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut rest = situatie.rest.clone();
+    let mut teller = Teller::new(situatie.handen.len());
+
+    for _ in 0..simulaties {
+        let (extra, _) = rest.partial_shuffle(&mut rng, situatie.ontbrekend());
+        situatie.evalueer(extra, &mut teller);
+    }
+    teller.resultaat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kaart::{Kleur::*, Waarde::*};
+
+    fn azen_en_heren() -> [(Kaart, Kaart); 2] {
+        [
+            (Kaart::new(Schoppen, Aas), Kaart::new(Harten, Aas)),
+            (Kaart::new(Schoppen, Koning), Kaart::new(Harten, Koning)),
+        ]
+    }
+
+    #[test]
+    fn exact_op_de_turn() {
+        let tafel = (
+            Some((
+                Kaart::new(Klaveren, Tal(2)),
+                Kaart::new(Ruiten, Tal(7)),
+                Kaart::new(Harten, Tal(9)),
+            )),
+            Some(Kaart::new(Schoppen, Boer)),
+            None,
+        );
+
+        // de heren winnen enkel als een van de twee overgebleven heren valt
+        let equity = bereken_equity(&azen_en_heren(), &tafel, &[], None).unwrap();
+        assert!((equity[0].winst - 100.0 * 42.0 / 44.0).abs() < 1e-9);
+        assert!((equity[1].winst - 100.0 * 2.0 / 44.0).abs() < 1e-9);
+        assert_eq!(equity[0].gelijkspel, 0.0);
+
+        let dood = [Kaart::new(Ruiten, Koning), Kaart::new(Klaveren, Koning)];
+        let equity = exacte_equity(&azen_en_heren(), &tafel, &dood).unwrap();
+        assert_eq!(equity[0].winst, 100.0);
+        assert_eq!(equity[1].verlies, 100.0);
+    }
+
+    #[test]
+    fn gedeelde_pot() {
+        let tafel = (
+            Some((
+                Kaart::new(Klaveren, Aas),
+                Kaart::new(Klaveren, Koning),
+                Kaart::new(Klaveren, Koningin),
+            )),
+            Some(Kaart::new(Klaveren, Boer)),
+            Some(Kaart::new(Klaveren, Tal(10))),
+        );
+
+        let equity = bereken_equity(&azen_en_heren(), &tafel, &[], None).unwrap();
+        assert_eq!(equity[0].gelijkspel, 100.0);
+        assert_eq!(equity[1].aandeel, 50.0);
+    }
+
+    #[test]
+    fn monte_carlo_preflop() {
+        let equity =
+            monte_carlo_equity(&azen_en_heren(), &(None, None, None), &[], 20_000, Some(0))
+                .unwrap();
+
+        // AA tegen KK is ongeveer 82 tegen 18
+        assert!((equity[0].aandeel - 82.0).abs() < 2.0, "{equity:?}");
+        assert!((equity[1].aandeel - 18.0).abs() < 2.0, "{equity:?}");
+
+        let nog_eens =
+            monte_carlo_equity(&azen_en_heren(), &(None, None, None), &[], 20_000, Some(0))
+                .unwrap();
+        assert_eq!(equity, nog_eens);
+    }
+
+    #[test]
+    fn tegen_een_range() {
+        let (azen, heren) = (azen_en_heren()[0], azen_en_heren()[1]);
+        let range: Range = "KK".parse().unwrap();
+        let equity =
+            equity_tegen_ranges(azen, &[range], &(None, None, None), &[], 10_000, Some(0)).unwrap();
+        assert!((equity.aandeel - 82.0).abs() < 2.0, "{equity:?}");
+
+        // de enige overgebleven heren zitten in de dode kaarten
+        let range: Range = "KsKh, KdKc".parse().unwrap();
+        let dood = [heren.0, heren.1, Kaart::new(Ruiten, Koning)];
+        assert!(
+            equity_tegen_ranges(azen, &[range], &(None, None, None), &dood, 100, None).is_err()
+        );
+
+        // enkel combinaties met gewicht 0 valt niets te trekken
+        let range: Range = "AA:0, KK:0".parse().unwrap();
+        assert!(equity_tegen_ranges(azen, &[range], &(None, None, None), &[], 100, None).is_err());
+
+        // zonder simulaties is er geen equity
+        let range: Range = "KK".parse().unwrap();
+        assert!(equity_tegen_ranges(azen, &[range], &(None, None, None), &[], 0, None).is_err());
+    }
+
+    #[test]
+    fn dubbele_kaart() {
+        let dood = [Kaart::new(Schoppen, Aas)];
+        assert!(bereken_equity(&azen_en_heren(), &(None, None, None), &dood, None).is_err());
+    }
+
+    #[test]
+    fn te_weinig_handen() {
+        let tafel = (None, None, None);
+        assert!(bereken_equity(&azen_en_heren()[..1], &tafel, &[], None).is_err());
+        assert!(exacte_equity(&[], &tafel, &[]).is_err());
+        assert!(monte_carlo_equity(&azen_en_heren()[..1], &tafel, &[], 10, None).is_err());
+    }
+
+    #[test]
+    fn te_weinig_kaarten() {
+        // 23 handen en 2 dode kaarten laten nog 4 kaarten over voor een tafel van 5
+        let deck = Kaart::maak_deck();
+        let handen = deck[..46].iter().copied().tuples().collect_vec();
+        let dood = &deck[46..48];
+        let tafel = (None, None, None);
+        assert!(bereken_equity(&handen, &tafel, dood, None).is_err());
+        assert!(exacte_equity(&handen, &tafel, dood).is_err());
+        assert!(monte_carlo_equity(&handen, &tafel, dood, 10, None).is_err());
+        // met één dode kaart minder gaat het nog net
+        assert!(exacte_equity(&handen, &tafel, &dood[..1]).is_ok());
+
+        let range: Range = "KK".parse().unwrap();
+        // 24 tegenstanders hebben 48 kaarten nodig, en de tafel nog 5
+        let ranges = vec![range; 24];
+        assert!(equity_tegen_ranges(handen[0], &ranges, &tafel, &[], 10, None).is_err());
+    }
+
+    #[test]
+    fn zonder_simulaties() {
+        let tafel = (None, None, None);
+        assert!(monte_carlo_equity(&azen_en_heren(), &tafel, &[], 0, None).is_err());
+    }
+}
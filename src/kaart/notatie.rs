@@ -0,0 +1,169 @@
+// Kaarten inlezen in de gebruikelijke tekstnotatie: eerst de waarde (`A`, `K`, `Q`, `J`,
+// `T` of `10`, `9` ... `2`), dan de kleur (`s`, `h`, `d`, `c`). De symbolen die de crate
+// zelf print (`♠`, `♡`, `♢`, `♣`) worden ook aanvaard, in beide volgordes.
+
+use std::str::FromStr;
+
+use super::{Kaart, Kleur, Waarde};
+use crate::PokbotcomError;
+
+fn lees_kleur(tekst: &str) -> Option<(Kleur, usize)> {
+    let teken = tekst.chars().next()?;
+    let kleur = match teken {
+        's' | 'S' | '♠' | '♤' => Kleur::Schoppen,
+        'h' | 'H' | '♥' | '♡' => Kleur::Harten,
+        'd' | 'D' | '♦' | '♢' => Kleur::Ruiten,
+        'c' | 'C' | '♣' | '♧' => Kleur::Klaveren,
+        _ => return None,
+    };
+    Some((kleur, teken.len_utf8()))
+}
+
+pub(super) fn lees_waarde(tekst: &str) -> Option<(Waarde, usize)> {
+    if tekst.starts_with("10") {
+        return Some((Waarde::Tal(10), 2));
+    }
+
+    let waarde = match tekst.chars().next()? {
+        'a' | 'A' => Waarde::Aas,
+        'k' | 'K' => Waarde::Koning,
+        'q' | 'Q' => Waarde::Koningin,
+        'j' | 'J' => Waarde::Boer,
+        't' | 'T' => Waarde::Tal(10),
+        c @ '2'..='9' => Waarde::Tal(c as u8 - b'0'),
+        _ => return None,
+    };
+    Some((waarde, 1))
+}
+
+// Leest één kaart aan het begin van de tekst en geeft ook het aantal gelezen bytes terug.
+fn lees_kaart(tekst: &str) -> Option<(Kaart, usize)> {
+    if let Some((waarde, n)) = lees_waarde(tekst) {
+        let (kleur, m) = lees_kleur(&tekst[n..])?;
+        return Some((Kaart::new(kleur, waarde), n + m));
+    }
+
+    let (kleur, n) = lees_kleur(tekst)?;
+    let (waarde, m) = lees_waarde(&tekst[n..])?;
+    Some((Kaart::new(kleur, waarde), n + m))
+}
+
+fn is_scheiding(c: char) -> bool {
+    c.is_whitespace() || c == ','
+}
+
+// Leest een lijst kaarten zoals `"As Kd 7h"`, `"As,Kd,7h"` of `"AsKd7h"`.
+pub fn lees_kaarten(tekst: &str) -> Result<Vec<Kaart>, PokbotcomError> {
+    let mut kaarten = Vec::new();
+    let mut rest = tekst.trim_start_matches(is_scheiding);
+
+    while !rest.is_empty() {
+        let Some((kaart, n)) = lees_kaart(rest) else {
+            let fout = rest.split(is_scheiding).next().unwrap_or(rest);
+            return Err(PokbotcomError::OngeldigeKaart(fout.to_string()));
+        };
+        kaarten.push(kaart);
+        rest = rest[n..].trim_start_matches(is_scheiding);
+    }
+
+    Ok(kaarten)
+}
+
+// Schrijft kaarten in ASCII-notatie, gescheiden door spaties.
+pub fn schrijf_kaarten(kaarten: &[Kaart]) -> String {
+    kaarten
+        .iter()
+        .map(|k| format!("{k:#}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn volledig<T>(
+    tekst: &str,
+    lees: impl Fn(&str) -> Option<(T, usize)>,
+) -> Result<T, PokbotcomError> {
+    let tekst = tekst.trim();
+    match lees(tekst) {
+        Some((resultaat, n)) if n == tekst.len() => Ok(resultaat),
+        _ => Err(PokbotcomError::OngeldigeKaart(tekst.to_string())),
+    }
+}
+
+impl FromStr for Kleur {
+    type Err = PokbotcomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        volledig(s, lees_kleur)
+    }
+}
+
+impl FromStr for Waarde {
+    type Err = PokbotcomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        volledig(s, lees_waarde)
+    }
+}
+
+impl FromStr for Kaart {
+    type Err = PokbotcomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        volledig(s, lees_kaart)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kaart::{Hand, Kleur::*, Waarde::*};
+
+    #[test]
+    fn enkele_kaarten() {
+        assert_eq!("As".parse(), Ok(Kaart::new(Schoppen, Aas)));
+        assert_eq!("Td".parse(), Ok(Kaart::new(Ruiten, Tal(10))));
+        assert_eq!("10c".parse(), Ok(Kaart::new(Klaveren, Tal(10))));
+        assert_eq!("♡A".parse(), Ok(Kaart::new(Harten, Aas)));
+        assert_eq!("7♠".parse(), Ok(Kaart::new(Schoppen, Tal(7))));
+        assert_eq!("q".parse(), Ok(Koningin));
+        assert_eq!("♣".parse(), Ok(Klaveren));
+
+        assert!("1s".parse::<Kaart>().is_err());
+        assert!("As7".parse::<Kaart>().is_err());
+        assert!("Ax".parse::<Kaart>().is_err());
+    }
+
+    #[test]
+    fn lijsten() {
+        let verwacht = vec![
+            Kaart::new(Schoppen, Aas),
+            Kaart::new(Ruiten, Koning),
+            Kaart::new(Harten, Tal(7)),
+        ];
+        assert_eq!(lees_kaarten("As Kd 7h"), Ok(verwacht.clone()));
+        assert_eq!(lees_kaarten(" As, Kd,7h "), Ok(verwacht.clone()));
+        assert_eq!(lees_kaarten("AsKd7h"), Ok(verwacht.clone()));
+        assert_eq!(lees_kaarten("♠A ♢K ♡7"), Ok(verwacht.clone()));
+        assert_eq!(lees_kaarten(""), Ok(vec![]));
+
+        assert_eq!(
+            lees_kaarten("As Kx 7h"),
+            Err(PokbotcomError::OngeldigeKaart("Kx".to_string()))
+        );
+    }
+
+    #[test]
+    fn heen_en_terug() {
+        for kaart in Kaart::maak_deck() {
+            assert_eq!(format!("{kaart:#}").parse(), Ok(kaart));
+            assert_eq!(kaart.to_string().parse(), Ok(kaart));
+        }
+
+        let kaarten = lees_kaarten("Ah Kh Qh Jh Th").unwrap();
+        assert_eq!(schrijf_kaarten(&kaarten), "Ah Kh Qh Jh Th");
+
+        let hand = Hand::new(kaarten.try_into().unwrap());
+        assert_eq!(format!("{hand:#}"), "Ah Kh Qh Jh Th");
+        assert_eq!(hand.to_string(), "♡A ♡K ♡Q ♡J ♡10");
+    }
+}
@@ -0,0 +1,214 @@
+// Een snelle evaluator voor simulaties, gebaseerd op de opzoektabellen van Cactus Kev.
+// Elke hand van vijf tot zeven kaarten krijgt een rang van 1 (7-5-4-3-2 zonder flush) tot
+// 7462 (royal flush), in dezelfde volgorde als `Hand`.
+
+use std::sync::OnceLock;
+
+use itertools::Itertools;
+
+use super::{Hand, Kaart, Kleur, Waarde};
+
+const PRIEMGETALLEN: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+// Een kaart als 32 bits:
+//
+//   xxxbbbbb bbbbbbbb kkkkrrrr xxpppppp
+//
+// met `b` één bit per waarde, `k` één bit per kleur, `r` de waarde (0 voor een twee tot
+// 12 voor een aas) en `p` het priemgetal van die waarde.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CompacteKaart(u32);
+
+impl From<Kaart> for CompacteKaart {
+    fn from(kaart: Kaart) -> Self {
+        let r = (kaart.waarde.rang() - 2) as u32;
+        let kleur = match kaart.kleur {
+            Kleur::Schoppen => 0x1000,
+            Kleur::Harten => 0x2000,
+            Kleur::Ruiten => 0x4000,
+            Kleur::Klaveren => 0x8000,
+        };
+
+        CompacteKaart((1 << (16 + r)) | kleur | (r << 8) | PRIEMGETALLEN[r as usize])
+    }
+}
+
+struct Tabellen {
+    flushes: Vec<u16>,          // geïndexeerd op de bits van de vijf waarden
+    uniek: Vec<u16>,            // vijf verschillende waarden zonder flush
+    producten: Vec<(u32, u16)>, // product van de priemgetallen, gesorteerd
+}
+
+fn tabellen() -> &'static Tabellen {
+    static TABELLEN: OnceLock<Tabellen> = OnceLock::new();
+    TABELLEN.get_or_init(bouw_tabellen)
+}
+
+// We leiden de tabellen af van `Hand` zelf, zodat beide evaluators gegarandeerd dezelfde
+// volgorde hanteren: van elke mogelijke combinatie van waarden maken we één voorbeeldhand.
+fn bouw_tabellen() -> Tabellen {
+    let kaart = |rang: u8, kleur: usize| {
+        Kaart::new(Kleur::ALLE[kleur], Waarde::uit_rang(rang + 2).unwrap())
+    };
+
+    let mut voorbeelden = Vec::new();
+    for rangen in (0..13u8).combinations(5) {
+        let flush = rangen.iter().map(|&r| kaart(r, 0)).collect_vec();
+        voorbeelden.push(flush.try_into().unwrap());
+
+        let geen_flush = rangen
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| kaart(r, usize::from(i == 0)))
+            .collect_vec();
+        voorbeelden.push(geen_flush.try_into().unwrap());
+    }
+    for rangen in (0..13u8).combinations_with_replacement(5) {
+        if rangen.iter().all_equal() || rangen.iter().all_unique() {
+            continue;
+        }
+        // dezelfde waarde krijgt telkens een andere kleur
+        let kaarten = rangen
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| kaart(r, rangen[..i].iter().filter(|&&s| s == r).count()))
+            .collect_vec();
+        voorbeelden.push(kaarten.try_into().unwrap());
+    }
+
+    let mut handen: Vec<Hand> = voorbeelden.into_iter().map(Hand::new).collect();
+    handen.sort();
+    assert_eq!(handen.len(), 7462);
+
+    let mut tabellen = Tabellen {
+        flushes: vec![0; 1 << 13],
+        uniek: vec![0; 1 << 13],
+        producten: Vec::new(),
+    };
+
+    for (i, hand) in handen.iter().enumerate() {
+        let rang = i as u16 + 1;
+        let kaarten = hand.kaarten.map(CompacteKaart::from);
+        let bits = kaarten.iter().fold(0, |acc, k| acc | k.0) >> 16;
+
+        if bits.count_ones() < 5 {
+            tabellen.producten.push((priemproduct(&kaarten), rang));
+        } else if kaarten.iter().map(|k| k.0 & 0xF000).all_equal() {
+            tabellen.flushes[bits as usize] = rang;
+        } else {
+            tabellen.uniek[bits as usize] = rang;
+        }
+    }
+    tabellen.producten.sort_unstable();
+
+    tabellen
+}
+
+fn priemproduct(kaarten: &[CompacteKaart; 5]) -> u32 {
+    kaarten.iter().map(|k| k.0 & 0xFF).product()
+}
+
+pub fn rang5(kaarten: [CompacteKaart; 5]) -> u16 {
+    let tabellen = tabellen();
+    let [a, b, c, d, e] = kaarten.map(|k| k.0);
+    let bits = ((a | b | c | d | e) >> 16) as usize;
+
+    if a & b & c & d & e & 0xF000 != 0 {
+        return tabellen.flushes[bits];
+    }
+
+    match tabellen.uniek[bits] {
+        0 => {
+            let product = priemproduct(&kaarten);
+            let index = tabellen
+                .producten
+                .binary_search_by_key(&product, |(p, _)| *p)
+                .unwrap();
+            tabellen.producten[index].1
+        }
+        rang => rang,
+    }
+}
+
+// De rang van de beste hand van vijf kaarten die uit vijf tot zeven kaarten te maken valt.
+pub fn rang(kaarten: &[CompacteKaart]) -> u16 {
+    let n = kaarten.len();
+    assert!(
+        (5..=7).contains(&n),
+        "De snelle evaluator verwacht vijf tot zeven kaarten."
+    );
+
+    let mut beste = 0;
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        beste = beste.max(rang5([
+                            kaarten[a], kaarten[b], kaarten[c], kaarten[d], kaarten[e],
+                        ]));
+                    }
+                }
+            }
+        }
+    }
+    beste
+}
+
+pub fn rang_van(kaarten: &[Kaart]) -> u16 {
+    let compact = kaarten
+        .iter()
+        .map(|&k| CompacteKaart::from(k))
+        .collect_vec();
+    rang(&compact)
+}
+
+impl Hand {
+    pub fn rang(&self) -> u16 {
+        rang5(self.kaarten.map(CompacteKaart::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::kaart::evalueer_zeven;
+
+    #[test]
+    fn alle_handen() {
+        let deck = Kaart::maak_deck().map(CompacteKaart::from);
+        let mut aantallen = [0u32; 10];
+        let grenzen = [1277, 4137, 4995, 5853, 5863, 7140, 7296, 7452, 7461, 7462];
+
+        for (a, b, c, d, e) in deck.into_iter().tuple_combinations() {
+            let rang = rang5([a, b, c, d, e]);
+            aantallen[grenzen.iter().position(|&g| rang <= g).unwrap()] += 1;
+        }
+
+        assert_eq!(
+            aantallen,
+            [1_302_540, 1_098_240, 123_552, 54_912, 10_200, 5_108, 3_744, 624, 36, 4]
+        );
+    }
+
+    #[test]
+    fn zelfde_volgorde_als_hand() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut deck = Kaart::maak_deck();
+
+        for _ in 0..2000 {
+            deck.shuffle(&mut rng);
+            let a: [Kaart; 7] = deck[..7].try_into().unwrap();
+            let b: [Kaart; 7] = deck[7..14].try_into().unwrap();
+
+            assert_eq!(
+                rang_van(&a).cmp(&rang_van(&b)),
+                evalueer_zeven(&a).cmp(&evalueer_zeven(&b)),
+                "{a:?} en {b:?}"
+            );
+            assert_eq!(rang_van(&a), evalueer_zeven(&a).rang());
+        }
+    }
+}
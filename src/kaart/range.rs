@@ -0,0 +1,273 @@
+// Ranges van starthanden in de gebruikelijke notatie, bv. "AKs, QQ+, 76s-54s, KTo-K7o".
+//
+// Elk deel is een paar (`QQ`), een suited (`AKs`) of offsuit (`AKo`) hand, of allebei
+// (`AK`), eventueel gevolgd door `+` of door een reeks tot een andere hand (`QQ-99`,
+// `76s-54s`). Een specifieke combinatie schrijf je als `AsKd`. Met `:0.5` achteraan krijgt
+// een deel een gewicht.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use rand::prelude::*;
+
+use super::notatie::{lees_kaarten, lees_waarde};
+use super::{Kaart, Kleur, Waarde};
+use crate::PokbotcomError;
+
+// twee hole cards, de hoogste kaart eerst
+pub type Combo = (Kaart, Kaart);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Soort {
+    Suited,
+    Offsuit,
+    Allebei,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Range {
+    combos: BTreeMap<Combo, f64>,
+}
+
+fn combo(a: Kaart, b: Kaart) -> Combo {
+    if a > b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn combos_van(hoog: u8, laag: u8, soort: Soort) -> Vec<Combo> {
+    let waarde = |rang| Waarde::uit_rang(rang).unwrap();
+    let mut combos = Vec::new();
+
+    for (i, &k1) in Kleur::ALLE.iter().enumerate() {
+        for (j, &k2) in Kleur::ALLE.iter().enumerate() {
+            let toegelaten = if hoog == laag {
+                i < j
+            } else {
+                match soort {
+                    Soort::Suited => i == j,
+                    Soort::Offsuit => i != j,
+                    Soort::Allebei => true,
+                }
+            };
+            if toegelaten {
+                combos.push(combo(
+                    Kaart::new(k1, waarde(hoog)),
+                    Kaart::new(k2, waarde(laag)),
+                ));
+            }
+        }
+    }
+    combos
+}
+
+// Leest een handklasse zoals `QQ`, `AKs`, `AKo` of `AK`.
+fn lees_klasse(tekst: &str) -> Option<(u8, u8, Soort)> {
+    let (a, n) = lees_waarde(tekst)?;
+    let (b, m) = lees_waarde(&tekst[n..])?;
+    let soort = match &tekst[n + m..] {
+        "" => Soort::Allebei,
+        "s" if a != b => Soort::Suited,
+        "o" if a != b => Soort::Offsuit,
+        _ => return None,
+    };
+
+    let (hoog, laag) = (a.rang().max(b.rang()), a.rang().min(b.rang()));
+    Some((hoog, laag, soort))
+}
+
+fn lees_deel(tekst: &str) -> Option<Vec<Combo>> {
+    if let Ok(kaarten) = lees_kaarten(tekst) {
+        return match kaarten[..] {
+            [a, b] if a != b => Some(vec![combo(a, b)]),
+            _ => None,
+        };
+    }
+
+    let mut klassen = Vec::new();
+    if let Some(klasse) = tekst.strip_suffix('+') {
+        let (hoog, laag, soort) = lees_klasse(klasse)?;
+        if hoog == laag {
+            klassen.extend((laag..=14).map(|r| (r, r, soort)));
+        } else {
+            klassen.extend((laag..hoog).map(|r| (hoog, r, soort)));
+        }
+    } else if let Some((van, tot)) = tekst.split_once('-') {
+        let (h1, l1, soort) = lees_klasse(van)?;
+        let (h2, l2, soort2) = lees_klasse(tot)?;
+        if soort != soort2 {
+            return None;
+        }
+
+        if h1 == l1 && h2 == l2 {
+            klassen.extend((h1.min(h2)..=h1.max(h2)).map(|r| (r, r, soort)));
+        } else if h1 == h2 {
+            klassen.extend((l1.min(l2)..=l1.max(l2)).map(|r| (h1, r, soort)));
+        } else if h1 - l1 == h2 - l2 && h1 != l1 {
+            let verschil = h1 - l1;
+            klassen.extend((h1.min(h2)..=h1.max(h2)).map(|r| (r, r - verschil, soort)));
+        } else {
+            return None;
+        }
+    } else {
+        klassen.push(lees_klasse(tekst)?);
+    }
+
+    Some(
+        klassen
+            .into_iter()
+            .flat_map(|(hoog, laag, soort)| combos_van(hoog, laag, soort))
+            .collect(),
+    )
+}
+
+impl FromStr for Range {
+    type Err = PokbotcomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut range = Range::default();
+
+        for deel in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let fout = || PokbotcomError::OngeldigeRange(deel.to_string());
+
+            let (hand, gewicht) = match deel.split_once(':') {
+                Some((hand, gewicht)) => {
+                    let gewicht: f64 = gewicht.trim().parse().map_err(|_| fout())?;
+                    if !(0.0..=1.0).contains(&gewicht) {
+                        return Err(fout());
+                    }
+                    (hand.trim(), gewicht)
+                }
+                None => (deel, 1.0),
+            };
+
+            for combo in lees_deel(hand).ok_or_else(fout)? {
+                range.combos.insert(combo, gewicht);
+            }
+        }
+
+        Ok(range)
+    }
+}
+
+impl Range {
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    pub fn bevat(&self, a: Kaart, b: Kaart) -> bool {
+        self.combos.contains_key(&combo(a, b))
+    }
+
+    pub fn gewicht(&self, a: Kaart, b: Kaart) -> Option<f64> {
+        self.combos.get(&combo(a, b)).copied()
+    }
+
+    // alle combinaties met hun gewicht, in een vaste volgorde
+    pub fn combos(&self) -> impl Iterator<Item = (Combo, f64)> + '_ {
+        self.combos.iter().map(|(c, g)| (*c, *g))
+    }
+
+    // Verwijdert de combinaties die een van de gekende kaarten bevatten.
+    pub fn verwijder_geblokkeerde(&mut self, bekende_kaarten: &[Kaart]) {
+        self.combos
+            .retain(|(a, b), _| !bekende_kaarten.contains(a) && !bekende_kaarten.contains(b));
+    }
+
+    pub fn zonder_geblokkeerde(&self, bekende_kaarten: &[Kaart]) -> Range {
+        let mut range = self.clone();
+        range.verwijder_geblokkeerde(bekende_kaarten);
+        range
+    }
+
+    // Trekt een willekeurige combinatie, rekening houdend met de gewichten.
+    pub fn trek(&self, rng: &mut StdRng) -> Option<Combo> {
+        let totaal: f64 = self.combos.values().sum();
+        if totaal <= 0.0 {
+            return None;
+        }
+
+        let mut gekozen = rng.gen_range(0.0..totaal);
+        for (combo, gewicht) in &self.combos {
+            if gekozen < *gewicht {
+                return Some(*combo);
+            }
+            gekozen -= gewicht;
+        }
+        self.combos.keys().next_back().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kaart::{Kleur::*, Waarde::*};
+
+    fn aantal(range: &str) -> usize {
+        range.parse::<Range>().unwrap().len()
+    }
+
+    #[test]
+    fn notatie() {
+        assert_eq!(aantal("AA"), 6);
+        assert_eq!(aantal("AKs"), 4);
+        assert_eq!(aantal("AKo"), 12);
+        assert_eq!(aantal("AK"), 16);
+        assert_eq!(aantal("QQ+"), 18);
+        assert_eq!(aantal("QQ-99"), 24);
+        assert_eq!(aantal("ATs+"), 16);
+        assert_eq!(aantal("76s-54s"), 12);
+        assert_eq!(aantal("KTo-K7o"), 48);
+        assert_eq!(aantal("22+"), 78);
+        assert_eq!(aantal("AsKd"), 1);
+        assert_eq!(aantal("AKs, QQ+, 76s-54s"), 34);
+        // overlappende delen tellen maar een keer
+        assert_eq!(aantal("AK, AKs"), 16);
+
+        for fout in ["AAs", "AK+s", "QQ-AKs", "76s-54o", "AsAs", "AKs:2", "XY"] {
+            assert!(fout.parse::<Range>().is_err(), "{fout}");
+        }
+    }
+
+    #[test]
+    fn inhoud() {
+        let range: Range = "76s-54s".parse().unwrap();
+        assert!(range.bevat(Kaart::new(Harten, Tal(6)), Kaart::new(Harten, Tal(5))));
+        assert!(!range.bevat(Kaart::new(Harten, Tal(6)), Kaart::new(Schoppen, Tal(5))));
+        assert!(!range.bevat(Kaart::new(Harten, Tal(8)), Kaart::new(Harten, Tal(7))));
+    }
+
+    #[test]
+    fn gewichten_en_blokkers() {
+        let range: Range = "AA, AKs:0.25".parse().unwrap();
+        assert_eq!(
+            range.gewicht(Kaart::new(Harten, Aas), Kaart::new(Harten, Koning)),
+            Some(0.25)
+        );
+
+        let zonder = range.zonder_geblokkeerde(&[Kaart::new(Schoppen, Aas)]);
+        assert_eq!(zonder.len(), 3 + 3);
+
+        // met enkel gewicht 0 overblijvend valt er niets te trekken
+        let mut rng = StdRng::seed_from_u64(0);
+        let leeg: Range = "AKs:0".parse().unwrap();
+        assert_eq!(leeg.trek(&mut rng), None);
+
+        let mut azen = 0;
+        for _ in 0..1000 {
+            let (a, b) = range.trek(&mut rng).unwrap();
+            assert!(range.bevat(a, b));
+            if a.waarde == b.waarde {
+                azen += 1;
+            }
+        }
+        // 6 paren azen met gewicht 1 tegen 4 keer AKs met gewicht 0.25
+        assert!((800..950).contains(&azen), "{azen}");
+    }
+}
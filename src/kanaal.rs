@@ -0,0 +1,139 @@
+// Kanalen waarlangs `Centrale` meldingen aan spelers bezorgt. Een speler zonder kanaal krijgt
+// geen meldingen; wie ze op de terminal wil zien, geeft hem een `StdoutKanaal`.
+
+use std::fmt::Debug;
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+use crate::{PokbotcomMelding, SpelId};
+
+pub trait MeldingKanaal: Debug + Send + Sync {
+    fn stuur(&self, melding: PokbotcomMelding);
+
+    // Zoals `stuur`, met het spel waaruit de melding komt. Een kanaal dat de meldingen van
+    // verschillende spellen uit elkaar moet houden, implementeert deze methode.
+    fn stuur_voor(&self, spel_id: SpelId, melding: PokbotcomMelding) {
+        self.stuur(melding)
+    }
+}
+
+// Houdt alle meldingen bij in het geheugen, zodat tests kunnen nagaan wat een speler
+// precies ontvangen heeft. Klonen delen dezelfde wachtrij.
+#[derive(Clone, Debug, Default)]
+pub struct Wachtrij(Arc<Mutex<Vec<PokbotcomMelding>>>);
+
+impl Wachtrij {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn meldingen(&self) -> Vec<PokbotcomMelding> {
+        self.0.lock().unwrap().clone()
+    }
+
+    // geeft alle meldingen terug en maakt de wachtrij leeg
+    pub fn neem(&self) -> Vec<PokbotcomMelding> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl MeldingKanaal for Wachtrij {
+    fn stuur(&self, melding: PokbotcomMelding) {
+        self.0.lock().unwrap().push(melding);
+    }
+}
+
+// Wanneer de ontvanger verdwenen is, gaan de meldingen verloren.
+impl MeldingKanaal for Sender<PokbotcomMelding> {
+    fn stuur(&self, melding: PokbotcomMelding) {
+        let _ = self.send(melding);
+    }
+}
+
+// Print elke melding op stdout, met de naam van de speler voor wie ze bedoeld is.
+#[derive(Debug)]
+pub struct StdoutKanaal {
+    naam: String,
+}
+
+impl StdoutKanaal {
+    pub fn new(naam: impl Into<String>) -> Self {
+        StdoutKanaal { naam: naam.into() }
+    }
+}
+
+impl MeldingKanaal for StdoutKanaal {
+    fn stuur(&self, melding: PokbotcomMelding) {
+        println!("[SERV] → [{:<4.4}]: {melding:?}", self.naam);
+    }
+}
+
+// Stuurt elke melding als één regel tekst over een TCP-verbinding.
+#[derive(Debug)]
+pub struct TcpKanaal(Mutex<TcpStream>);
+
+impl TcpKanaal {
+    pub fn new(stream: TcpStream) -> Self {
+        TcpKanaal(Mutex::new(stream))
+    }
+
+    pub fn verbind(adres: &str) -> std::io::Result<Self> {
+        Ok(Self::new(TcpStream::connect(adres)?))
+    }
+}
+
+impl MeldingKanaal for TcpKanaal {
+    fn stuur(&self, melding: PokbotcomMelding) {
+        let mut stream = self.0.lock().unwrap();
+        if let Err(fout) = writeln!(stream, "{melding}") {
+            eprintln!("[SERV] kon melding niet versturen: {fout}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::kaart::notatie::lees_kaarten;
+    use crate::{Actie, LegaleActies, SpelerId};
+
+    #[test]
+    fn over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let kanaal = TcpKanaal::verbind(&listener.local_addr().unwrap().to_string()).unwrap();
+        let (ontvanger, _) = listener.accept().unwrap();
+
+        let kaarten = lees_kaarten("As Kd").unwrap();
+        kanaal.stuur(PokbotcomMelding::Hand(kaarten[0], kaarten[1]));
+        kanaal.stuur(PokbotcomMelding::SpelerActie(SpelerId(3), Actie::Bet(50)));
+        kanaal.stuur(PokbotcomMelding::AanDeBeurt(LegaleActies {
+            fold: true,
+            call: true,
+            te_callen: 20,
+            bet: true,
+            min_bet: 1,
+            max_bet: 960,
+            all_in: true,
+            ..Default::default()
+        }));
+
+        let regels = BufReader::new(ontvanger)
+            .lines()
+            .take(3)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            regels,
+            [
+                "hand As Kd",
+                "actie 3 bet 50",
+                "aan_de_beurt fold call 20 bet 1-960 all_in"
+            ]
+        );
+    }
+}
@@ -2,16 +2,22 @@
 
 use itertools::Itertools;
 use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
 
 pub mod kaart {
-    use core::cmp::Ordering::{self, Equal, Greater, Less};
+    use core::cmp::Ordering::{self, Equal};
 
     use itertools::Itertools;
     use Combinatie::*;
     use Kleur::*;
     use Waarde::*;
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub mod equity;
+    pub mod notatie;
+    pub mod opzoektabel;
+    pub mod range;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub enum Kleur {
         Klaveren,
         Schoppen,
@@ -19,8 +25,26 @@ pub mod kaart {
         Ruiten,
     }
 
+    impl Kleur {
+        pub const ALLE: [Kleur; 4] = [Klaveren, Schoppen, Harten, Ruiten];
+    }
+
+    // Met `{:#}` wordt de ASCII-notatie gebruikt (`c`, `s`, `h` en `d`).
     impl std::fmt::Display for Kleur {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if f.alternate() {
+                return write!(
+                    f,
+                    "{}",
+                    match self {
+                        Klaveren => 'c',
+                        Schoppen => 's',
+                        Harten => 'h',
+                        Ruiten => 'd',
+                    }
+                );
+            }
+
             write!(
                 f,
                 "{}",
@@ -34,7 +58,7 @@ pub mod kaart {
         }
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub enum Waarde {
         Tal(u8),
         Boer,
@@ -43,12 +67,37 @@ pub mod kaart {
         Aas,
     }
 
+    impl Waarde {
+        // numerieke rang, van 2 tot en met 14 (aas hoog)
+        pub fn rang(self) -> u8 {
+            match self {
+                Tal(a) => a,
+                Boer => 11,
+                Koningin => 12,
+                Koning => 13,
+                Aas => 14,
+            }
+        }
+
+        pub fn uit_rang(rang: u8) -> Option<Self> {
+            match rang {
+                2..=10 => Some(Tal(rang)),
+                11 => Some(Boer),
+                12 => Some(Koningin),
+                13 => Some(Koning),
+                14 => Some(Aas),
+                _ => None,
+            }
+        }
+    }
+
     impl std::fmt::Display for Waarde {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             write!(
                 f,
                 "{}",
                 match self {
+                    Tal(10) if f.alternate() => "T".to_string(),
                     Tal(a) => a.to_string(),
                     Boer => "J".to_string(),
                     Koningin => "Q".to_string(),
@@ -59,7 +108,7 @@ pub mod kaart {
         }
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub struct Kaart {
         waarde: Waarde,
         kleur: Kleur,
@@ -99,9 +148,14 @@ pub mod kaart {
         }
     }
 
+    // Met `{:#}` wordt de kaart in ASCII-notatie geschreven, bv. `Ah` in plaats van `♡A`.
     impl std::fmt::Display for Kaart {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            write!(f, "{}{}", self.kleur, self.waarde)
+            if f.alternate() {
+                write!(f, "{:#}{:#}", self.waarde, self.kleur)
+            } else {
+                write!(f, "{}{}", self.kleur, self.waarde)
+            }
         }
     }
 
@@ -119,7 +173,7 @@ pub mod kaart {
         RoyalFlush,
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[derive(Clone, Copy, Debug)]
     pub struct Hand {
         combinatie: Combinatie,
         kaarten: [Kaart; 5],
@@ -127,7 +181,7 @@ pub mod kaart {
 
     impl Hand {
         pub fn new(kaarten: [Kaart; 5]) -> Self {
-            let mut kaarten = kaarten.clone();
+            let mut kaarten = kaarten;
             let slice: &mut [Kaart] = kaarten.as_mut_slice();
             slice.sort_by(|a, b| a.cmp(b).reverse());
 
@@ -208,104 +262,116 @@ pub mod kaart {
         }
     }
 
-    impl PartialOrd for Hand {
-        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-            match self.combinatie.partial_cmp(&other.combinatie) {
-                Some(Equal) => match self.combinatie {
-                    High => Some(self.kaarten.cmp(&other.kaarten)),
-                    Paar(a) => Some(a.cmp(&match other.combinatie {
-                        Paar(b) => b,
-                        _ => unreachable!(),
-                    })),
-                    TweePaar(a, b) => {
-                        let (c, d) = match other.combinatie {
-                            TweePaar(c, d) => (c, d),
-                            _ => unreachable!(),
-                        };
-
-                        let eerste_paar_vgl = a.cmp(&c);
-
-                        Some(match eerste_paar_vgl {
-                            Equal => b.cmp(&d),
-                            _ => eerste_paar_vgl,
-                        })
-                    }
-                    ThreeOfAKind(a) => Some(a.cmp(&match other.combinatie {
-                        ThreeOfAKind(b) => b,
-                        _ => unreachable!(),
-                    })),
-                    Straight(a, mss_ace_high_a) => {
-                        let (b, mss_ace_high_b) = match other.combinatie {
-                            Straight(b, mss_ace_high_b) => (b, mss_ace_high_b),
-                            _ => unreachable!(),
-                        };
-
-                        let eerste_paar_vgl = a.cmp(&b);
-
-                        Some(match eerste_paar_vgl {
-                            Equal => {
-                                if a == Aas {
-                                    let ace_high_a = mss_ace_high_a.unwrap();
-                                    let ace_high_b = mss_ace_high_b.unwrap();
-                                    if ace_high_a && !ace_high_b {
-                                        Ordering::Greater
-                                    } else if !ace_high_a && ace_high_b {
-                                        Ordering::Less
-                                    } else {
-                                        Equal
-                                    }
-                                } else {
-                                    Equal
-                                }
-                            }
-                            _ => eerste_paar_vgl,
-                        })
-                    }
-                    Flush => todo!(),
-                    FullHouse(a, b) => {
-                        let (c, d) = match other.combinatie {
-                            TweePaar(c, d) => (c, d),
-                            _ => unreachable!(),
-                        };
-
-                        let eerste_paar_vgl = a.cmp(&c);
-
-                        Some(match eerste_paar_vgl {
-                            Equal => b.cmp(&d),
-                            _ => eerste_paar_vgl,
-                        })
-                    }
-                    FourOfAKind(a) => Some(a.cmp(&match other.combinatie {
-                        FourOfAKind(b) => b,
-                        _ => unreachable!(),
-                    })),
-                    StraightFlush(a) => {
-                        let b = match other.combinatie {
-                            StraightFlush(b) => b,
-                            _ => unreachable!(),
-                        };
-
-                        Some(if a == Aas && b != Aas {
-                            Less
-                        } else if a != Aas && b == Aas {
-                            Greater
-                        } else {
-                            a.cmp(&b)
-                        })
-                    }
-                    RoyalFlush => Some(Equal),
-                },
-                ord => return ord,
+    impl Hand {
+        pub fn combinatie(&self) -> Combinatie {
+            self.combinatie
+        }
+
+        pub fn kaarten(&self) -> [Kaart; 5] {
+            self.kaarten
+        }
+
+        fn categorie(&self) -> u8 {
+            match self.combinatie {
+                High => 0,
+                Paar(_) => 1,
+                TweePaar(..) => 2,
+                ThreeOfAKind(_) => 3,
+                Straight(..) => 4,
+                Flush => 5,
+                FullHouse(..) => 6,
+                FourOfAKind(_) => 7,
+                StraightFlush(_) => 8,
+                RoyalFlush => 9,
+            }
+        }
+
+        // De waarden in de volgorde waarin ze bij een gelijke combinatie vergeleken worden:
+        // eerst de grootste groepen (bv. het triplet van een full house), dan de kickers.
+        fn tiebreak(&self) -> [u8; 5] {
+            let mut rangen = self.kaarten.map(|k| k.waarde.rang());
+
+            if matches!(
+                self.combinatie,
+                Straight(Aas, Some(false)) | StraightFlush(Aas)
+            ) {
+                // bij een wheel (A-5) telt de aas als laagste kaart
+                return [5, 4, 3, 2, 1];
+            }
+
+            let aantal = |r: u8| rangen.iter().filter(|&&s| s == r).count();
+            let aantallen = rangen.map(aantal);
+            let mut paren: Vec<(usize, u8)> = aantallen.into_iter().zip(rangen).collect();
+            paren.sort_by(|a, b| b.cmp(a));
+
+            for (i, (_, r)) in paren.into_iter().enumerate() {
+                rangen[i] = r;
+            }
+            rangen
+        }
+
+        fn sleutel(&self) -> (u8, [u8; 5]) {
+            (self.categorie(), self.tiebreak())
+        }
+    }
+
+    impl std::fmt::Display for Hand {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for (i, kaart) in self.kaarten.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " ")?;
+                }
+                if f.alternate() {
+                    write!(f, "{kaart:#}")?;
+                } else {
+                    write!(f, "{kaart}")?;
+                }
             }
+            Ok(())
+        }
+    }
+
+    // Twee handen zijn gelijk wanneer ze even sterk zijn, ongeacht de kleuren van de kaarten.
+    impl PartialEq for Hand {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Equal
+        }
+    }
+
+    impl Eq for Hand {}
+
+    impl PartialOrd for Hand {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
         }
     }
 
     impl Ord for Hand {
         fn cmp(&self, other: &Self) -> Ordering {
-            self.partial_cmp(other).unwrap()
+            self.sleutel().cmp(&other.sleutel())
         }
     }
 
+    pub fn beste_hand(kaarten: &[Kaart]) -> Hand {
+        assert!(
+            kaarten.len() >= 5,
+            "Er zijn minstens vijf kaarten nodig om een hand te vormen."
+        );
+
+        kaarten
+            .iter()
+            .copied()
+            .tuple_combinations::<(_, _, _, _, _)>()
+            .map(|(a, b, c, d, e)| Hand::new([a, b, c, d, e]))
+            .max()
+            .unwrap()
+    }
+
+    // de twee kaarten van een speler samen met de vijf kaarten op tafel
+    pub fn evalueer_zeven(kaarten: &[Kaart; 7]) -> Hand {
+        beste_hand(kaarten)
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -400,43 +466,432 @@ pub mod kaart {
 
             // TODO: uitgebreider testen!
         }
+
+        #[test]
+        fn zeven_kaarten() {
+            let hand = evalueer_zeven(&[
+                Kaart::new(Schoppen, Waarde::Tal(2)),
+                Kaart::new(Harten, Waarde::Tal(9)),
+                Kaart::new(Klaveren, Waarde::Tal(7)),
+                Kaart::new(Ruiten, Waarde::Koning),
+                Kaart::new(Schoppen, Waarde::Tal(8)),
+                Kaart::new(Harten, Waarde::Tal(10)),
+                Kaart::new(Klaveren, Waarde::Tal(6)),
+            ]);
+            assert_eq!(hand.combinatie, Straight(Tal(10), None));
+
+            let hand = evalueer_zeven(&[
+                Kaart::new(Schoppen, Waarde::Aas),
+                Kaart::new(Harten, Waarde::Aas),
+                Kaart::new(Klaveren, Waarde::Koning),
+                Kaart::new(Ruiten, Waarde::Aas),
+                Kaart::new(Schoppen, Waarde::Tal(3)),
+                Kaart::new(Harten, Waarde::Koning),
+                Kaart::new(Klaveren, Waarde::Tal(6)),
+            ]);
+            assert_eq!(hand.combinatie, FullHouse(Aas, Koning));
+        }
+
+        #[test]
+        fn beste_hand_van_vijf() {
+            let kaarten = [
+                Kaart::new(Schoppen, Waarde::Tal(2)),
+                Kaart::new(Harten, Waarde::Tal(2)),
+                Kaart::new(Klaveren, Waarde::Tal(7)),
+                Kaart::new(Ruiten, Waarde::Koning),
+                Kaart::new(Schoppen, Waarde::Tal(8)),
+            ];
+            assert_eq!(beste_hand(&kaarten), Hand::new(kaarten));
+        }
+
+        fn hand(kaarten: [(Kleur, Waarde); 5]) -> Hand {
+            Hand::new(kaarten.map(|(kleur, waarde)| Kaart::new(kleur, waarde)))
+        }
+
+        #[test]
+        fn kickers() {
+            // paar azen met koning-kicker wint van paar azen met koningin-kicker
+            assert!(
+                hand([
+                    (Harten, Aas),
+                    (Schoppen, Aas),
+                    (Klaveren, Koning),
+                    (Ruiten, Tal(7)),
+                    (Harten, Tal(2)),
+                ]) > hand([
+                    (Klaveren, Aas),
+                    (Ruiten, Aas),
+                    (Schoppen, Koningin),
+                    (Harten, Boer),
+                    (Harten, Tal(9)),
+                ])
+            );
+
+            // twee paar: bij gelijke paren beslist de vijfde kaart
+            assert!(
+                hand([
+                    (Harten, Boer),
+                    (Schoppen, Boer),
+                    (Klaveren, Tal(4)),
+                    (Ruiten, Tal(4)),
+                    (Harten, Tal(9)),
+                ]) > hand([
+                    (Klaveren, Boer),
+                    (Ruiten, Boer),
+                    (Schoppen, Tal(4)),
+                    (Harten, Tal(4)),
+                    (Harten, Tal(8)),
+                ])
+            );
+
+            // three of a kind met kickers
+            assert!(
+                hand([
+                    (Harten, Tal(8)),
+                    (Schoppen, Tal(8)),
+                    (Klaveren, Tal(8)),
+                    (Ruiten, Aas),
+                    (Harten, Tal(3)),
+                ]) > hand([
+                    (Harten, Tal(8)),
+                    (Schoppen, Tal(8)),
+                    (Ruiten, Tal(8)),
+                    (Klaveren, Koning),
+                    (Harten, Koningin),
+                ])
+            );
+
+            // four of a kind met kicker
+            assert!(
+                hand([
+                    (Harten, Tal(5)),
+                    (Schoppen, Tal(5)),
+                    (Klaveren, Tal(5)),
+                    (Ruiten, Tal(5)),
+                    (Harten, Tal(3)),
+                ]) < hand([
+                    (Harten, Tal(5)),
+                    (Schoppen, Tal(5)),
+                    (Klaveren, Tal(5)),
+                    (Ruiten, Tal(5)),
+                    (Harten, Tal(4)),
+                ])
+            );
+        }
+
+        #[test]
+        fn flush_en_full_house() {
+            assert!(
+                hand([
+                    (Harten, Aas),
+                    (Harten, Tal(9)),
+                    (Harten, Tal(7)),
+                    (Harten, Tal(5)),
+                    (Harten, Tal(2)),
+                ]) > hand([
+                    (Schoppen, Aas),
+                    (Schoppen, Tal(9)),
+                    (Schoppen, Tal(7)),
+                    (Schoppen, Tal(4)),
+                    (Schoppen, Tal(3)),
+                ])
+            );
+
+            assert!(
+                hand([
+                    (Harten, Tal(3)),
+                    (Schoppen, Tal(3)),
+                    (Klaveren, Tal(3)),
+                    (Ruiten, Tal(2)),
+                    (Harten, Tal(2)),
+                ]) < hand([
+                    (Harten, Tal(4)),
+                    (Schoppen, Tal(4)),
+                    (Klaveren, Tal(4)),
+                    (Ruiten, Tal(2)),
+                    (Schoppen, Tal(2)),
+                ])
+            );
+
+            assert!(
+                hand([
+                    (Harten, Koning),
+                    (Schoppen, Koning),
+                    (Klaveren, Koning),
+                    (Ruiten, Tal(2)),
+                    (Harten, Tal(2)),
+                ]) < hand([
+                    (Harten, Koning),
+                    (Schoppen, Koning),
+                    (Ruiten, Koning),
+                    (Klaveren, Tal(3)),
+                    (Schoppen, Tal(3)),
+                ])
+            );
+        }
+
+        #[test]
+        fn straights_en_gelijkspel() {
+            let wheel = hand([
+                (Harten, Aas),
+                (Schoppen, Tal(2)),
+                (Klaveren, Tal(3)),
+                (Ruiten, Tal(4)),
+                (Harten, Tal(5)),
+            ]);
+            let zes_hoog = hand([
+                (Harten, Tal(6)),
+                (Schoppen, Tal(2)),
+                (Klaveren, Tal(3)),
+                (Ruiten, Tal(4)),
+                (Harten, Tal(5)),
+            ]);
+            let koning_hoog = hand([
+                (Harten, Koning),
+                (Schoppen, Koningin),
+                (Klaveren, Boer),
+                (Ruiten, Tal(10)),
+                (Harten, Tal(9)),
+            ]);
+            assert!(wheel < zes_hoog);
+            assert!(zes_hoog < koning_hoog);
+
+            // dezelfde waarden in andere kleuren zijn even sterk
+            let andere_kleuren = hand([
+                (Schoppen, Koning),
+                (Harten, Koningin),
+                (Ruiten, Boer),
+                (Klaveren, Tal(10)),
+                (Schoppen, Tal(9)),
+            ]);
+            assert_eq!(koning_hoog.cmp(&andere_kleuren), Equal);
+            assert_eq!(koning_hoog, andere_kleuren);
+
+            assert_eq!(
+                hand([
+                    (Harten, Aas),
+                    (Schoppen, Tal(9)),
+                    (Klaveren, Tal(7)),
+                    (Ruiten, Tal(4)),
+                    (Harten, Tal(3)),
+                ])
+                .cmp(&hand([
+                    (Schoppen, Aas),
+                    (Harten, Tal(9)),
+                    (Ruiten, Tal(7)),
+                    (Klaveren, Tal(4)),
+                    (Schoppen, Tal(3)),
+                ])),
+                Equal
+            );
+        }
+
+        #[test]
+        fn alle_handen() {
+            let deck = Kaart::maak_deck();
+            let mut aantallen = [0u32; 10];
+            let mut sleutels = Vec::with_capacity(2_598_960);
+
+            for (a, b, c, d, e) in deck.into_iter().tuple_combinations() {
+                let hand = Hand::new([a, b, c, d, e]);
+                aantallen[hand.categorie() as usize] += 1;
+                sleutels.push(hand.sleutel());
+            }
+
+            assert_eq!(
+                aantallen,
+                [1_302_540, 1_098_240, 123_552, 54_912, 10_200, 5_108, 3_744, 624, 36, 4]
+            );
+
+            // er bestaan precies 7462 verschillend sterke handen
+            sleutels.sort_unstable();
+            sleutels.dedup();
+            assert_eq!(sleutels.len(), 7462);
+        }
     }
 }
 
+pub mod herhaling;
+pub mod historie;
+pub mod kanaal;
+pub mod opslag;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod toernooi;
+#[cfg(feature = "serde")]
+pub mod wire;
+
 use std::{
     cell::OnceCell,
     ops::Rem,
-    sync::atomic::{AtomicU64, Ordering},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
+use crate::herhaling::{GelogdeActie, Momentopname, SpelerToestand, Uitkomst};
+use crate::historie::{HandHistorie, Stoel, Straat};
+use crate::kanaal::MeldingKanaal;
+use crate::opslag::{Opslag, Tellers};
+use crate::toernooi::{Toernooi, ToernooiId};
+
 use crate::kaart::*;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum SpelStatus {
     Wachtend,
     Lopend,
     Gestopt,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpelerId(pub u64);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpelId(pub u64);
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub struct Speler {
     pub id: OnceCell<SpelerId>,
     pub naam: String,
     pub chips: u64,
     pub hand: Option<(Kaart, Kaart)>,
     pub inzet: u64,
+    pub kanaal: Option<Arc<dyn MeldingKanaal>>, // zonder kanaal gaan meldingen verloren
 }
 
 pub const CHIPS_PER_SPELER: u64 = 1000;
 pub const SMALL_BLIND: u64 = 10;
 pub const BIG_BLIND: u64 = 20;
 
+// De bedragen van de verplichte inzetten van een hand. Wie de ante betaalt, hangt af van
+// `VerplichteInzetten`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlindNiveau {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ante: u64,
+}
+
+impl BlindNiveau {
+    pub fn new(small_blind: u64, big_blind: u64, ante: u64) -> Self {
+        BlindNiveau {
+            small_blind,
+            big_blind,
+            ante,
+        }
+    }
+
+    // Zonder big blind zou een bet van 0 chips een ronde opnieuw openen.
+    pub fn controleer(&self) -> Result<(), PokbotcomError> {
+        if self.big_blind == 0 {
+            return Err(PokbotcomError::GeenBigBlind);
+        }
+        if self.small_blind > self.big_blind {
+            return Err(PokbotcomError::SmallBlindTeGroot(*self));
+        }
+        Ok(())
+    }
+}
+
+impl Default for BlindNiveau {
+    fn default() -> Self {
+        BlindNiveau::new(SMALL_BLIND, BIG_BLIND, 0)
+    }
+}
+
+// Welke verplichte inzetten er naast de blinds zijn. Zonder big blind ante betaalt elke speler
+// de ante, voor de blinds; met big blind ante betaalt de big blind de ante alleen, na zijn
+// blind. Een straddle is een blind van twee big blinds voor de speler na de big blind, die dan
+// preflop als laatste aan de beurt is; aan een tafel van twee wordt niet gestraddled.
+//
+// Heads-up betaalt de dealer de small blind en de andere speler de big blind. De dealer is
+// preflop als eerste aan de beurt en na de flop als laatste, zoals altijd.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct VerplichteInzetten {
+    pub big_blind_ante: bool,
+    pub straddle: bool,
+}
+
+impl VerplichteInzetten {
+    pub fn met_straddle(&self, spelers: usize) -> bool {
+        self.straddle && spelers > 2
+    }
+
+    // De antes en de blinds (small blind, big blind en eventueel de straddle) als plaats aan
+    // tafel en bedrag, met `stacks` de chips van elke plaats. Wie te weinig chips heeft, zet
+    // in wat hij heeft.
+    pub fn bereken(
+        &self,
+        niveau: BlindNiveau,
+        stacks: &[u64],
+        dealer: usize,
+    ) -> (Bedragen<usize>, Bedragen<usize>) {
+        let aantal = stacks.len();
+        let mut over = stacks.to_vec();
+        let mut betaal = |plaats: usize, bedrag: u64| {
+            let chips = bedrag.min(over[plaats]);
+            over[plaats] -= chips;
+            (plaats, chips)
+        };
+
+        let mut antes = Vec::new();
+        if niveau.ante > 0 && !self.big_blind_ante {
+            antes.extend((0..aantal).map(|plaats| betaal(plaats, niveau.ante)));
+        }
+        let small_blind = Self::small_blind(aantal, dealer);
+        let big_blind = (small_blind + 1) % aantal;
+        let mut blinds = vec![
+            betaal(small_blind, niveau.small_blind),
+            betaal(big_blind, niveau.big_blind),
+        ];
+        if self.met_straddle(aantal) {
+            blinds.push(betaal((dealer + 3) % aantal, 2 * niveau.big_blind));
+        }
+        if niveau.ante > 0 && self.big_blind_ante {
+            antes.push(betaal(big_blind, niveau.ante));
+        }
+        (antes, blinds)
+    }
+
+    pub fn small_blind(spelers: usize, dealer: usize) -> usize {
+        match spelers {
+            2 => dealer,
+            _ => (dealer + 1) % spelers,
+        }
+    }
+
+    // Wie preflop als eerste aan de beurt is, als hij kan handelen: de speler na de big blind
+    // of na de straddle.
+    pub fn eerste_aan_de_beurt(&self, spelers: usize, dealer: usize) -> usize {
+        let blinds = if self.met_straddle(spelers) { 3 } else { 2 };
+        (Self::small_blind(spelers, dealer) + blinds) % spelers
+    }
+
+    // Wat er preflop gecallt moet worden.
+    pub fn preflop_inzet(&self, niveau: BlindNiveau, spelers: usize) -> u64 {
+        match self.met_straddle(spelers) {
+            true => 2 * niveau.big_blind,
+            false => niveau.big_blind,
+        }
+    }
+}
+
 impl Speler {
     pub fn new_zonder_id(naam: String) -> Self {
         Speler {
@@ -445,22 +900,20 @@ impl Speler {
             chips: CHIPS_PER_SPELER,
             hand: None,
             inzet: 0,
+            kanaal: None,
         }
     }
 
-    pub fn stuur_bericht(&self, bericht: PokbotcomMelding, in_respons: bool) {
-        println!(
-            "{}[SERV] → [{}]: {bericht:?}",
-            if in_respons { "    " } else { "" },
-            self.naam
-                .chars()
-                .chain([' '].into_iter().cycle())
-                .take(4)
-                .fold(String::new(), |mut acc, c| {
-                    acc.push(c);
-                    acc
-                })
-        )
+    pub fn met_kanaal(mut self, kanaal: impl MeldingKanaal + 'static) -> Self {
+        self.kanaal = Some(Arc::new(kanaal));
+        self
+    }
+
+    // Een speler zonder kanaal krijgt geen meldingen.
+    pub fn stuur_bericht(&self, spel_id: SpelId, bericht: PokbotcomMelding) {
+        if let Some(kanaal) = &self.kanaal {
+            kanaal.stuur_voor(spel_id, bericht);
+        }
     }
 
     pub fn zet_chips_in(&mut self, te_vorderen: u64) -> Result<()> {
@@ -472,40 +925,260 @@ impl Speler {
             Err(PokbotcomError::NietGenoegChips.into())
         }
     }
+
+    // Zet zoveel mogelijk van het bedrag in; wie te weinig chips heeft gaat all-in.
+    pub fn zet_maximaal_in(&mut self, bedrag: u64) -> u64 {
+        let ingezet = bedrag.min(self.chips);
+        self.chips -= ingezet;
+        self.inzet += ingezet;
+        ingezet
+    }
+
+    pub fn is_all_in(&self) -> bool {
+        self.hand.is_some() && self.chips == 0
+    }
+
+    pub fn kan_handelen(&self) -> bool {
+        self.hand.is_some() && self.chips > 0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pot {
+    pub chips: u64,
+    pub gerechtigden: Vec<SpelerId>, // de spelers die deze pot kunnen winnen
 }
 
+// een aantal chips per plaats aan tafel of per speler
+pub type Bedragen<P> = Vec<(P, u64)>;
+
+// flop, turn en river
+pub type Tafel = (Option<(Kaart, Kaart, Kaart)>, Option<Kaart>, Option<Kaart>);
+
 #[derive(Clone, Debug)]
 pub struct Spel {
     pub id: OnceCell<SpelId>,
     pub spelers: Vec<SpelerId>,
-    pub pot: u64,
-    pub tafel: (Option<(Kaart, Kaart, Kaart)>, Option<Kaart>, Option<Kaart>),
+    pub potten: Vec<Pot>, // de hoofdpot, gevolgd door eventuele sidepots
+    pub tafel: Tafel,
     pub huidige_dealer: usize, // index in vector van spelers
     pub aan_de_beurt: usize,   // index in vector van spelers
     pub deck: Vec<Kaart>,
     pub huidige_inzet: u64,
     pub laatste_actionabele_speler: Option<SpelerId>,
+    pub laatste_verhoging: u64, // de laatste volledige verhoging, en dus de minimale raise
+    pub gehandeld: Vec<SpelerId>, // wie sinds de laatste volledige verhoging al aan de beurt was
+    pub verhogingen: u32,       // het aantal bets en raises in deze ronde
+    pub bet_structuur: BetStructuur,
+    pub niveau: BlindNiveau,
+    pub verplichte_inzetten: VerplichteInzetten,
+    pub antes: Vec<(SpelerId, u64)>, // de antes van deze hand, tot ze in de pot gaan
+    pub toernooi: Option<ToernooiId>, // het toernooi waarvoor aan deze tafel gespeeld wordt
     pub status: SpelStatus,
-    rng: StdRng,
+    pub historie: Vec<HandHistorie>, // van elke hand die in dit spel gedeeld werd
+    pub log: Vec<GelogdeActie>,      // elke ontvangen actie, ook de geweigerde
+    seed: u64,
+    rng: ChaCha12Rng, // hetzelfde algoritme als `StdRng`, maar de toestand kan bewaard worden
 }
 
 impl Spel {
     pub fn new(toegekende_id: SpelId, rng_seed: Option<u64>) -> Self {
+        // ook zonder gegeven seed krijgt elk spel er een, zodat het nagespeeld kan worden
+        let seed = rng_seed.unwrap_or_else(rand::random);
         Spel {
             id: OnceCell::from(toegekende_id),
             spelers: Vec::new(),
-            pot: 0,
+            potten: Vec::new(),
             tafel: (None, None, None),
             huidige_dealer: 0,
             aan_de_beurt: 1,
             deck: Kaart::maak_deck().to_vec(),
             huidige_inzet: BIG_BLIND,
             laatste_actionabele_speler: None,
+            laatste_verhoging: BIG_BLIND,
+            gehandeld: Vec::new(),
+            verhogingen: 0,
+            bet_structuur: BetStructuur::NoLimit,
+            niveau: BlindNiveau::default(),
+            verplichte_inzetten: VerplichteInzetten::default(),
+            antes: Vec::new(),
+            toernooi: None,
             status: SpelStatus::Wachtend,
-            rng: match rng_seed {
-                Some(seed) => StdRng::seed_from_u64(seed),
-                None => StdRng::from_entropy(),
-            },
+            historie: Vec::new(),
+            log: Vec::new(),
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    // Alle chips in de potten, met de antes die er nog in moeten.
+    pub fn pot(&self) -> u64 {
+        let antes: u64 = self.antes.iter().map(|(_, ante)| ante).sum();
+        self.potten.iter().map(|p| p.chips).sum::<u64>() + antes
+    }
+
+    // Een spel dat bewaard werd voor er een historie bijgehouden werd, heeft er geen.
+    fn huidige_hand(&mut self) -> Option<&mut HandHistorie> {
+        self.historie.last_mut()
+    }
+
+    // Alle chips in de potten en de inzetten van deze ronde, van de spelers die meespelen.
+    fn pot_met_inzetten<'a>(&self, spelers: impl IntoIterator<Item = &'a Speler>) -> u64 {
+        let inzetten: u64 = spelers
+            .into_iter()
+            .filter(|s| s.id.get().is_some_and(|id| self.spelers.contains(id)))
+            .map(|s| s.inzet)
+            .sum();
+        self.pot() + inzetten
+    }
+
+    // Wie al gehandeld heeft sinds de laatste volledige verhoging, mag na een onvolledige
+    // all-in raise enkel nog callen of folden. Bij fixed limit kan de ronde ook al afgetopt
+    // zijn.
+    fn mag_verhogen(&self, speler_id: SpelerId) -> bool {
+        !self.gehandeld.contains(&speler_id) && !self.is_afgetopt()
+    }
+
+    fn is_afgetopt(&self) -> bool {
+        match self.bet_structuur {
+            BetStructuur::FixedLimit {
+                max_verhogingen, ..
+            } => self.verhogingen >= max_verhogingen,
+            _ => false,
+        }
+    }
+
+    // Hoeveel een speler de inzet hoogstens mag verhogen nadat hij `te_callen` gecallt heeft,
+    // met `pot` alle chips in de potten en op tafel.
+    fn max_verhoging(&self, pot: u64, te_callen: u64) -> u64 {
+        match self.bet_structuur {
+            BetStructuur::NoLimit => u64::MAX,
+            BetStructuur::PotLimit => pot + te_callen,
+            BetStructuur::FixedLimit { .. } => self
+                .bet_structuur
+                .minimale_bet(Straat::van(&self.tafel), self.niveau.big_blind),
+        }
+    }
+
+    // Of de speler de inzet met `verhoging` chips mag verhogen. Een all-in mag kleiner zijn
+    // dan de laatste verhoging, maar niet groter dan de bet structuur toelaat.
+    // Een verhoging is minstens even groot als de vorige, en altijd minstens één chip, zodat een
+    // bet van 0 de ronde nooit opnieuw opent.
+    fn minimale_verhoging(&self) -> u64 {
+        self.laatste_verhoging.max(1)
+    }
+
+    fn controleer_verhoging(
+        &self,
+        speler_id: SpelerId,
+        verhoging: u64,
+        all_in: bool,
+        pot: u64,
+        te_callen: u64,
+    ) -> Result<(), PokbotcomError> {
+        let max = self.max_verhoging(pot, te_callen);
+        if self.gehandeld.contains(&speler_id) {
+            Err(PokbotcomError::ActieNietHeropend)
+        } else if self.is_afgetopt() {
+            Err(PokbotcomError::GeenVerhogingMeer)
+        } else if !all_in && verhoging < self.minimale_verhoging() {
+            Err(PokbotcomError::TeKleineVerhoging(self.minimale_verhoging()))
+        } else if verhoging > max {
+            Err(PokbotcomError::TeGroteVerhoging(max))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Waarom de speler die aan de beurt is `actie` niet mag doen, zo precies mogelijk.
+    fn weigering(&self, speler: &Speler, actie: Actie, pot: u64) -> PokbotcomError {
+        let speler_id = *speler.id.get().unwrap();
+        let te_callen = self.huidige_inzet.saturating_sub(speler.inzet);
+        let max_bet = LegaleActies::van(self, speler, pot).max_bet;
+        let verhoging = match actie {
+            Actie::Check => return PokbotcomError::NietGenoegChips,
+            Actie::Call if te_callen == 0 => return PokbotcomError::NietsTeCallen,
+            Actie::Bet(chips) => {
+                let toegelaten = self.controleer_verhoging(speler_id, chips, false, pot, te_callen);
+                if toegelaten.is_ok() && chips > max_bet {
+                    Err(PokbotcomError::TeGroteVerhoging(max_bet))
+                } else {
+                    toegelaten
+                }
+            }
+            Actie::AllIn => {
+                let verhoging = speler.chips.saturating_sub(te_callen);
+                self.controleer_verhoging(speler_id, verhoging, true, pot, te_callen)
+            }
+            _ => Ok(()),
+        };
+        verhoging
+            .err()
+            .unwrap_or(PokbotcomError::ActieNietToegelaten(actie))
+    }
+
+    // Een volledige verhoging: de volgende raise moet minstens even groot zijn, en iedereen
+    // mag opnieuw verhogen.
+    fn verhoog(&mut self, speler_id: SpelerId, verhoging: u64) {
+        self.laatste_actionabele_speler = Some(speler_id);
+        self.laatste_verhoging = verhoging;
+        self.verhogingen += 1;
+        self.gehandeld.clear();
+    }
+
+    fn noteer_actie(&mut self, speler_id: SpelerId, actie: Actie) {
+        let straat = Straat::van(&self.tafel);
+        if let Some(hand) = self.huidige_hand() {
+            hand.acties.push((straat, speler_id, actie));
+        }
+    }
+}
+
+// Hoeveel er ingezet mag worden. Bij pot limit is een raise hoogstens de pot nadat er gecallt
+// is. Bij fixed limit is elke bet en raise precies `kleine_bet` preflop en op de flop, en
+// `grote_bet` op de turn en de river, en wordt er per ronde hoogstens `max_verhogingen` keer
+// ingezet of verhoogd; preflop telt de big blind als de eerste bet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "soort", rename_all = "snake_case")
+)]
+pub enum BetStructuur {
+    #[default]
+    NoLimit,
+    PotLimit,
+    FixedLimit {
+        kleine_bet: u64,
+        grote_bet: u64,
+        max_verhogingen: u32,
+    },
+}
+
+impl BetStructuur {
+    // de kleinste bet waarmee een ronde geopend kan worden
+    pub fn minimale_bet(&self, straat: Straat, big_blind: u64) -> u64 {
+        match (self, straat) {
+            (BetStructuur::FixedLimit { kleine_bet, .. }, Straat::Preflop | Straat::Flop) => {
+                *kleine_bet
+            }
+            (BetStructuur::FixedLimit { grote_bet, .. }, Straat::Turn | Straat::River) => {
+                *grote_bet
+            }
+            _ => big_blind,
+        }
+    }
+
+    pub fn controleer(&self) -> Result<(), PokbotcomError> {
+        match *self {
+            BetStructuur::FixedLimit { kleine_bet: 0, .. } => Err(PokbotcomError::GeenKleineBet),
+            BetStructuur::FixedLimit {
+                kleine_bet,
+                grote_bet,
+                ..
+            } if grote_bet < kleine_bet => Err(PokbotcomError::GroteBetTeKlein(*self)),
+            _ => Ok(()),
         }
     }
 }
@@ -516,12 +1189,66 @@ pub enum Actie {
     Check,
     Call,
     Bet(u64),
+    AllIn,
+}
+
+// Wat de speler die aan de beurt is, mag doen. Met `Actie::Bet(chips)` verhoogt hij de
+// huidige inzet met `chips`, waarbij `min_bet <= chips <= max_bet`; wie niet genoeg chips
+// heeft om te callen, gaat met `Actie::Call` all-in voor `te_callen`. Na een onvolledige
+// all-in raise mag wie al gehandeld had, niet meer verhogen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LegaleActies {
+    pub fold: bool,
+    pub check: bool,
+    pub call: bool,
+    pub te_callen: u64,
+    pub bet: bool,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub all_in: bool,
+}
+
+impl LegaleActies {
+    // `pot` zijn alle chips in de potten en op tafel, zie `Spel::pot_met_inzetten`.
+    fn van(spel: &Spel, speler: &Speler, pot: u64) -> Self {
+        if speler.hand.is_none() || speler.id.get() != Some(&spel.spelers[spel.aan_de_beurt]) {
+            return LegaleActies::default();
+        }
+
+        let te_callen = spel.huidige_inzet.saturating_sub(speler.inzet);
+        let max_verhoging = spel.max_verhoging(pot, te_callen);
+        let max_bet = speler.chips.saturating_sub(te_callen).min(max_verhoging);
+        let mag_verhogen = spel.mag_verhogen(*speler.id.get().unwrap());
+        LegaleActies {
+            fold: true,
+            check: te_callen == 0,
+            call: te_callen > 0 && speler.chips > 0,
+            te_callen: te_callen.min(speler.chips),
+            bet: mag_verhogen && max_bet >= spel.minimale_verhoging(),
+            min_bet: spel.minimale_verhoging(),
+            max_bet,
+            all_in: speler.chips > 0
+                && (speler.chips <= te_callen
+                    || mag_verhogen && speler.chips - te_callen <= max_verhoging),
+        }
+    }
+
+    pub fn laat_toe(&self, actie: Actie) -> bool {
+        match actie {
+            Actie::Fold => self.fold,
+            Actie::Check => self.check,
+            Actie::Call => self.call,
+            Actie::Bet(chips) => self.bet && (self.min_bet..=self.max_bet).contains(&chips),
+            Actie::AllIn => self.all_in,
+        }
+    }
 }
 
 use anyhow::Result;
 use thiserror::Error;
 
-#[derive(Debug, Error, Clone, Copy)]
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum PokbotcomError {
     #[error("Deze speler had zich al geregistreerd, met id {0:?}.")]
     SpelerAlGeregistreerd(SpelerId),
@@ -529,27 +1256,185 @@ pub enum PokbotcomError {
     NietGenoegChips,
     #[error("Het is niet jouw beurt.")]
     NietJouwBeurt,
+    #[error("De kaart {0} komt meer dan eens voor.")]
+    DubbeleKaart(Kaart),
+    #[error("\"{0}\" is geen geldige kaart.")]
+    OngeldigeKaart(String),
+    #[error("\"{0}\" is geen geldige range.")]
+    OngeldigeRange(String),
+    #[error("Er bestaat geen spel met id {0:?}.")]
+    OnbekendSpel(SpelId),
+    #[error("Er bestaat geen speler met id {0:?}.")]
+    OnbekendeSpeler(SpelerId),
+    #[error("Spel {0:?} is niet bezig.")]
+    SpelNietBezig(SpelId),
+    #[error("Een spel heeft minstens twee spelers nodig.")]
+    TeWeinigSpelers,
+    #[error("Speler {0:?} speelt niet mee in spel {1:?}.")]
+    NietInSpel(SpelerId, SpelId),
+    #[error("Ongeldig bericht: {0}")]
+    OngeldigBericht(String),
+    #[error("Versie {0} van het berichtformaat wordt niet ondersteund.")]
+    OnbekendeVersie(u32),
+    #[error("Ongeldige hand history: {0}")]
+    OngeldigeHistorie(String),
+    #[error("Een bet of raise moet de inzet met minstens {0} chips verhogen.")]
+    TeKleineVerhoging(u64),
+    #[error("Een bet of raise mag de inzet met hoogstens {0} chips verhogen.")]
+    TeGroteVerhoging(u64),
+    #[error("Na een onvolledige all-in raise mag je enkel nog callen of folden.")]
+    ActieNietHeropend,
+    #[error("Er mag deze ronde niet meer verhoogd worden.")]
+    GeenVerhogingMeer,
+    #[error("Spel {0:?} is al begonnen.")]
+    SpelAlBegonnen(SpelId),
+    #[error("Er bestaat geen toernooi met id {0:?}.")]
+    OnbekendToernooi(ToernooiId),
+    #[error("Toernooi {0:?} is al begonnen.")]
+    ToernooiAlBegonnen(ToernooiId),
+    #[error("Een toernooi heeft minstens één blindniveau nodig.")]
+    GeenBlindNiveaus,
+    #[error("De big blind moet minstens 1 chip zijn.")]
+    GeenBigBlind,
+    #[error("De small blind mag niet groter zijn dan de big blind in {0:?}.")]
+    SmallBlindTeGroot(BlindNiveau),
+    #[error("De kleine bet van fixed limit moet minstens 1 chip zijn.")]
+    GeenKleineBet,
+    #[error("De grote bet mag niet kleiner zijn dan de kleine bet in {0:?}.")]
+    GroteBetTeKlein(BetStructuur),
+    #[error("De percentages van een uitbetaling moeten samen 100 zijn.")]
+    OngeldigeUitbetaling,
+    #[error("Te veel spelers en betaalde plaatsen om de ICM-equity uit te rekenen.")]
+    IcmTeGroot,
+    #[error("Speler {0:?} komt meer dan eens voor.")]
+    DubbeleSpeler(SpelerId),
+    #[error("Speler {0:?} speelt al mee in spel {1:?}.")]
+    SpeeltAl(SpelerId, SpelId),
+    #[error("Er is niets te callen.")]
+    NietsTeCallen,
+    #[error("{0:?} is nu niet toegelaten.")]
+    ActieNietToegelaten(Actie),
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PokbotcomMelding {
     Hand(Kaart, Kaart),
     Flop(Kaart, Kaart, Kaart),
     Turn(Kaart),
     River(Kaart),
     SpelerActie(SpelerId, Actie),
-    AanDeBeurt,
+    AanDeBeurt(LegaleActies),
     RondeOver,
-    Gewonnen(Hand, SpelerId),
+    // elke winnaar met zijn hand en het gewonnen aantal chips; zonder showdown toont de winnaar
+    // zijn hand niet
+    Gewonnen(Vec<(SpelerId, Option<Hand>, u64)>),
+    Uitgeschakeld(SpelerId, usize), // de speler ligt uit het toernooi, op deze plaats
+    ToernooiUitslag(Vec<SpelerId>), // alle deelnemers, vanaf de winnaar
+    Verplaatst(SpelId),             // de speler speelt voortaan aan deze tafel van het toernooi
 }
 
-#[derive(Debug)]
+// Eén regel tekst per melding, met kaarten in ASCII-notatie, bv. `actie 3 bet 50` of
+// `gewonnen 1 Ah Kh Qh Jh Th 200`. Meerdere winnaars worden gescheiden door `;`. Na
+// `aan_de_beurt` volgen de toegelaten acties, bv. `aan_de_beurt fold call 20 bet 20-980 all_in`.
+impl std::fmt::Display for PokbotcomMelding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PokbotcomMelding::Hand(a, b) => write!(f, "hand {a:#} {b:#}"),
+            PokbotcomMelding::Flop(a, b, c) => write!(f, "flop {a:#} {b:#} {c:#}"),
+            PokbotcomMelding::Turn(k) => write!(f, "turn {k:#}"),
+            PokbotcomMelding::River(k) => write!(f, "river {k:#}"),
+            PokbotcomMelding::SpelerActie(SpelerId(id), actie) => {
+                write!(f, "actie {id} ")?;
+                match actie {
+                    Actie::Fold => write!(f, "fold"),
+                    Actie::Check => write!(f, "check"),
+                    Actie::Call => write!(f, "call"),
+                    Actie::Bet(chips) => write!(f, "bet {chips}"),
+                    Actie::AllIn => write!(f, "all_in"),
+                }
+            }
+            PokbotcomMelding::AanDeBeurt(legaal) => {
+                write!(f, "aan_de_beurt")?;
+                if legaal.fold {
+                    write!(f, " fold")?;
+                }
+                if legaal.check {
+                    write!(f, " check")?;
+                }
+                if legaal.call {
+                    write!(f, " call {}", legaal.te_callen)?;
+                }
+                if legaal.bet {
+                    write!(f, " bet {}-{}", legaal.min_bet, legaal.max_bet)?;
+                }
+                if legaal.all_in {
+                    write!(f, " all_in")?;
+                }
+                Ok(())
+            }
+            PokbotcomMelding::RondeOver => write!(f, "ronde_over"),
+            PokbotcomMelding::Gewonnen(winnaars) => {
+                write!(f, "gewonnen")?;
+                for (i, (SpelerId(id), hand, chips)) in winnaars.iter().enumerate() {
+                    let scheiding = if i == 0 { " " } else { "; " };
+                    match hand {
+                        Some(hand) => write!(f, "{scheiding}{id} {hand:#} {chips}")?,
+                        None => write!(f, "{scheiding}{id} - {chips}")?,
+                    }
+                }
+                Ok(())
+            }
+            PokbotcomMelding::Uitgeschakeld(SpelerId(id), plaats) => {
+                write!(f, "uitgeschakeld {id} {plaats}")
+            }
+            PokbotcomMelding::ToernooiUitslag(uitslag) => {
+                write!(f, "toernooi_uitslag")?;
+                for SpelerId(id) in uitslag {
+                    write!(f, " {id}")?;
+                }
+                Ok(())
+            }
+            PokbotcomMelding::Verplaatst(SpelId(id)) => write!(f, "verplaatst {id}"),
+        }
+    }
+}
+
+// Verdeelt een pot onder de spelers met de beste hand. Oneven chips gaan naar de winnaars
+// die het eerst links van de dealer zitten.
+fn verdeel_pot(
+    chips: u64,
+    mut handen: Vec<(Hand, SpelerId)>,
+    plaatsen: &[SpelerId],
+    dealer: usize,
+) -> Vec<(SpelerId, Option<Hand>, u64)> {
+    let Some(&(beste, _)) = handen.iter().max_by(|(h1, _), (h2, _)| h1.cmp(h2)) else {
+        return Vec::new();
+    };
+
+    handen.retain(|(hand, _)| *hand == beste);
+    handen.sort_by_key(|(_, id)| {
+        let plaats = plaatsen.iter().position(|s| s == id).unwrap();
+        (plaats + plaatsen.len() - dealer - 1) % plaatsen.len()
+    });
+
+    let deel = chips / handen.len() as u64;
+    let rest = chips % handen.len() as u64;
+    handen
+        .into_iter()
+        .enumerate()
+        .map(|(i, (hand, id))| (id, Some(hand), deel + u64::from((i as u64) < rest)))
+        .collect()
+}
+
+#[derive(Debug, Default)]
 pub struct Centrale {
     pub spelers: Vec<Speler>,
     pub spellen: Vec<Spel>,
+    pub toernooien: Vec<Toernooi>,
 
     pub volgende_geldige_speler_id: AtomicU64,
     pub volgende_geldige_spel_id: AtomicU64,
+    pub volgende_geldige_toernooi_id: AtomicU64,
 }
 
 impl Centrale {
@@ -557,27 +1442,102 @@ impl Centrale {
         Centrale {
             spelers: Vec::new(),
             spellen: Vec::new(),
+            toernooien: Vec::new(),
             volgende_geldige_spel_id: 0.into(),
             volgende_geldige_speler_id: 0.into(),
+            volgende_geldige_toernooi_id: 0.into(),
         }
     }
 
-    fn laad_uit_db() -> Self {
-        unimplemented!()
+    pub fn laad_uit_db(opslag: &impl Opslag) -> Result<Self> {
+        let tellers = opslag.laad_tellers()?;
+        Ok(Centrale {
+            spelers: opslag.laad_spelers()?,
+            spellen: opslag.laad_spellen()?,
+            toernooien: opslag.laad_toernooien()?,
+            volgende_geldige_speler_id: tellers.volgende_speler_id.into(),
+            volgende_geldige_spel_id: tellers.volgende_spel_id.into(),
+            volgende_geldige_toernooi_id: tellers.volgende_toernooi_id.into(),
+        })
     }
 
-    pub fn registreer_speler(&mut self, speler: Speler) -> Result<SpelerId> {
-        let geregistreerde_id = SpelerId(
-            self.volgende_geldige_speler_id
-                .fetch_add(1, Ordering::Relaxed),
-        );
-        speler
-            .id
-            .set(geregistreerde_id)
-            .map_err(|id| PokbotcomError::SpelerAlGeregistreerd(id))?;
-
-        self.spelers.push(speler);
-        Ok(geregistreerde_id)
+    // Bewaart alles in één transactie en verwijdert wat er niet meer is.
+    pub fn bewaar_in_db(&self, opslag: &mut (impl Opslag + ?Sized)) -> Result<()> {
+        let spelers = self.spelers.iter().map(|s| *s.id.get().unwrap());
+        let spellen = self.spellen.iter().map(|s| *s.id.get().unwrap());
+        let (spelers, spellen) = (spelers.collect::<Vec<_>>(), spellen.collect::<Vec<_>>());
+        let toernooien = self.toernooien.iter().map(|t| t.id).collect::<Vec<_>>();
+        opslag.in_transactie(&mut |opslag| {
+            self.bewaar_deel_in_db(opslag, &spelers, &spellen)?;
+            for toernooi in &self.toernooien {
+                opslag.bewaar_toernooi(toernooi)?;
+            }
+            opslag.houd_enkel(&spelers, &spellen, &toernooien)
+        })
+    }
+
+    // Bewaart enkel deze spelers en spellen, met de tellers: genoeg na een wijziging die
+    // niets anders raakt. Een hand aan een toernooitafel kan spelers naar andere tafels
+    // verplaatsen, dus van zo'n spel wordt het hele toernooi bewaard, met al zijn tafels en
+    // spelers.
+    pub fn bewaar_deel_in_db(
+        &self,
+        opslag: &mut (impl Opslag + ?Sized),
+        spelers: &[SpelerId],
+        spellen: &[SpelId],
+    ) -> Result<()> {
+        let mut spelers = spelers.to_vec();
+        let mut spellen = spellen.to_vec();
+        let toernooien = spellen
+            .iter()
+            .filter_map(|id| self.get_spel(*id).toernooi)
+            .unique()
+            .filter_map(|id| self.toernooi(id))
+            .collect_vec();
+        for toernooi in &toernooien {
+            spelers.extend(&toernooi.spelers);
+            spellen.extend(
+                self.spellen
+                    .iter()
+                    .filter(|s| s.toernooi == Some(toernooi.id))
+                    .map(|s| *s.id.get().unwrap()),
+            );
+        }
+        let (spelers, spellen) = (
+            spelers.into_iter().unique().collect_vec(),
+            spellen.into_iter().unique().collect_vec(),
+        );
+
+        opslag.in_transactie(&mut |opslag| {
+            for speler_id in &spelers {
+                opslag.bewaar_speler(self.get_speler(*speler_id))?;
+            }
+            for spel_id in &spellen {
+                opslag.bewaar_spel(self.get_spel(*spel_id))?;
+            }
+            for toernooi in &toernooien {
+                opslag.bewaar_toernooi(toernooi)?;
+            }
+            opslag.bewaar_tellers(Tellers {
+                volgende_speler_id: self.volgende_geldige_speler_id.load(Ordering::Relaxed),
+                volgende_spel_id: self.volgende_geldige_spel_id.load(Ordering::Relaxed),
+                volgende_toernooi_id: self.volgende_geldige_toernooi_id.load(Ordering::Relaxed),
+            })
+        })
+    }
+
+    pub fn registreer_speler(&mut self, speler: Speler) -> Result<SpelerId> {
+        let geregistreerde_id = SpelerId(
+            self.volgende_geldige_speler_id
+                .fetch_add(1, Ordering::Relaxed),
+        );
+        speler
+            .id
+            .set(geregistreerde_id)
+            .map_err(PokbotcomError::SpelerAlGeregistreerd)?;
+
+        self.spelers.push(speler);
+        Ok(geregistreerde_id)
     }
 
     fn verwijder_speler(&mut self, speler_id: SpelerId) {
@@ -617,7 +1577,12 @@ impl Centrale {
             .unwrap()
     }
 
-    pub fn maak_spel(&mut self, spelers: Vec<SpelerId>, rng_seed: Option<u64>) -> SpelId {
+    pub fn maak_spel(&mut self, spelers: Vec<SpelerId>, rng_seed: Option<u64>) -> Result<SpelId> {
+        self.controleer_spelers(&spelers, None)?;
+        Ok(self.nieuw_spel(spelers, rng_seed))
+    }
+
+    fn nieuw_spel(&mut self, spelers: Vec<SpelerId>, rng_seed: Option<u64>) -> SpelId {
         let geregistreerde_id = SpelId(
             self.volgende_geldige_spel_id
                 .fetch_add(1, Ordering::Relaxed),
@@ -628,49 +1593,224 @@ impl Centrale {
         geregistreerde_id
     }
 
-    pub fn start_spel(&mut self, spel_id: SpelId) -> Result<()> {
+    // Of de spelers samen aan een tafel kunnen: minstens twee, allemaal geregistreerd, niemand
+    // dubbel en niemand die al aan een andere tafel speelt, want een speler heeft maar één
+    // hand en één inzet tegelijk.
+    fn controleer_spelers(
+        &self,
+        spelers: &[SpelerId],
+        behalve: Option<SpelId>,
+    ) -> Result<(), PokbotcomError> {
+        if spelers.len() < 2 {
+            return Err(PokbotcomError::TeWeinigSpelers);
+        }
+        if let Some(id) = spelers
+            .iter()
+            .find(|id| !self.spelers.iter().any(|s| s.id.get() == Some(id)))
+        {
+            return Err(PokbotcomError::OnbekendeSpeler(*id));
+        }
+        if let Some(id) = spelers.iter().duplicates().next() {
+            return Err(PokbotcomError::DubbeleSpeler(*id));
+        }
+        for spel in &self.spellen {
+            let spel_id = *spel.id.get().unwrap();
+            if spel.status != SpelStatus::Lopend || Some(spel_id) == behalve {
+                continue;
+            }
+            if let Some(id) = spelers.iter().find(|id| spel.spelers.contains(id)) {
+                return Err(PokbotcomError::SpeeltAl(*id, spel_id));
+            }
+        }
+        Ok(())
+    }
+
+    // Een spel is no limit tenzij er voor de start een andere bet structuur gekozen wordt.
+    pub fn zet_bet_structuur(&mut self, spel_id: SpelId, structuur: BetStructuur) -> Result<()> {
         let spel = self
             .spellen
             .iter_mut()
             .find(|s| s.id.get().unwrap() == &spel_id)
-            .unwrap();
-        assert!(spel.spelers.len() >= 2);
+            .ok_or(PokbotcomError::OnbekendSpel(spel_id))?;
+        if spel.status != SpelStatus::Wachtend {
+            return Err(PokbotcomError::SpelAlBegonnen(spel_id).into());
+        }
+        structuur.controleer()?;
+        spel.bet_structuur = structuur;
+        Ok(())
+    }
 
-        spel.deck = Kaart::maak_deck().to_vec();
-        spel.deck.shuffle(&mut spel.rng);
+    // De blinds en antes van een spel, en wie de ante betaalt en of er gestraddled wordt.
+    // Zonder deze instelling spelen we met blinds van 10 en 20, zonder ante of straddle.
+    pub fn zet_verplichte_inzetten(
+        &mut self,
+        spel_id: SpelId,
+        niveau: BlindNiveau,
+        verplichte_inzetten: VerplichteInzetten,
+    ) -> Result<()> {
+        let spel = self
+            .spellen
+            .iter_mut()
+            .find(|s| s.id.get().unwrap() == &spel_id)
+            .ok_or(PokbotcomError::OnbekendSpel(spel_id))?;
+        if spel.status != SpelStatus::Wachtend {
+            return Err(PokbotcomError::SpelAlBegonnen(spel_id).into());
+        }
+        niveau.controleer()?;
+        spel.niveau = niveau;
+        spel.verplichte_inzetten = verplichte_inzetten;
+        Ok(())
+    }
 
+    pub fn start_spel(&mut self, spel_id: SpelId) -> Result<()> {
+        // wie intussen aan een andere tafel begonnen is, kan hier niet meer meespelen
+        self.controleer_spelers(&self.get_spel(spel_id).spelers, Some(spel_id))?;
+        let spel = self.get_mut_spel(spel_id);
         spel.status = SpelStatus::Lopend;
 
+        self.deel_hand(spel_id, None)
+    }
+
+    // Begint een nieuwe hand met een geschud deck, of met het gegeven deck dat van achteren af
+    // gedeeld wordt: deelt de hole cards, zet de blinds in en opent de historie van de hand.
+    fn deel_hand(&mut self, spel_id: SpelId, deck: Option<Vec<Kaart>>) -> Result<()> {
+        let spel = self
+            .spellen
+            .iter_mut()
+            .find(|s| s.id.get().unwrap() == &spel_id)
+            .unwrap();
+
+        spel.deck = deck.unwrap_or_else(|| {
+            let mut deck = Kaart::maak_deck().to_vec();
+            deck.shuffle(&mut spel.rng);
+            deck
+        });
+
         spel.tafel = (None, None, None);
 
-        spel.huidige_inzet = BIG_BLIND;
+        // een straddle telt als verhoging: daarna moet er met minstens twee big blinds verhoogd
+        // worden
+        let (niveau, regels, aantal) = (spel.niveau, spel.verplichte_inzetten, spel.spelers.len());
+        spel.huidige_inzet = regels.preflop_inzet(niveau, aantal);
+        spel.laatste_verhoging = spel
+            .bet_structuur
+            .minimale_bet(Straat::Preflop, spel.huidige_inzet);
+        spel.verhogingen = if regels.met_straddle(aantal) { 2 } else { 1 };
+        spel.gehandeld.clear();
+
+        let mut historie = HandHistorie {
+            handnummer: spel.historie.last().map_or(1, |h| h.handnummer + 1),
+            dealer: spel.spelers[spel.huidige_dealer],
+            bet_structuur: spel.bet_structuur,
+            niveau,
+            verplichte_inzetten: regels,
+            ..HandHistorie::leeg(spel_id)
+        };
 
-        for (lokale_id, speler_id) in spel.spelers.iter().enumerate() {
+        let stacks = spel
+            .spelers
+            .iter()
+            .map(|id| {
+                self.spelers
+                    .iter()
+                    .find(|s| s.id.get() == Some(id))
+                    .unwrap()
+                    .chips
+            })
+            .collect_vec();
+        let (antes, blinds) = regels.bereken(niveau, &stacks, spel.huidige_dealer);
+        spel.antes.clear();
+
+        for speler_id in spel.spelers.iter() {
             let speler = self
                 .spelers
                 .iter_mut()
                 .find(|s| s.id.get().unwrap() == speler_id)
                 .unwrap();
-            speler.hand = Some((spel.deck.pop().unwrap(), spel.deck.pop().unwrap()));
-            speler.stuur_bericht(
-                PokbotcomMelding::Hand(speler.hand.unwrap().0, speler.hand.unwrap().1),
-                false,
-            );
-
-            if lokale_id == (spel.huidige_dealer + 1).rem(spel.spelers.len()) {
-                speler.zet_chips_in(SMALL_BLIND)?;
-            } else if lokale_id == (spel.huidige_dealer + 2).rem(spel.spelers.len()) {
-                speler.zet_chips_in(BIG_BLIND)?;
-            } else if lokale_id == (spel.huidige_dealer + 3).rem(spel.spelers.len()) {
-                speler.stuur_bericht(PokbotcomMelding::AanDeBeurt, false);
-            }
+            historie.stoelen.push(Stoel {
+                speler_id: *speler_id,
+                naam: speler.naam.clone(),
+                chips: speler.chips,
+            });
+
+            let (a, b) = (spel.deck.pop().unwrap(), spel.deck.pop().unwrap());
+            speler.hand = Some((a, b));
+            historie.hole_cards.push((*speler_id, a, b));
+            speler.stuur_bericht(spel_id, PokbotcomMelding::Hand(a, b));
         }
 
-        spel.aan_de_beurt = (spel.huidige_dealer + 3).rem(spel.spelers.len());
+        // de antes zijn dood geld: ze tellen niet mee voor wat er gecallt moet worden, en gaan
+        // pas in de pot wanneer de inzetten van de eerste ronde verzameld worden
+        for (plaats, ante) in antes {
+            let speler_id = spel.spelers[plaats];
+            self.spelers
+                .iter_mut()
+                .find(|s| s.id.get() == Some(&speler_id))
+                .unwrap()
+                .chips -= ante;
+            spel.antes.push((speler_id, ante));
+        }
+        historie.antes = spel.antes.clone();
+        // de big blind, of de straddle, sluit de eerste ronde af: als niemand verhoogt, krijgt
+        // hij nog de keuze om te checken of te verhogen
+        spel.laatste_actionabele_speler = blinds.last().map(|&(plaats, _)| spel.spelers[plaats]);
+        for (plaats, blind) in blinds {
+            let speler_id = spel.spelers[plaats];
+            self.spelers
+                .iter_mut()
+                .find(|s| s.id.get() == Some(&speler_id))
+                .unwrap()
+                .zet_maximaal_in(blind);
+            historie.blinds.push((speler_id, blind));
+        }
+        spel.historie.push(historie);
+
+        // wie al all-in is door de blinds of de ante, wordt overgeslagen
+        let eerste = regels.eerste_aan_de_beurt(aantal, spel.huidige_dealer);
+        spel.aan_de_beurt = (0..aantal)
+            .map(|i| (eerste + i) % aantal)
+            .find(|&plaats| {
+                self.spelers
+                    .iter()
+                    .find(|s| s.id.get() == Some(&spel.spelers[plaats]))
+                    .unwrap()
+                    .kan_handelen()
+            })
+            .unwrap_or(eerste);
+
+        // als de blinds en antes iedereen all-in zetten, wordt de tafel meteen opengedraaid
+        let spel = self.get_spel(spel_id);
+        let speler = self.get_speler(spel.spelers[spel.aan_de_beurt]);
+        if !speler.kan_handelen() {
+            return self.ronde_klaar(spel_id);
+        }
+        let pot = spel.pot_met_inzetten(&self.spelers);
+        speler.stuur_bericht(
+            spel_id,
+            PokbotcomMelding::AanDeBeurt(LegaleActies::van(spel, speler, pot)),
+        );
 
         Ok(())
     }
 
+    // De historie van een hand, geteld vanaf 1. Ook de hand die nog bezig is, kan opgevraagd
+    // worden; die is dan nog niet volledig.
+    pub fn hand_historie(&self, spel_id: SpelId, handnummer: u64) -> Option<&HandHistorie> {
+        self.spellen
+            .iter()
+            .find(|s| s.id.get().unwrap() == &spel_id)?
+            .historie
+            .iter()
+            .find(|h| h.handnummer == handnummer)
+    }
+
+    pub fn hand_historieen(&self, spel_id: SpelId) -> &[HandHistorie] {
+        self.spellen
+            .iter()
+            .find(|s| s.id.get().unwrap() == &spel_id)
+            .map_or(&[], |s| &s.historie)
+    }
+
     pub fn stuur_naar_alle_spelers(&self, spel_id: SpelId, melding: PokbotcomMelding) {
         let speler_ids = &self
             .spellen
@@ -684,64 +1824,107 @@ impl Centrale {
                 .iter()
                 .find(|s| s.id.get().unwrap() == speler_id)
                 .unwrap();
-            speler.stuur_bericht(melding, true);
+            speler.stuur_bericht(spel_id, melding.clone());
         }
     }
 
+    // Verzamelt de inzetten van deze ronde in de potten. Voor elke speler die all-in ging
+    // met minder chips dan de anderen wordt een sidepot gemaakt.
     fn verzamel_pot(&mut self, spel_id: SpelId) {
         let spel = self
             .spellen
             .iter_mut()
             .find(|s| s.id.get().unwrap() == &spel_id)
             .unwrap();
+
+        let mut inzetten = Vec::new();
         for speler_id in &spel.spelers {
             let speler = self
                 .spelers
                 .iter_mut()
                 .find(|s| s.id.get().unwrap() == speler_id)
                 .unwrap();
-            spel.pot += speler.inzet;
+            inzetten.push((*speler_id, speler.inzet, speler.hand.is_some()));
             speler.inzet = 0;
         }
-    }
 
-    fn naar_volgende_gesamtronde(&mut self, spel_id: SpelId) -> Result<()> {
-        let spel = self
-            .spellen
-            .iter_mut()
-            .find(|s| s.id.get().unwrap() == &spel_id)
-            .unwrap();
+        // wie gefold heeft, kan geen enkele pot meer winnen
+        for pot in spel.potten.iter_mut() {
+            pot.gerechtigden
+                .retain(|id| inzetten.iter().any(|(s, _, in_hand)| s == id && *in_hand));
+        }
 
-        spel.huidige_dealer = (spel.huidige_dealer + 1) % spel.spelers.len();
+        // een inzet die door niemand gevolgd werd, gaat terug naar de speler
+        let mut volgorde = (0..inzetten.len()).collect_vec();
+        volgorde.sort_by_key(|&i| std::cmp::Reverse(inzetten[i].1));
+        if let [hoogste, tweede, ..] = volgorde[..] {
+            let teveel = inzetten[hoogste].1 - inzetten[tweede].1;
+            if teveel > 0 {
+                inzetten[hoogste].1 -= teveel;
+                self.spelers
+                    .iter_mut()
+                    .find(|s| s.id.get().unwrap() == &inzetten[hoogste].0)
+                    .unwrap()
+                    .chips += teveel;
+            }
+        }
 
-        spel.tafel = (None, None, None);
+        // de antes tellen mee voor de sidepots: wie enkel een deel van de ante kon betalen,
+        // maakt net als met een te kleine call enkel kans op wat iedereen tot dan inzette
+        for (speler_id, ante) in spel.antes.drain(..) {
+            if let Some((_, inzet, _)) = inzetten.iter_mut().find(|(id, _, _)| *id == speler_id) {
+                *inzet += ante;
+            }
+        }
 
-        spel.huidige_inzet = BIG_BLIND;
+        while inzetten.iter().any(|(_, inzet, _)| *inzet > 0) {
+            let all_in = |id: &SpelerId| {
+                self.spelers
+                    .iter()
+                    .find(|s| s.id.get().unwrap() == id)
+                    .unwrap()
+                    .is_all_in()
+            };
+            let niveau = inzetten
+                .iter()
+                .filter(|(id, inzet, in_hand)| *inzet > 0 && *in_hand && all_in(id))
+                .map(|(_, inzet, _)| *inzet)
+                .min()
+                .unwrap_or_else(|| inzetten.iter().map(|(_, inzet, _)| *inzet).max().unwrap());
+
+            let mut chips = 0;
+            let mut gerechtigden = Vec::new();
+            for (id, inzet, in_hand) in inzetten.iter_mut() {
+                let bijdrage = (*inzet).min(niveau);
+                chips += bijdrage;
+                *inzet -= bijdrage;
+                // met een big blind ante zet de big blind meer in dan wie hem callt
+                if *in_hand && (bijdrage == niveau || !all_in(id)) {
+                    gerechtigden.push(*id);
+                }
+            }
 
-        for (lokale_id, speler_id) in spel.spelers.iter().enumerate() {
-            let speler = self
-                .spelers
-                .iter_mut()
-                .find(|s| s.id.get().unwrap() == speler_id)
-                .unwrap();
-            speler.hand = Some((spel.deck.pop().unwrap(), spel.deck.pop().unwrap()));
-            speler.stuur_bericht(
-                PokbotcomMelding::Hand(speler.hand.unwrap().0, speler.hand.unwrap().1),
-                false,
-            );
+            match spel.potten.last_mut() {
+                Some(pot) if pot.gerechtigden == gerechtigden => pot.chips += chips,
+                _ => spel.potten.push(Pot {
+                    chips,
+                    gerechtigden,
+                }),
+            }
+        }
+    }
 
-            if lokale_id == (spel.huidige_dealer + 1).rem(spel.spelers.len()) {
-                speler.zet_chips_in(SMALL_BLIND)?;
-            } else if lokale_id == (spel.huidige_dealer + 2).rem(spel.spelers.len()) {
-                speler.zet_chips_in(BIG_BLIND)?;
-            } else if lokale_id == (spel.huidige_dealer + 3).rem(spel.spelers.len()) {
-                speler.stuur_bericht(PokbotcomMelding::AanDeBeurt, false);
+    fn naar_volgende_gesamtronde(&mut self, spel_id: SpelId) -> Result<()> {
+        if let Some(toernooi_id) = self.get_spel(spel_id).toernooi {
+            if !self.na_toernooi_hand(toernooi_id, spel_id)? {
+                return Ok(());
             }
         }
 
-        spel.aan_de_beurt = (spel.huidige_dealer + 3).rem(spel.spelers.len());
+        let spel = self.get_mut_spel(spel_id);
+        spel.huidige_dealer = (spel.huidige_dealer + 1) % spel.spelers.len();
 
-        Ok(())
+        self.deel_hand(spel_id, None)
     }
 
     pub fn ronde_klaar(&mut self, spel_id: SpelId) -> Result<()> {
@@ -756,8 +1939,42 @@ impl Centrale {
 
         spel.laatste_actionabele_speler = None;
         spel.huidige_inzet = 0;
+        spel.verhogingen = 0;
+        spel.gehandeld.clear();
+
+        let in_hand = self
+            .spelers
+            .iter()
+            .filter(|s| spel.spelers.contains(s.id.get().unwrap()) && s.hand.is_some())
+            .map(|s| *s.id.get().unwrap())
+            .collect_vec();
 
         let melding = match spel.tafel {
+            // wie als enige overblijft, wint alle potten zonder zijn hand te tonen
+            _ if in_hand.len() == 1 => {
+                let winnaar = in_hand[0];
+                let potten = spel.potten.clone();
+                let verdeling = spel
+                    .potten
+                    .drain(..)
+                    .map(|pot| (winnaar, None, pot.chips))
+                    .collect_vec();
+                self.spelers
+                    .iter_mut()
+                    .find(|s| s.id.get() == Some(&winnaar))
+                    .unwrap()
+                    .chips += verdeling.iter().map(|(_, _, chips)| chips).sum::<u64>();
+
+                if let Some(hand) = spel.huidige_hand() {
+                    hand.potten = potten;
+                    hand.winsten = verdeling
+                        .iter()
+                        .map(|(id, _, chips)| (*id, *chips))
+                        .collect();
+                }
+
+                PokbotcomMelding::Gewonnen(verdeling)
+            }
             (None, None, None) => {
                 let flop = (
                     spel.deck.pop().unwrap(),
@@ -766,107 +1983,205 @@ impl Centrale {
                 );
 
                 spel.tafel.0 = Some(flop);
+                if let Some(hand) = spel.huidige_hand() {
+                    hand.tafel.extend([flop.0, flop.1, flop.2]);
+                }
                 PokbotcomMelding::Flop(flop.0, flop.1, flop.2)
             }
             (Some(_), None, None) => {
                 let turn = spel.deck.pop().unwrap();
 
                 spel.tafel.1 = Some(turn);
+                if let Some(hand) = spel.huidige_hand() {
+                    hand.tafel.push(turn);
+                }
                 PokbotcomMelding::Turn(turn)
             }
             (Some(_), Some(_), None) => {
                 let river = spel.deck.pop().unwrap();
 
                 spel.tafel.2 = Some(river);
+                if let Some(hand) = spel.huidige_hand() {
+                    hand.tafel.push(river);
+                }
                 PokbotcomMelding::River(river)
             }
             (Some(_), Some(_), Some(_)) => {
                 // spelletje klaar
 
-                let tafel = vec![
+                let tafel = [
                     spel.tafel.0.unwrap().0,
                     spel.tafel.0.unwrap().1,
                     spel.tafel.0.unwrap().2,
                     spel.tafel.1.unwrap(),
                     spel.tafel.2.unwrap(),
                 ];
-                let deelnemende_handen = self
+                let handen = self
                     .spelers
                     .iter()
                     .filter(|s| spel.spelers.contains(s.id.get().unwrap()))
                     .filter(|s| s.hand.is_some())
-                    .map(|s| (s.hand.unwrap(), *s.id.get().unwrap()));
-
-                let mut beste_handen = Vec::new();
-
-                for hand_en_speler in deelnemende_handen {
-                    let hand_iter = [hand_en_speler.0 .0, hand_en_speler.0 .1].into_iter();
-                    for combinatie in tafel
-                        .clone()
-                        .into_iter()
-                        .chain(hand_iter)
-                        .tuple_combinations::<(_, _, _, _, _)>()
-                    {
-                        let array: [Kaart; 5] = [
-                            combinatie.0,
-                            combinatie.1,
-                            combinatie.2,
-                            combinatie.3,
-                            combinatie.4,
-                        ];
-                        // TODO: check of de tafel de winnende hand beet heeft
-                        let berekende_hand = Hand::new(array);
-                        beste_handen.push((berekende_hand, hand_en_speler.1));
-                    }
+                    .map(|s| {
+                        let (a, b) = s.hand.unwrap();
+                        let zeven = [tafel[0], tafel[1], tafel[2], tafel[3], tafel[4], a, b];
+                        (evalueer_zeven(&zeven), *s.id.get().unwrap())
+                    })
+                    .collect_vec();
+
+                let potten = spel.potten.clone();
+
+                // elke pot gaat naar de beste hand onder de spelers die hem kunnen winnen
+                let mut verdeling = Vec::new();
+                for pot in spel.potten.drain(..) {
+                    let kandidaten = handen
+                        .iter()
+                        .filter(|(_, id)| pot.gerechtigden.contains(id))
+                        .copied()
+                        .collect_vec();
+                    verdeling.extend(verdeel_pot(
+                        pot.chips,
+                        kandidaten,
+                        &spel.spelers,
+                        spel.huidige_dealer,
+                    ));
                 }
 
-                beste_handen.sort_by(|(h1, _), (h2, _)| h1.cmp(h2).reverse());
+                for (id, _, chips) in &verdeling {
+                    let winnaar = self
+                        .spelers
+                        .iter_mut()
+                        .find(|s| s.id.get().unwrap() == id)
+                        .unwrap();
+                    winnaar.chips += chips;
+                }
 
-                let winnaar = self
-                    .spelers
-                    .iter_mut()
-                    .find(|s| s.id.get().unwrap() == &beste_handen[0].1)
-                    .unwrap();
-                dbg!(winnaar.chips);
-                dbg!(spel.pot);
-                winnaar.chips += spel.pot;
-                dbg!(winnaar.chips);
-                spel.pot = 0;
+                if let Some(hand) = spel.huidige_hand() {
+                    hand.showdown = handen.iter().map(|(hand, id)| (*id, *hand)).collect();
+                    hand.potten = potten;
+                    hand.winsten = verdeling
+                        .iter()
+                        .map(|(id, _, chips)| (*id, *chips))
+                        .collect();
+                }
 
-                PokbotcomMelding::Gewonnen(beste_handen[0].0, beste_handen[0].1)
+                PokbotcomMelding::Gewonnen(verdeling)
             }
             _ => unreachable!(),
         };
+        spel.laatste_verhoging = spel
+            .bet_structuur
+            .minimale_bet(Straat::van(&spel.tafel), spel.niveau.big_blind);
 
+        let aantal_plaatsen = spel.spelers.len();
         spel.aan_de_beurt = spel.huidige_dealer;
-        loop {
-            spel.aan_de_beurt = (spel.aan_de_beurt + 1) % spel.spelers.len();
+        for _ in 0..aantal_plaatsen {
+            spel.aan_de_beurt = (spel.aan_de_beurt + 1) % aantal_plaatsen;
             if self
                 .spelers
                 .iter()
                 .find(|s| s.id.get().unwrap() == &spel.spelers[spel.aan_de_beurt])
                 .unwrap()
-                .hand
-                .is_some()
+                .kan_handelen()
             {
                 break;
             }
         }
 
+        let kunnen_handelen = self
+            .spelers
+            .iter()
+            .filter(|s| spel.spelers.contains(s.id.get().unwrap()))
+            .filter(|s| s.kan_handelen())
+            .count();
+
+        let gewonnen = matches!(melding, PokbotcomMelding::Gewonnen(..));
         self.stuur_naar_alle_spelers(spel_id, melding);
-        if matches!(melding, PokbotcomMelding::Gewonnen(..)) {
+        if gewonnen {
             self.naar_volgende_gesamtronde(spel_id)?;
+        } else if kunnen_handelen < 2 {
+            // niemand kan nog inzetten: de rest van de tafel wordt meteen opengedraaid
+            return self.ronde_klaar(spel_id);
+        } else {
+            let spel = self.get_spel(spel_id);
+            let speler = self.get_speler(spel.spelers[spel.aan_de_beurt]);
+            let pot = spel.pot_met_inzetten(&self.spelers);
+            speler.stuur_bericht(
+                spel_id,
+                PokbotcomMelding::AanDeBeurt(LegaleActies::van(spel, speler, pot)),
+            );
         }
 
         Ok(())
     }
 
+    // Verwerkt een actie en schrijft ze, met de uitkomst, in de log van het spel.
     pub fn ontvang_actie(
         &mut self,
         spel_id: SpelId,
         speler_id: SpelerId,
         actie: Actie,
     ) -> Result<()> {
+        let res = self.verwerk_actie(spel_id, speler_id, actie);
+        let uitkomst = Uitkomst {
+            fout: res.as_ref().err().map(|fout| fout.to_string()),
+            toestand: self.momentopname(spel_id),
+        };
+        self.get_mut_spel(spel_id).log.push(GelogdeActie {
+            speler_id,
+            actie,
+            uitkomst: Some(uitkomst),
+        });
+        res
+    }
+
+    // Wat de speler nu mag doen in het spel; niets als hij niet aan de beurt is.
+    pub fn legale_acties(&self, spel_id: SpelId, speler_id: SpelerId) -> Result<LegaleActies> {
+        let spel = self
+            .spellen
+            .iter()
+            .find(|s| s.id.get().unwrap() == &spel_id)
+            .ok_or(PokbotcomError::OnbekendSpel(spel_id))?;
+        if !spel.spelers.contains(&speler_id) {
+            return Err(PokbotcomError::NietInSpel(speler_id, spel_id).into());
+        }
+        if spel.status != SpelStatus::Lopend {
+            return Err(PokbotcomError::SpelNietBezig(spel_id).into());
+        }
+        let pot = spel.pot_met_inzetten(&self.spelers);
+        Ok(LegaleActies::van(spel, self.get_speler(speler_id), pot))
+    }
+
+    // Alles aan een spel dat een actie kan veranderen, behalve het deck zelf.
+    pub fn momentopname(&self, spel_id: SpelId) -> Momentopname {
+        let spel = self.get_spel(spel_id);
+        Momentopname {
+            spelers: spel
+                .spelers
+                .iter()
+                .map(|id| {
+                    let speler = self.get_speler(*id);
+                    SpelerToestand {
+                        speler_id: *id,
+                        chips: speler.chips,
+                        inzet: speler.inzet,
+                        hand: speler.hand,
+                    }
+                })
+                .collect(),
+            potten: spel.potten.clone(),
+            tafel: spel.tafel,
+            huidige_dealer: spel.huidige_dealer,
+            aan_de_beurt: spel.aan_de_beurt,
+            kaarten_in_deck: spel.deck.len(),
+            huidige_inzet: spel.huidige_inzet,
+            laatste_actionabele_speler: spel.laatste_actionabele_speler,
+            laatste_verhoging: spel.laatste_verhoging,
+            verhogingen: spel.verhogingen,
+            status: spel.status,
+        }
+    }
+
+    fn verwerk_actie(&mut self, spel_id: SpelId, speler_id: SpelerId, actie: Actie) -> Result<()> {
         let spel = self
             .spellen
             .iter_mut()
@@ -884,25 +2199,16 @@ impl Centrale {
             }
         }
 
-        let speler = uninit_speler.expect(&format!(
-            "Speler met id {speler_id:?} niet in spel {spel_id:?} gevonden."
-        ));
-
-        println!(
-            "[{}] → [SERV]: {actie:?}",
-            speler
-                .naam
-                .chars()
-                .chain([' '].into_iter().cycle())
-                .take(4)
-                .fold(String::new(), |mut acc, c| {
-                    acc.push(c);
-                    acc
-                })
-        );
+        let speler = uninit_speler.unwrap_or_else(|| {
+            panic!("Speler met id {speler_id:?} niet in spel {spel_id:?} gevonden.")
+        });
 
+        let te_callen = spel.huidige_inzet.saturating_sub(speler.inzet);
+        let pot = spel.pot_met_inzetten(andere_spelers.iter().copied()) + speler.inzet;
         let res = if speler_id != spel.spelers[spel.aan_de_beurt] {
             Err(PokbotcomError::NietJouwBeurt.into())
+        } else if !LegaleActies::van(spel, speler, pot).laat_toe(actie) {
+            Err(spel.weigering(speler, actie, pot).into())
         } else {
             match actie {
                 Actie::Fold => {
@@ -915,39 +2221,73 @@ impl Centrale {
                             spel.laatste_actionabele_speler = Some(speler_id);
                         }
                         Some(laatste_speler) if laatste_speler == speler_id => {
+                            spel.noteer_actie(speler_id, actie);
                             return self.ronde_klaar(spel_id);
                         }
                         _ => {}
                     }
-
-                    if speler.inzet < spel.huidige_inzet {
-                        Err(PokbotcomError::NietGenoegChips.into())
-                    } else {
-                        Ok(())
-                    }
+                    Ok(())
+                }
+                Actie::Call => {
+                    // wie niet genoeg chips heeft om te callen, gaat all-in
+                    speler.zet_maximaal_in(spel.huidige_inzet - speler.inzet);
+                    Ok(())
                 }
-                Actie::Call => speler.zet_chips_in(spel.huidige_inzet - speler.inzet),
+                // `laat_toe` houdt de bet binnen wat hij na het callen nog heeft
                 Actie::Bet(extra_chips) => {
-                    spel.laatste_actionabele_speler = Some(speler_id);
-
-                    let res = speler.zet_chips_in(spel.huidige_inzet - speler.inzet + extra_chips);
+                    let res = speler.zet_chips_in(te_callen + extra_chips);
                     if res.is_ok() {
                         spel.huidige_inzet += extra_chips;
+                        spel.verhoog(speler_id, extra_chips);
                     }
 
                     res
                 }
+                Actie::AllIn => {
+                    let verhoging = speler.chips.saturating_sub(te_callen);
+                    speler.zet_maximaal_in(speler.chips);
+                    if verhoging > 0 {
+                        spel.huidige_inzet = speler.inzet;
+                        // een onvolledige all-in raise heropent de actie niet voor wie al
+                        // gehandeld heeft, maar die moet de nieuwe inzet wel nog callen
+                        if verhoging >= spel.laatste_verhoging {
+                            spel.verhoog(speler_id, verhoging);
+                        } else {
+                            spel.laatste_actionabele_speler = Some(speler_id);
+                        }
+                    }
+                    Ok(())
+                }
             }
         };
 
         if res.is_ok() {
-            println!("    [SERV] OK");
-            // stuur actie naar alle andere spelers
-            for sp in andere_spelers {
-                sp.stuur_bericht(PokbotcomMelding::SpelerActie(speler_id, actie), true);
+            spel.noteer_actie(speler_id, actie);
+            if !spel.gehandeld.contains(&speler_id) {
+                spel.gehandeld.push(speler_id);
+            }
+            // stuur actie naar alle andere spelers aan tafel
+            for sp in andere_spelers
+                .iter()
+                .filter(|sp| spel.spelers.contains(sp.id.get().unwrap()))
+            {
+                sp.stuur_bericht(spel_id, PokbotcomMelding::SpelerActie(speler_id, actie));
+            }
+
+            // na de laatste fold is de hand meteen voorbij
+            let in_hand = self
+                .spelers
+                .iter()
+                .filter(|s| spel.spelers.contains(s.id.get().unwrap()) && s.hand.is_some())
+                .count();
+            if in_hand < 2 {
+                return self.ronde_klaar(spel_id);
             }
 
-            loop {
+            // spelers die all-in zijn, worden overgeslagen
+            let pot = spel.pot_met_inzetten(&self.spelers);
+            let mut volgende_gevonden = false;
+            for _ in 0..spel.spelers.len() {
                 spel.aan_de_beurt = (spel.aan_de_beurt + 1).rem(spel.spelers.len());
                 let nu_actieve_speler = self
                     .spelers
@@ -955,19 +2295,31 @@ impl Centrale {
                     .find(|s| s.id.get().unwrap() == &spel.spelers[spel.aan_de_beurt])
                     .unwrap();
                 if nu_actieve_speler.hand.is_some() {
-                    if spel
-                        .laatste_actionabele_speler
-                        .is_some_and(|id| &id == nu_actieve_speler.id.get().unwrap())
+                    // de ronde is rond zodra de actie terugkomt bij wie al gehandeld heeft en
+                    // niets meer moet bijleggen
+                    if spel.gehandeld.contains(nu_actieve_speler.id.get().unwrap())
+                        && nu_actieve_speler.inzet >= spel.huidige_inzet
                     {
                         return self.ronde_klaar(spel_id);
                     }
-                    break;
-                } else {
-                    nu_actieve_speler.stuur_bericht(PokbotcomMelding::AanDeBeurt, true);
+                    if nu_actieve_speler.kan_handelen() {
+                        nu_actieve_speler.stuur_bericht(
+                            spel_id,
+                            PokbotcomMelding::AanDeBeurt(LegaleActies::van(
+                                spel,
+                                nu_actieve_speler,
+                                pot,
+                            )),
+                        );
+                        volgende_gevonden = true;
+                        break;
+                    }
                 }
             }
-        } else {
-            println!("    [SERV] {:?}", res);
+
+            if !volgende_gevonden {
+                return self.ronde_klaar(spel_id);
+            }
         }
 
         res
@@ -991,7 +2343,9 @@ mod tests {
         let id_c = centrale.registreer_speler(speler_c).unwrap();
         let id_d = centrale.registreer_speler(speler_d).unwrap();
 
-        let spel_id = centrale.maak_spel(vec![id_a, id_b, id_c, id_d], Some(0));
+        let spel_id = centrale
+            .maak_spel(vec![id_a, id_b, id_c, id_d], Some(0))
+            .unwrap();
 
         assert!(id_a == SpelerId(0));
         assert!(id_b == SpelerId(1));
@@ -1026,10 +2380,10 @@ mod tests {
         assert_eq!(centrale.spelers[2].chips, CHIPS_PER_SPELER - BIG_BLIND - 50);
 
         assert_eq!(centrale.spellen[0].aan_de_beurt, 0);
-        
+
         // Door te folden, kunnen we de turn te zien krijgen.
         assert!(centrale.ontvang_actie(spel_id, id_a, Actie::Fold).is_ok());
-        
+
         assert!(centrale.ontvang_actie(spel_id, id_b, Actie::Check).is_ok());
         assert!(centrale.ontvang_actie(spel_id, id_c, Actie::Check).is_ok());
         // De flop
@@ -1037,18 +2391,812 @@ mod tests {
         assert!(centrale.ontvang_actie(spel_id, id_c, Actie::Check).is_ok());
 
         // We checken even of de pot wel degelijk 50 + 50 + drie BIG_BLINDS bevat
-        assert_eq!(centrale.spellen[0].pot, 50 + 50 + BIG_BLIND + BIG_BLIND + BIG_BLIND);
+        assert_eq!(
+            centrale.spellen[0].pot(),
+            50 + 50 + BIG_BLIND + BIG_BLIND + BIG_BLIND
+        );
 
         // De river
         assert!(centrale.ontvang_actie(spel_id, id_b, Actie::Check).is_ok());
         assert!(centrale.ontvang_actie(spel_id, id_c, Actie::Check).is_ok());
-        
+
         // Cart heeft een straight, wat de beste hand is. Dus Cart wint de pot.
         // Hij is Big blind en heeft 50 gebet, dus zou op CHIPS_PER_SPELER - 50 - BIG BLIND + (POT) moeten zitten
         // MAAR aangezien de volgende ronde onmiddellijk al begonnen is, en Cart daarin Small Blind is, gaat er nog SMALL_BLIND vanaf gegaan zijn
-        assert_eq!(centrale.spelers[2].chips, CHIPS_PER_SPELER - 50 - BIG_BLIND + (50 + 50 + BIG_BLIND + BIG_BLIND + BIG_BLIND) - SMALL_BLIND);
+        assert_eq!(
+            centrale.spelers[2].chips,
+            CHIPS_PER_SPELER - 50 - BIG_BLIND + (50 + 50 + BIG_BLIND + BIG_BLIND + BIG_BLIND)
+                - SMALL_BLIND
+        );
 
         // Gesamtronde voorbij, Aart is aan de beurt aangezien Bart dealt, Cart SB en Dart BB is.
         assert_eq!(centrale.spellen[0].aan_de_beurt, 0);
     }
+
+    #[test]
+    fn spelers_aan_tafel() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart", "Dart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let fout = |res: Result<SpelId>| res.unwrap_err().downcast::<PokbotcomError>().unwrap();
+
+        assert_eq!(
+            fout(centrale.maak_spel(vec![ids[0]], None)),
+            PokbotcomError::TeWeinigSpelers
+        );
+        assert_eq!(
+            fout(centrale.maak_spel(vec![ids[0], SpelerId(9)], None)),
+            PokbotcomError::OnbekendeSpeler(SpelerId(9))
+        );
+        assert_eq!(
+            fout(centrale.maak_spel(vec![ids[0], ids[1], ids[0]], None)),
+            PokbotcomError::DubbeleSpeler(ids[0])
+        );
+
+        // twee wachtende spellen met dezelfde speler mogen, maar ze kunnen niet allebei lopen
+        let spel_id = centrale.maak_spel(vec![ids[0], ids[1]], None).unwrap();
+        let ander_spel = centrale.maak_spel(vec![ids[1], ids[2]], None).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        assert_eq!(
+            fout(centrale.maak_spel(vec![ids[2], ids[1]], None)),
+            PokbotcomError::SpeeltAl(ids[1], spel_id)
+        );
+        let res = centrale.start_spel(ander_spel).map(|_| ander_spel);
+        assert_eq!(fout(res), PokbotcomError::SpeeltAl(ids[1], spel_id));
+        assert_eq!(centrale.get_spel(ander_spel).status, SpelStatus::Wachtend);
+        centrale.maak_spel(vec![ids[2], ids[3]], None).unwrap();
+    }
+
+    #[test]
+    fn gedeelde_pot() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(0)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+
+        // De tafel heeft een broadway-straight die niemand kan verbeteren: Aart en Bart
+        // spelen allebei de tafel, Cart heeft gefold.
+        for speler in centrale.spelers.iter_mut() {
+            speler.chips = CHIPS_PER_SPELER;
+            speler.inzet = 0;
+        }
+        centrale.spelers[0].hand = Some((
+            Kaart::new(Kleur::Harten, Waarde::Tal(2)),
+            Kaart::new(Kleur::Schoppen, Waarde::Tal(3)),
+        ));
+        centrale.spelers[1].hand = Some((
+            Kaart::new(Kleur::Klaveren, Waarde::Tal(2)),
+            Kaart::new(Kleur::Ruiten, Waarde::Tal(3)),
+        ));
+        centrale.spelers[2].hand = None;
+
+        let spel = &mut centrale.spellen[0];
+        spel.tafel = (
+            Some((
+                Kaart::new(Kleur::Harten, Waarde::Aas),
+                Kaart::new(Kleur::Schoppen, Waarde::Koning),
+                Kaart::new(Kleur::Klaveren, Waarde::Koningin),
+            )),
+            Some(Kaart::new(Kleur::Ruiten, Waarde::Boer)),
+            Some(Kaart::new(Kleur::Harten, Waarde::Tal(10))),
+        );
+        spel.potten = vec![Pot {
+            chips: 101,
+            gerechtigden: ids.to_vec(),
+        }];
+
+        centrale.ronde_klaar(spel_id).unwrap();
+
+        // Aart is dealer, dus Bart zit het eerst links van de dealer en krijgt de oneven chip.
+        // Daarna is de volgende ronde al begonnen: Bart dealt, Cart is SB en Aart BB.
+        assert_eq!(centrale.spelers[0].chips, CHIPS_PER_SPELER + 50 - BIG_BLIND);
+        assert_eq!(centrale.spelers[1].chips, CHIPS_PER_SPELER + 51);
+        assert_eq!(centrale.spelers[2].chips, CHIPS_PER_SPELER - SMALL_BLIND);
+        assert_eq!(centrale.spellen[0].pot(), 0);
+    }
+
+    #[test]
+    fn sidepots() {
+        use Kleur::*;
+        use Waarde::*;
+
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let [id_a, id_b, id_c] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(0)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+
+        // Aart (dealer) heeft 100 chips, Bart (SB) 300 en Cart (BB) 250.
+        centrale.spelers[0].chips = 100;
+        centrale.spelers[1].chips = 300 - SMALL_BLIND;
+        centrale.spelers[2].chips = 250 - BIG_BLIND;
+
+        // Aart heeft three of a kind, Bart een paar azen en Cart niets.
+        let handen = [
+            (Kaart::new(Klaveren, Tal(9)), Kaart::new(Ruiten, Tal(9))),
+            (Kaart::new(Harten, Aas), Kaart::new(Ruiten, Aas)),
+            (Kaart::new(Harten, Koningin), Kaart::new(Ruiten, Tal(3))),
+        ];
+        let tafel = [
+            Kaart::new(Klaveren, Tal(2)),
+            Kaart::new(Ruiten, Tal(7)),
+            Kaart::new(Harten, Tal(9)),
+            Kaart::new(Schoppen, Boer),
+            Kaart::new(Klaveren, Koning),
+        ];
+        for (speler, hand) in centrale.spelers.iter_mut().zip(handen) {
+            speler.hand = Some(hand);
+        }
+
+        // het deck wordt van achteren af gedeeld
+        let spel = &mut centrale.spellen[0];
+        spel.deck = Kaart::maak_deck()
+            .into_iter()
+            .filter(|k| !tafel.contains(k) && !handen.iter().any(|(a, b)| a == k || b == k))
+            .chain(tafel.into_iter().rev())
+            .collect();
+
+        assert!(centrale.ontvang_actie(spel_id, id_a, Actie::AllIn).is_ok());
+        assert!(centrale.ontvang_actie(spel_id, id_b, Actie::AllIn).is_ok());
+        // Cart kan de 300 van Bart niet volgen en gaat all-in voor 250.
+        assert!(centrale.ontvang_actie(spel_id, id_c, Actie::Call).is_ok());
+
+        // De hoofdpot van 3 x 100 gaat naar Aart, de sidepot van 2 x 150 naar Bart en de
+        // 50 die Cart niet kon volgen krijgt Bart terug. In de volgende ronde is Bart dealer,
+        // Cart SB zonder chips en Aart BB.
+        assert_eq!(centrale.spelers[0].chips, 300 - BIG_BLIND);
+        assert_eq!(centrale.spelers[1].chips, 300 + 50);
+        assert_eq!(centrale.spelers[2].chips, 0);
+        assert_eq!(centrale.spellen[0].pot(), 0);
+    }
+
+    #[test]
+    fn sidepots_opbouwen() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart", "Dart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let [id_a, id_b, id_c, _] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(0)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+
+        // Aart is all-in voor 50, Bart voor 120, Cart en Dart zetten 200 in en Dart foldt
+        // daarna nog.
+        for (speler, (inzet, chips)) in
+            centrale
+                .spelers
+                .iter_mut()
+                .zip([(50, 0), (120, 0), (200, 500), (200, 500)])
+        {
+            speler.inzet = inzet;
+            speler.chips = chips;
+        }
+        centrale.spelers[3].hand = None;
+
+        centrale.verzamel_pot(spel_id);
+
+        assert_eq!(
+            centrale.spellen[0].potten,
+            vec![
+                Pot {
+                    chips: 4 * 50,
+                    gerechtigden: vec![id_a, id_b, id_c],
+                },
+                Pot {
+                    chips: 3 * 70,
+                    gerechtigden: vec![id_b, id_c],
+                },
+                Pot {
+                    chips: 2 * 80,
+                    gerechtigden: vec![id_c],
+                },
+            ]
+        );
+        assert!(centrale.spelers.iter().all(|s| s.inzet == 0));
+    }
+
+    #[test]
+    fn winnen_zonder_showdown() {
+        let mut centrale = Centrale::new();
+        let wachtrij = kanaal::Wachtrij::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            let mut speler = Speler::new_zonder_id(naam.into());
+            if naam == "Cart" {
+                speler = speler.met_kanaal(wachtrij.clone());
+            }
+            centrale.registreer_speler(speler).unwrap()
+        });
+        let [id_a, id_b, id_c] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(0)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+
+        // A en B folden: de big blind krijgt wat niet gecallt werd terug en wint de small
+        // blind, zonder dat er nog kaarten op tafel komen
+        centrale.ontvang_actie(spel_id, id_a, Actie::Fold).unwrap();
+        centrale.ontvang_actie(spel_id, id_b, Actie::Fold).unwrap();
+        assert!(wachtrij
+            .meldingen()
+            .contains(&PokbotcomMelding::Gewonnen(vec![(id_c, None, 20)])));
+
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+        assert!(hand.is_klaar());
+        assert!(hand.tafel.is_empty() && hand.showdown.is_empty());
+        assert_eq!(hand.winsten, [(id_c, 20)]);
+        assert_eq!(centrale.hand_historieen(spel_id).len(), 2);
+        let inzet = centrale.get_speler(id_c).inzet;
+        assert_eq!(
+            centrale.get_speler(id_c).chips + inzet,
+            CHIPS_PER_SPELER + 10
+        );
+    }
+
+    #[test]
+    fn meldingen_per_speler() {
+        let mut centrale = Centrale::new();
+        let wachtrijen = [(); 3].map(|_| kanaal::Wachtrij::new());
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            let speler = Speler::new_zonder_id(naam.into());
+            let wachtrij = wachtrijen[centrale.spelers.len()].clone();
+            centrale
+                .registreer_speler(speler.met_kanaal(wachtrij))
+                .unwrap()
+        });
+        let [id_a, id_b, id_c] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(0)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+
+        let handen = centrale
+            .spelers
+            .iter()
+            .map(|s| s.hand.unwrap())
+            .collect_vec();
+        centrale
+            .ontvang_actie(spel_id, id_a, Actie::Bet(40))
+            .unwrap();
+        centrale.ontvang_actie(spel_id, id_b, Actie::Call).unwrap();
+        centrale.ontvang_actie(spel_id, id_c, Actie::Call).unwrap();
+
+        let (a, b, c) = centrale.spellen[0].tafel.0.unwrap();
+        let hand = |i: usize| PokbotcomMelding::Hand(handen[i].0, handen[i].1);
+        let flop = PokbotcomMelding::Flop(a, b, c);
+        use PokbotcomMelding::*;
+        let beurt = |te_callen: u64, min_bet: u64, chips: u64| {
+            AanDeBeurt(LegaleActies {
+                fold: true,
+                check: te_callen == 0,
+                call: te_callen > 0,
+                te_callen,
+                bet: true,
+                min_bet,
+                max_bet: chips - te_callen,
+                all_in: true,
+            })
+        };
+
+        assert_eq!(
+            wachtrijen[0].neem(),
+            [
+                hand(0),
+                beurt(20, 20, 1000),
+                SpelerActie(id_b, Actie::Call),
+                SpelerActie(id_c, Actie::Call),
+                RondeOver,
+                flop.clone(),
+            ]
+        );
+        assert_eq!(
+            wachtrijen[1].neem(),
+            [
+                hand(1),
+                SpelerActie(id_a, Actie::Bet(40)),
+                beurt(50, 40, 990),
+                SpelerActie(id_c, Actie::Call),
+                RondeOver,
+                flop.clone(),
+                beurt(0, 20, 940),
+            ]
+        );
+        assert_eq!(
+            wachtrijen[2].neem(),
+            [
+                hand(2),
+                SpelerActie(id_a, Actie::Bet(40)),
+                SpelerActie(id_b, Actie::Call),
+                beurt(40, 40, 980),
+                RondeOver,
+                flop,
+            ]
+        );
+        assert!(wachtrijen.iter().all(|w| w.meldingen().is_empty()));
+    }
+
+    #[test]
+    fn legale_acties() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let [id_a, id_b, id_c] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(1)).unwrap();
+        assert!(centrale.legale_acties(spel_id, id_a).is_err());
+        centrale.start_spel(spel_id).unwrap();
+
+        let legaal = centrale.legale_acties(spel_id, id_a).unwrap();
+        assert!(legaal.fold && legaal.call && !legaal.check);
+        assert_eq!(
+            (legaal.te_callen, legaal.min_bet, legaal.max_bet),
+            (20, 20, 980)
+        );
+        assert!(legaal.laat_toe(Actie::Bet(980)) && !legaal.laat_toe(Actie::Bet(981)));
+        assert_eq!(
+            centrale.legale_acties(spel_id, id_b).unwrap(),
+            LegaleActies::default()
+        );
+        assert!(centrale.legale_acties(SpelId(5), id_a).is_err());
+        assert!(centrale.legale_acties(spel_id, SpelerId(5)).is_err());
+
+        // wie minder chips heeft dan er te callen is, kan enkel nog all-in callen
+        centrale
+            .ontvang_actie(spel_id, id_a, Actie::Bet(500))
+            .unwrap();
+        centrale.get_mut_speler(id_b).chips = 100;
+        let legaal = centrale.legale_acties(spel_id, id_b).unwrap();
+        assert_eq!(
+            legaal,
+            LegaleActies {
+                fold: true,
+                call: true,
+                te_callen: 100,
+                min_bet: 500,
+                all_in: true,
+                ..Default::default()
+            }
+        );
+        assert!(!legaal.laat_toe(Actie::Bet(1)));
+        centrale.ontvang_actie(spel_id, id_b, Actie::Call).unwrap();
+        assert!(centrale.get_speler(id_b).is_all_in());
+        assert!(centrale.legale_acties(spel_id, id_c).unwrap().call);
+    }
+
+    #[test]
+    fn minimale_verhoging() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart", "Dart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let [id_a, id_b, id_c, id_d] = ids;
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(1)).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        let fout = |res: Result<()>| res.unwrap_err().downcast::<PokbotcomError>().unwrap();
+
+        // D opent: minstens de big blind erbovenop, en niet voor niets
+        for chips in [0, 19] {
+            assert_eq!(
+                fout(centrale.ontvang_actie(spel_id, id_d, Actie::Bet(chips))),
+                PokbotcomError::TeKleineVerhoging(BIG_BLIND)
+            );
+        }
+        assert_eq!(
+            centrale.get_spel(spel_id).laatste_actionabele_speler,
+            Some(id_c)
+        );
+
+        // ook bij no limit niet meer dan hij heeft, en zonder overflow
+        for chips in [CHIPS_PER_SPELER, u64::MAX] {
+            assert_eq!(
+                fout(centrale.ontvang_actie(spel_id, id_d, Actie::Bet(chips))),
+                PokbotcomError::TeGroteVerhoging(CHIPS_PER_SPELER - BIG_BLIND)
+            );
+        }
+        assert_eq!(centrale.get_speler(id_d).chips, CHIPS_PER_SPELER);
+        centrale
+            .ontvang_actie(spel_id, id_d, Actie::Bet(40))
+            .unwrap();
+
+        // een re-raise moet minstens even groot zijn als de vorige verhoging
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, id_a, Actie::Bet(39))),
+            PokbotcomError::TeKleineVerhoging(40)
+        );
+        assert_eq!(centrale.legale_acties(spel_id, id_a).unwrap().min_bet, 40);
+
+        // B gaat all-in voor 30 boven de 60, een onvolledige verhoging
+        centrale.ontvang_actie(spel_id, id_a, Actie::Call).unwrap();
+        centrale.get_mut_speler(id_b).chips = 80;
+        centrale.ontvang_actie(spel_id, id_b, Actie::AllIn).unwrap();
+        assert_eq!(centrale.get_spel(spel_id).huidige_inzet, 90);
+        assert_eq!(centrale.get_spel(spel_id).laatste_verhoging, 40);
+
+        // C heeft nog niet gehandeld en mag dus wel verhogen, met minstens 40
+        let legaal = centrale.legale_acties(spel_id, id_c).unwrap();
+        assert!(legaal.bet && legaal.min_bet == 40);
+        centrale.ontvang_actie(spel_id, id_c, Actie::Call).unwrap();
+
+        // D en A hadden al gehandeld: enkel nog callen of folden
+        let legaal = centrale.legale_acties(spel_id, id_d).unwrap();
+        assert!(legaal.call && !legaal.bet && !legaal.all_in);
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, id_d, Actie::Bet(100))),
+            PokbotcomError::ActieNietHeropend
+        );
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, id_d, Actie::AllIn)),
+            PokbotcomError::ActieNietHeropend
+        );
+        centrale.ontvang_actie(spel_id, id_d, Actie::Call).unwrap();
+        centrale.ontvang_actie(spel_id, id_a, Actie::Call).unwrap();
+
+        // op de flop begint het opnieuw bij de big blind
+        let spel = centrale.get_spel(spel_id);
+        assert!(spel.tafel.0.is_some());
+        assert_eq!((spel.huidige_inzet, spel.laatste_verhoging), (0, BIG_BLIND));
+
+        // wat `LegaleActies` niet toelaat, wordt geweigerd
+        let eerste = spel.spelers[spel.aan_de_beurt];
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, eerste, Actie::Call)),
+            PokbotcomError::NietsTeCallen
+        );
+        centrale.get_mut_speler(eerste).chips = 10;
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, eerste, Actie::Bet(BIG_BLIND))),
+            PokbotcomError::TeGroteVerhoging(10)
+        );
+        assert!(!centrale.legale_acties(spel_id, eerste).unwrap().bet);
+        centrale
+            .ontvang_actie(spel_id, eerste, Actie::AllIn)
+            .unwrap();
+    }
+
+    fn drie_spelers(structuur: BetStructuur) -> (Centrale, SpelId, [SpelerId; 3]) {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(1)).unwrap();
+        centrale.zet_bet_structuur(spel_id, structuur).unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        (centrale, spel_id, ids)
+    }
+
+    #[test]
+    fn ongeldige_bedragen() {
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let spel_id = centrale.maak_spel(ids.to_vec(), Some(1)).unwrap();
+        let fout = |res: Result<()>| res.unwrap_err().downcast::<PokbotcomError>().unwrap();
+
+        let regels = VerplichteInzetten::default();
+        assert_eq!(
+            fout(centrale.zet_verplichte_inzetten(spel_id, BlindNiveau::new(0, 0, 0), regels)),
+            PokbotcomError::GeenBigBlind
+        );
+        let niveau = BlindNiveau::new(30, 20, 0);
+        assert_eq!(
+            fout(centrale.zet_verplichte_inzetten(spel_id, niveau, regels)),
+            PokbotcomError::SmallBlindTeGroot(niveau)
+        );
+        let geen_kleine_bet = BetStructuur::FixedLimit {
+            kleine_bet: 0,
+            grote_bet: 40,
+            max_verhogingen: 4,
+        };
+        assert_eq!(
+            fout(centrale.zet_bet_structuur(spel_id, geen_kleine_bet)),
+            PokbotcomError::GeenKleineBet
+        );
+        let omgekeerd = BetStructuur::FixedLimit {
+            kleine_bet: 40,
+            grote_bet: 20,
+            max_verhogingen: 4,
+        };
+        assert_eq!(
+            fout(centrale.zet_bet_structuur(spel_id, omgekeerd)),
+            PokbotcomError::GroteBetTeKlein(omgekeerd)
+        );
+
+        // het spel speelt verder met de standaardbedragen
+        let spel = centrale.get_spel(spel_id);
+        assert_eq!(spel.niveau, BlindNiveau::default());
+        assert_eq!(spel.bet_structuur, BetStructuur::NoLimit);
+        centrale
+            .zet_verplichte_inzetten(spel_id, BlindNiveau::new(20, 20, 0), regels)
+            .unwrap();
+
+        // ook zonder blinds opent een bet van 0 chips de ronde niet opnieuw
+        centrale.get_mut_spel(spel_id).niveau = BlindNiveau::new(0, 0, 0);
+        centrale.start_spel(spel_id).unwrap();
+        let spel = centrale.get_spel(spel_id);
+        let eerste = spel.spelers[spel.aan_de_beurt];
+        assert_eq!(centrale.legale_acties(spel_id, eerste).unwrap().min_bet, 1);
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, eerste, Actie::Bet(0))),
+            PokbotcomError::TeKleineVerhoging(1)
+        );
+    }
+
+    #[test]
+    fn pot_limit() {
+        let (mut centrale, spel_id, [id_a, id_b, _]) = drie_spelers(BetStructuur::PotLimit);
+        let fout = |res: Result<()>| res.unwrap_err().downcast::<PokbotcomError>().unwrap();
+        assert_eq!(
+            fout(centrale.zet_bet_structuur(spel_id, BetStructuur::NoLimit)),
+            PokbotcomError::SpelAlBegonnen(spel_id)
+        );
+
+        // A callt 20 in een pot van 30 en mag dan nog met de pot van 50 verhogen
+        let legaal = centrale.legale_acties(spel_id, id_a).unwrap();
+        assert_eq!((legaal.min_bet, legaal.max_bet), (20, 50));
+        assert!(!legaal.all_in);
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, id_a, Actie::Bet(51))),
+            PokbotcomError::TeGroteVerhoging(50)
+        );
+        centrale
+            .ontvang_actie(spel_id, id_a, Actie::Bet(50))
+            .unwrap();
+
+        // B callt 60 in een pot van 100: hoogstens 160 erbovenop
+        assert_eq!(centrale.legale_acties(spel_id, id_b).unwrap().max_bet, 160);
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, id_b, Actie::AllIn)),
+            PokbotcomError::TeGroteVerhoging(160)
+        );
+        centrale.get_mut_speler(id_b).chips = 200;
+        assert!(centrale.legale_acties(spel_id, id_b).unwrap().all_in);
+        centrale.ontvang_actie(spel_id, id_b, Actie::AllIn).unwrap();
+    }
+
+    #[test]
+    fn fixed_limit() {
+        let (mut centrale, spel_id, [id_a, id_b, id_c]) = drie_spelers(BetStructuur::FixedLimit {
+            kleine_bet: 20,
+            grote_bet: 40,
+            max_verhogingen: 4,
+        });
+        let fout = |res: Result<()>| res.unwrap_err().downcast::<PokbotcomError>().unwrap();
+
+        // preflop is elke raise precies de kleine bet
+        let legaal = centrale.legale_acties(spel_id, id_a).unwrap();
+        assert_eq!((legaal.min_bet, legaal.max_bet), (20, 20));
+        assert!(!legaal.all_in);
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, id_a, Actie::Bet(10))),
+            PokbotcomError::TeKleineVerhoging(20)
+        );
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, id_a, Actie::Bet(40))),
+            PokbotcomError::TeGroteVerhoging(20)
+        );
+
+        // de big blind en drie raises: daarna is de ronde afgetopt
+        for id in [id_a, id_b, id_c] {
+            centrale.ontvang_actie(spel_id, id, Actie::Bet(20)).unwrap();
+        }
+        assert_eq!(centrale.get_spel(spel_id).huidige_inzet, 80);
+        let legaal = centrale.legale_acties(spel_id, id_a).unwrap();
+        assert!(legaal.call && !legaal.bet && !legaal.all_in);
+        assert_eq!(
+            fout(centrale.ontvang_actie(spel_id, id_a, Actie::Bet(20))),
+            PokbotcomError::GeenVerhogingMeer
+        );
+        centrale.ontvang_actie(spel_id, id_a, Actie::Call).unwrap();
+        centrale.ontvang_actie(spel_id, id_b, Actie::Call).unwrap();
+
+        // op de flop nog de kleine bet, op de turn de grote
+        assert_eq!(centrale.legale_acties(spel_id, id_b).unwrap().max_bet, 20);
+        for id in [id_b, id_c, id_a] {
+            centrale.ontvang_actie(spel_id, id, Actie::Check).unwrap();
+        }
+        let legaal = centrale.legale_acties(spel_id, id_b).unwrap();
+        assert_eq!((legaal.min_bet, legaal.max_bet), (40, 40));
+    }
+
+    fn met_verplichte_inzetten(
+        namen: &[&str],
+        niveau: BlindNiveau,
+        regels: VerplichteInzetten,
+    ) -> (Centrale, SpelId, Vec<SpelerId>) {
+        let mut centrale = Centrale::new();
+        let ids = namen
+            .iter()
+            .map(|naam| {
+                centrale
+                    .registreer_speler(Speler::new_zonder_id(naam.to_string()))
+                    .unwrap()
+            })
+            .collect_vec();
+        let spel_id = centrale.maak_spel(ids.clone(), Some(1)).unwrap();
+        centrale
+            .zet_verplichte_inzetten(spel_id, niveau, regels)
+            .unwrap();
+        (centrale, spel_id, ids)
+    }
+
+    // iedereen checkt of callt tot de hand gedaan is
+    fn speel_hand_uit(centrale: &mut Centrale, spel_id: SpelId) {
+        let handen = centrale.hand_historieen(spel_id).len();
+        while centrale.hand_historieen(spel_id).len() == handen {
+            let spel = centrale.get_spel(spel_id);
+            let id = spel.spelers[spel.aan_de_beurt];
+            let actie = match centrale.legale_acties(spel_id, id).unwrap() {
+                legaal if legaal.check => Actie::Check,
+                _ => Actie::Call,
+            };
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+    }
+
+    #[test]
+    fn straddle() {
+        let regels = VerplichteInzetten {
+            straddle: true,
+            ..Default::default()
+        };
+        let (mut centrale, spel_id, ids) = met_verplichte_inzetten(
+            &["Aart", "Bart", "Cart", "Dart"],
+            BlindNiveau::default(),
+            regels,
+        );
+        centrale.start_spel(spel_id).unwrap();
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+        assert_eq!(hand.blinds, [(ids[1], 10), (ids[2], 20), (ids[3], 40)]);
+
+        // A zit na de straddle en moet 40 callen; een raise is minstens 40
+        let spel = centrale.get_spel(spel_id);
+        assert_eq!(spel.aan_de_beurt, 0);
+        let legaal = centrale.legale_acties(spel_id, ids[0]).unwrap();
+        assert_eq!((legaal.te_callen, legaal.min_bet), (40, 40));
+        for id in &ids[..3] {
+            centrale.ontvang_actie(spel_id, *id, Actie::Call).unwrap();
+        }
+
+        // de straddle is als laatste aan de beurt en mag nog verhogen
+        let legaal = centrale.legale_acties(spel_id, ids[3]).unwrap();
+        assert!(legaal.check && legaal.bet);
+        centrale
+            .ontvang_actie(spel_id, ids[3], Actie::Check)
+            .unwrap();
+        assert!(centrale.get_spel(spel_id).tafel.0.is_some());
+
+        // met twee spelers wordt er niet gestraddled
+        let (mut centrale, spel_id, ids) =
+            met_verplichte_inzetten(&["Aart", "Bart"], BlindNiveau::default(), regels);
+        centrale.start_spel(spel_id).unwrap();
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+        assert_eq!(hand.blinds.len(), 2);
+        assert_eq!(centrale.get_spel(spel_id).huidige_inzet, 20);
+        assert!(ids.iter().all(|id| centrale.get_speler(*id).inzet <= 20));
+    }
+
+    #[test]
+    fn heads_up() {
+        let (mut centrale, spel_id, ids) = met_verplichte_inzetten(
+            &["Aart", "Bart"],
+            BlindNiveau::default(),
+            VerplichteInzetten::default(),
+        );
+        centrale.start_spel(spel_id).unwrap();
+        for nummer in 1..=20u64 {
+            // de dealer zit op de small blind en is preflop als eerste aan de beurt
+            let spel = centrale.get_spel(spel_id);
+            let dealer = spel.spelers[spel.huidige_dealer];
+            let ander = spel.spelers[1 - spel.huidige_dealer];
+            assert_eq!(dealer, ids[(nummer as usize + 1) % 2], "hand {nummer}");
+            assert_eq!(spel.spelers[spel.aan_de_beurt], dealer);
+            let hand = centrale.hand_historie(spel_id, nummer).unwrap();
+            assert_eq!(hand.dealer, dealer);
+            assert_eq!(hand.blinds, [(dealer, 10), (ander, 20)]);
+
+            // na een call van de small blind mag de big blind nog checken
+            centrale
+                .ontvang_actie(spel_id, dealer, Actie::Call)
+                .unwrap();
+            assert!(centrale.legale_acties(spel_id, ander).unwrap().check);
+
+            // zijn check sluit de ronde af; na de flop is de big blind als eerste aan de beurt
+            speel_hand_uit(&mut centrale, spel_id);
+            let hand = centrale.hand_historie(spel_id, nummer).unwrap();
+            assert_eq!(
+                hand.acties_in(Straat::Preflop).collect_vec(),
+                [(dealer, Actie::Call), (ander, Actie::Check)],
+                "hand {nummer}"
+            );
+            let eerste = |straat| hand.acties.iter().find(|(s, ..)| *s == straat).unwrap().1;
+            assert_eq!(eerste(Straat::Preflop), dealer);
+            for straat in [Straat::Flop, Straat::Turn, Straat::River] {
+                assert_eq!(eerste(straat), ander, "{straat:?} in hand {nummer}");
+            }
+        }
+    }
+
+    #[test]
+    fn big_blind_ante() {
+        let regels = VerplichteInzetten {
+            big_blind_ante: true,
+            ..Default::default()
+        };
+        let (mut centrale, spel_id, ids) = met_verplichte_inzetten(
+            &["Aart", "Bart", "Cart"],
+            BlindNiveau::new(10, 20, 20),
+            regels,
+        );
+        centrale.start_spel(spel_id).unwrap();
+        let hand = centrale.hand_historie(spel_id, 1).unwrap().clone();
+        assert_eq!(hand.antes, [(ids[2], 20)]);
+        assert_eq!(centrale.get_speler(ids[2]).chips, 1000 - 20 - 20);
+        assert_eq!(centrale.get_spel(spel_id).pot(), 20);
+        assert_eq!(centrale.get_spel(spel_id).huidige_inzet, 20);
+
+        // de big blind zet meer in dan wie hem callt, maar iedereen speelt voor de hele pot
+        speel_hand_uit(&mut centrale, spel_id);
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+        assert_eq!(
+            hand.potten,
+            [Pot {
+                chips: 80,
+                gerechtigden: ids.clone()
+            }]
+        );
+        let centrale = Centrale::speel_na(hand).unwrap();
+        assert_eq!(
+            centrale.hand_historie(spel_id, 1).unwrap().potten,
+            hand.potten
+        );
+    }
+
+    #[test]
+    fn ante_in_sidepot() {
+        let (mut centrale, spel_id, ids) = met_verplichte_inzetten(
+            &["Aart", "Bart", "Cart"],
+            BlindNiveau::new(10, 20, 10),
+            VerplichteInzetten::default(),
+        );
+        centrale.get_mut_speler(ids[0]).chips = 5;
+        centrale.start_spel(spel_id).unwrap();
+
+        // A is all-in met een deel van de ante en komt dus niet aan de beurt
+        let spel = centrale.get_spel(spel_id);
+        assert_eq!(spel.aan_de_beurt, 1);
+        assert_eq!(spel.pot(), 25);
+
+        speel_hand_uit(&mut centrale, spel_id);
+        let hand = centrale.hand_historie(spel_id, 1).unwrap();
+        assert_eq!(
+            hand.potten,
+            [
+                Pot {
+                    chips: 15,
+                    gerechtigden: ids.clone()
+                },
+                Pot {
+                    chips: 50,
+                    gerechtigden: ids[1..].to_vec()
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,55 @@
+// Waar `Centrale` zijn spelers, spellen, toernooien en id-tellers bewaart, zodat een herstarte
+// server verder kan waar hij gebleven was (zie `Centrale::laad_uit_db` en
+// `Centrale::bewaar_in_db`). Het kanaal van een speler wordt niet bewaard en moet na het laden
+// opnieuw ingesteld worden; de server bewaart daarvoor zijn sessies mee.
+
+use std::fmt::Debug;
+
+use anyhow::Result;
+
+use crate::toernooi::{Toernooi, ToernooiId};
+use crate::{Spel, SpelId, Speler, SpelerId};
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tellers {
+    pub volgende_speler_id: u64,
+    pub volgende_spel_id: u64,
+    pub volgende_toernooi_id: u64,
+}
+
+// Het sessietoken van een speler die zich via de server registreerde, met zijn callback.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BewaardeSessie {
+    pub sessie: String,
+    pub speler_id: SpelerId,
+    pub callback: Option<String>,
+}
+
+// Bewaren overschrijft wat er al was voor dezelfde id.
+pub trait Opslag: Debug {
+    fn bewaar_speler(&mut self, speler: &Speler) -> Result<()>;
+    fn bewaar_spel(&mut self, spel: &Spel) -> Result<()>;
+    fn bewaar_toernooi(&mut self, toernooi: &Toernooi) -> Result<()>;
+    fn bewaar_tellers(&mut self, tellers: Tellers) -> Result<()>;
+    fn bewaar_sessie(&mut self, sessie: &BewaardeSessie) -> Result<()>;
+    // verwijdert alle spelers, spellen en toernooien die er niet bij zijn
+    fn houd_enkel(
+        &mut self,
+        spelers: &[SpelerId],
+        spellen: &[SpelId],
+        toernooien: &[ToernooiId],
+    ) -> Result<()>;
+
+    // Voert `werk` uit als één geheel: lukt een deel niet, dan wordt er niets van bewaard.
+    // Mag genest worden.
+    fn in_transactie(&mut self, werk: &mut dyn FnMut(&mut dyn Opslag) -> Result<()>) -> Result<()>;
+
+    fn laad_spelers(&self) -> Result<Vec<Speler>>;
+    fn laad_spellen(&self) -> Result<Vec<Spel>>;
+    fn laad_toernooien(&self) -> Result<Vec<Toernooi>>;
+    fn laad_tellers(&self) -> Result<Tellers>;
+    fn laad_sessies(&self) -> Result<Vec<BewaardeSessie>>;
+}
@@ -0,0 +1,473 @@
+// Opslag in een SQLite-databank. Spelers en tellers krijgen elk hun kolommen; een spel wordt
+// als één JSON-document in het formaat van `wire` bewaard, met deck en random generator, en een
+// toernooi net zo met zijn instellingen en waar het in zijn schema staat.
+
+use std::path::Path;
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{BewaardeSessie, Opslag, Tellers};
+use crate::kaart::notatie::{lees_kaarten, schrijf_kaarten};
+use crate::toernooi::{Toernooi, ToernooiId};
+use crate::{wire, PokbotcomError, Spel, SpelId, Speler, SpelerId};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS spelers (
+        id    INTEGER PRIMARY KEY,
+        naam  TEXT NOT NULL,
+        chips INTEGER NOT NULL,
+        hand  TEXT,
+        inzet INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS spellen (
+        id   INTEGER PRIMARY KEY,
+        spel TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS toernooien (
+        id       INTEGER PRIMARY KEY,
+        toernooi TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS tellers (
+        naam   TEXT PRIMARY KEY,
+        waarde INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS sessies (
+        sessie    TEXT PRIMARY KEY,
+        speler_id INTEGER NOT NULL,
+        callback  TEXT
+    );
+";
+
+#[derive(Debug)]
+pub struct SqliteOpslag {
+    verbinding: Connection,
+}
+
+impl SqliteOpslag {
+    pub fn open(pad: impl AsRef<Path>) -> Result<Self> {
+        Self::met_verbinding(Connection::open(pad)?)
+    }
+
+    pub fn in_geheugen() -> Result<Self> {
+        Self::met_verbinding(Connection::open_in_memory()?)
+    }
+
+    fn met_verbinding(verbinding: Connection) -> Result<Self> {
+        verbinding.execute_batch(SCHEMA)?;
+        Ok(SqliteOpslag { verbinding })
+    }
+
+    fn teller(&self, naam: &str) -> Result<u64> {
+        let waarde: Option<i64> = self
+            .verbinding
+            .query_row(
+                "SELECT waarde FROM tellers WHERE naam = ?1",
+                [naam],
+                |rij| rij.get(0),
+            )
+            .optional()?;
+        Ok(waarde.unwrap_or(0) as u64)
+    }
+
+    fn ids(&self, tabel: &str) -> Result<Vec<u64>> {
+        let mut query = self
+            .verbinding
+            .prepare(&format!("SELECT id FROM {tabel}"))?;
+        let ids = query
+            .query_map([], |rij| rij.get::<_, i64>(0))?
+            .map(|id| Ok(id? as u64))
+            .collect();
+        ids
+    }
+}
+
+impl Opslag for SqliteOpslag {
+    fn bewaar_speler(&mut self, speler: &Speler) -> Result<()> {
+        let hand = speler.hand.map(|(a, b)| schrijf_kaarten(&[a, b]));
+        self.verbinding.execute(
+            "INSERT OR REPLACE INTO spelers (id, naam, chips, hand, inzet)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                speler.id.get().unwrap().0 as i64,
+                speler.naam,
+                speler.chips as i64,
+                hand,
+                speler.inzet as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn bewaar_spel(&mut self, spel: &Spel) -> Result<()> {
+        self.verbinding.execute(
+            "INSERT OR REPLACE INTO spellen (id, spel) VALUES (?1, ?2)",
+            params![spel.id.get().unwrap().0 as i64, wire::naar_json(spel)],
+        )?;
+        Ok(())
+    }
+
+    fn bewaar_toernooi(&mut self, toernooi: &Toernooi) -> Result<()> {
+        self.verbinding.execute(
+            "INSERT OR REPLACE INTO toernooien (id, toernooi) VALUES (?1, ?2)",
+            params![toernooi.id.0 as i64, wire::naar_json(toernooi)],
+        )?;
+        Ok(())
+    }
+
+    fn bewaar_tellers(&mut self, tellers: Tellers) -> Result<()> {
+        let transactie = self.verbinding.savepoint()?;
+        for (naam, waarde) in [
+            ("volgende_speler_id", tellers.volgende_speler_id),
+            ("volgende_spel_id", tellers.volgende_spel_id),
+            ("volgende_toernooi_id", tellers.volgende_toernooi_id),
+        ] {
+            transactie.execute(
+                "INSERT OR REPLACE INTO tellers (naam, waarde) VALUES (?1, ?2)",
+                params![naam, waarde as i64],
+            )?;
+        }
+        transactie.commit()?;
+        Ok(())
+    }
+
+    fn bewaar_sessie(&mut self, sessie: &BewaardeSessie) -> Result<()> {
+        self.verbinding.execute(
+            "INSERT OR REPLACE INTO sessies (sessie, speler_id, callback) VALUES (?1, ?2, ?3)",
+            params![sessie.sessie, sessie.speler_id.0 as i64, sessie.callback],
+        )?;
+        Ok(())
+    }
+
+    fn houd_enkel(
+        &mut self,
+        spelers: &[SpelerId],
+        spellen: &[SpelId],
+        toernooien: &[ToernooiId],
+    ) -> Result<()> {
+        let spelers = spelers.iter().map(|id| id.0).collect::<Vec<_>>();
+        let spellen = spellen.iter().map(|id| id.0).collect::<Vec<_>>();
+        let toernooien = toernooien.iter().map(|id| id.0).collect::<Vec<_>>();
+        for (tabel, houden) in [
+            ("spelers", spelers),
+            ("spellen", spellen),
+            ("toernooien", toernooien),
+        ] {
+            for id in self.ids(tabel)? {
+                if !houden.contains(&id) {
+                    self.verbinding
+                        .execute(&format!("DELETE FROM {tabel} WHERE id = ?1"), [id as i64])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // met een savepoint in plaats van een transactie, zodat het ook genest kan
+    fn in_transactie(&mut self, werk: &mut dyn FnMut(&mut dyn Opslag) -> Result<()>) -> Result<()> {
+        self.verbinding.execute_batch("SAVEPOINT in_transactie")?;
+        match werk(self) {
+            Ok(()) => {
+                self.verbinding.execute_batch("RELEASE in_transactie")?;
+                Ok(())
+            }
+            Err(fout) => {
+                self.verbinding
+                    .execute_batch("ROLLBACK TO in_transactie; RELEASE in_transactie")?;
+                Err(fout)
+            }
+        }
+    }
+
+    fn laad_spelers(&self) -> Result<Vec<Speler>> {
+        let mut query = self
+            .verbinding
+            .prepare("SELECT id, naam, chips, hand, inzet FROM spelers ORDER BY id")?;
+        let rijen = query.query_map([], |rij| {
+            Ok((
+                rij.get::<_, i64>(0)?,
+                rij.get::<_, String>(1)?,
+                rij.get::<_, i64>(2)?,
+                rij.get::<_, Option<String>>(3)?,
+                rij.get::<_, i64>(4)?,
+            ))
+        })?;
+
+        let mut spelers = Vec::new();
+        for rij in rijen {
+            let (id, naam, chips, hand, inzet) = rij?;
+            let mut speler = Speler::new_zonder_id(naam);
+            speler.id.set(SpelerId(id as u64)).unwrap();
+            speler.chips = chips as u64;
+            speler.inzet = inzet as u64;
+            speler.hand = match hand {
+                Some(hand) => match lees_kaarten(&hand)?[..] {
+                    [a, b] => Some((a, b)),
+                    _ => return Err(PokbotcomError::OngeldigeKaart(hand).into()),
+                },
+                None => None,
+            };
+            spelers.push(speler);
+        }
+        Ok(spelers)
+    }
+
+    fn laad_spellen(&self) -> Result<Vec<Spel>> {
+        let mut query = self
+            .verbinding
+            .prepare("SELECT spel FROM spellen ORDER BY id")?;
+        let rijen = query.query_map([], |rij| rij.get::<_, String>(0))?;
+
+        let mut spellen = Vec::new();
+        for json in rijen {
+            spellen.push(wire::lees_json(&json?)?);
+        }
+        Ok(spellen)
+    }
+
+    fn laad_toernooien(&self) -> Result<Vec<Toernooi>> {
+        let mut query = self
+            .verbinding
+            .prepare("SELECT toernooi FROM toernooien ORDER BY id")?;
+        let rijen = query.query_map([], |rij| rij.get::<_, String>(0))?;
+
+        let mut toernooien = Vec::new();
+        for json in rijen {
+            toernooien.push(wire::lees_json(&json?)?);
+        }
+        Ok(toernooien)
+    }
+
+    fn laad_tellers(&self) -> Result<Tellers> {
+        Ok(Tellers {
+            volgende_speler_id: self.teller("volgende_speler_id")?,
+            volgende_spel_id: self.teller("volgende_spel_id")?,
+            volgende_toernooi_id: self.teller("volgende_toernooi_id")?,
+        })
+    }
+
+    fn laad_sessies(&self) -> Result<Vec<BewaardeSessie>> {
+        let mut query = self
+            .verbinding
+            .prepare("SELECT sessie, speler_id, callback FROM sessies ORDER BY speler_id")?;
+        let sessies = query
+            .query_map([], |rij| {
+                Ok(BewaardeSessie {
+                    sessie: rij.get(0)?,
+                    speler_id: SpelerId(rij.get::<_, i64>(1)? as u64),
+                    callback: rij.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(sessies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toernooi::{NiveauDuur, ToernooiInstellingen};
+    use crate::{Actie, Centrale, SpelStatus};
+
+    fn speel(centrale: &mut Centrale, acties: &[(u64, Actie)]) {
+        let spel_id = *centrale.spellen[0].id.get().unwrap();
+        for (speler, actie) in acties {
+            centrale
+                .ontvang_actie(spel_id, SpelerId(*speler), *actie)
+                .unwrap();
+        }
+    }
+
+    fn vergelijk(a: &Centrale, b: &Centrale) {
+        let spelers = |c: &Centrale| {
+            c.spelers
+                .iter()
+                .map(|s| {
+                    (
+                        *s.id.get().unwrap(),
+                        s.naam.clone(),
+                        s.chips,
+                        s.hand,
+                        s.inzet,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let spellen = |c: &Centrale| {
+            c.spellen
+                .iter()
+                .map(|s| serde_json::to_value(s).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spelers(a), spelers(b));
+        assert_eq!(spellen(a), spellen(b));
+    }
+
+    #[test]
+    fn herstarten_midden_in_een_spel() {
+        let pad = std::env::temp_dir().join(format!("pokbotcom-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&pad);
+
+        let mut centrale = Centrale::new();
+        for naam in ["Aart", "Bart", "Cart"] {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap();
+        }
+        let spel_id = centrale
+            .maak_spel(vec![SpelerId(0), SpelerId(1), SpelerId(2)], Some(7))
+            .unwrap();
+        centrale.start_spel(spel_id).unwrap();
+        speel(
+            &mut centrale,
+            &[(0, Actie::Bet(40)), (1, Actie::Call), (2, Actie::Call)],
+        );
+
+        centrale
+            .bewaar_in_db(&mut SqliteOpslag::open(&pad).unwrap())
+            .unwrap();
+        let mut hersteld = Centrale::laad_uit_db(&SqliteOpslag::open(&pad).unwrap()).unwrap();
+        std::fs::remove_file(&pad).unwrap();
+
+        vergelijk(&centrale, &hersteld);
+        assert_eq!(hersteld.spelers[0].chips, 1000 - 60);
+        assert!(hersteld.spellen[0].tafel.0.is_some());
+
+        // beide spelen exact hetzelfde verder tot en met de volgende hand; de vergelijking
+        // omvat ook de toestand van de random generator
+        let rest = [1, 2, 0].repeat(3).into_iter().map(|s| (s, Actie::Check));
+        speel(&mut centrale, &rest.clone().collect::<Vec<_>>());
+        speel(&mut hersteld, &rest.collect::<Vec<_>>());
+        vergelijk(&centrale, &hersteld);
+
+        let ids = ["Dart", "Eart"].map(|naam| {
+            hersteld
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        assert_eq!(ids[0], SpelerId(3));
+        assert_eq!(hersteld.maak_spel(ids.to_vec(), None).unwrap().0, 1);
+    }
+
+    #[test]
+    fn wat_weg_is_wordt_verwijderd() {
+        let mut opslag = SqliteOpslag::in_geheugen().unwrap();
+        let mut centrale = Centrale::new();
+        for naam in ["Aart", "Bart", "Cart"] {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap();
+        }
+        centrale
+            .maak_spel(vec![SpelerId(0), SpelerId(1)], None)
+            .unwrap();
+        centrale
+            .maak_spel(vec![SpelerId(1), SpelerId(2)], None)
+            .unwrap();
+        centrale.bewaar_in_db(&mut opslag).unwrap();
+
+        centrale.spellen.remove(0);
+        centrale.spelers.remove(0);
+        centrale.bewaar_in_db(&mut opslag).unwrap();
+        let hersteld = Centrale::laad_uit_db(&opslag).unwrap();
+        vergelijk(&centrale, &hersteld);
+        assert_eq!(hersteld.spellen[0].id.get(), Some(&SpelId(1)));
+
+        // een transactie die mislukt, laat niets achter
+        let sessie = BewaardeSessie {
+            sessie: "abc".into(),
+            speler_id: SpelerId(1),
+            callback: Some("http://localhost/bot".into()),
+        };
+        let fout = opslag.in_transactie(&mut |opslag| {
+            opslag.bewaar_sessie(&sessie)?;
+            anyhow::bail!("mislukt")
+        });
+        assert!(fout.is_err());
+        assert!(opslag.laad_sessies().unwrap().is_empty());
+        opslag.bewaar_sessie(&sessie).unwrap();
+        assert_eq!(opslag.laad_sessies().unwrap(), vec![sessie]);
+    }
+
+    // checkt of callt tot de hand aan deze tafel gedaan is
+    fn speel_hand_uit(centrale: &mut Centrale, spel_id: SpelId) {
+        let handen = centrale.get_spel(spel_id).historie.len();
+        while centrale.get_spel(spel_id).historie.len() == handen {
+            let spel = centrale.get_spel(spel_id);
+            let id = spel.spelers[spel.aan_de_beurt];
+            let actie = match centrale.legale_acties(spel_id, id).unwrap().check {
+                true => Actie::Check,
+                false => Actie::Call,
+            };
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+    }
+
+    #[test]
+    fn toernooi_na_herstarten() {
+        let mut opslag = SqliteOpslag::in_geheugen().unwrap();
+        let mut centrale = Centrale::new();
+        let ids = ["Aart", "Bart", "Cart"].map(|naam| {
+            centrale
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let instellingen = ToernooiInstellingen {
+            niveau_duur: NiveauDuur::Handen(2),
+            ..Default::default()
+        };
+        let toernooi_id = centrale
+            .maak_toernooi(ids.to_vec(), instellingen, Some(3))
+            .unwrap();
+        centrale.start_toernooi(toernooi_id).unwrap();
+        let spel_id = centrale.toernooi(toernooi_id).unwrap().spellen[0];
+        speel_hand_uit(&mut centrale, spel_id);
+        speel(&mut centrale, &[(1, Actie::Call)]);
+        centrale.bewaar_in_db(&mut opslag).unwrap();
+
+        let mut hersteld = Centrale::laad_uit_db(&opslag).unwrap();
+        vergelijk(&centrale, &hersteld);
+        let stand = |c: &Centrale| {
+            let t = c.toernooi(toernooi_id).unwrap();
+            (
+                t.spelers.clone(),
+                t.spellen.clone(),
+                t.niveau,
+                t.handen_in_niveau,
+                t.uitgeschakeld.clone(),
+                t.onderweg.clone(),
+                t.status,
+                t.instellingen.clone(),
+            )
+        };
+        assert_eq!(stand(&hersteld), stand(&centrale));
+        assert_eq!(hersteld.toernooi(toernooi_id).unwrap().handen_in_niveau, 1);
+
+        // beide spelen de hand verder uit en gaan samen naar het volgende niveau
+        speel_hand_uit(&mut centrale, spel_id);
+        speel_hand_uit(&mut hersteld, spel_id);
+        vergelijk(&centrale, &hersteld);
+        assert_eq!(stand(&hersteld), stand(&centrale));
+        let toernooi = hersteld.toernooi(toernooi_id).unwrap();
+        assert_eq!((toernooi.niveau, toernooi.status), (1, SpelStatus::Lopend));
+        assert_eq!(hersteld.get_spel(spel_id).niveau, toernooi.huidig_niveau());
+
+        // een nieuw toernooi krijgt een nieuwe id
+        let nieuw = ["Dart", "Eart"].map(|naam| {
+            hersteld
+                .registreer_speler(Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let nieuw_id = hersteld
+            .maak_toernooi(nieuw.to_vec(), Default::default(), None)
+            .unwrap();
+        assert_ne!(nieuw_id, toernooi_id);
+    }
+
+    #[test]
+    fn lege_databank() {
+        let hersteld = Centrale::laad_uit_db(&SqliteOpslag::in_geheugen().unwrap()).unwrap();
+        assert!(hersteld.spelers.is_empty());
+        assert!(hersteld.spellen.is_empty());
+    }
+}
@@ -0,0 +1,1189 @@
+// De HTTP-API uit spec.md. Een speler registreert zich met `POST /spelers` en krijgt een
+// sessietoken terug; alle andere endpoints verwachten dat token in de header
+// `Authorization: Bearer <token>` en handelen in naam van de bijhorende speler.
+//
+//   POST /spelers                    {"naam": "...", "callback": "http://..."}
+//   POST /spellen                    {"spelers": [0, 1], "rng_seed": 42}
+//                                    met eventueel "bet_structuur": {"soort": "pot_limit"},
+//                                    "niveau": {"small_blind": 25, "big_blind": 50, "ante": 5}
+//                                    en "verplichte_inzetten": {"straddle": true}
+//   POST /spellen/{spel_id}/start
+//   POST /spellen/{spel_id}/acties   {"soort": "bet", "chips": 50}
+//   GET  /spellen/{spel_id}          de toestand van het spel zoals de speler die ziet
+//   GET  /spellen/{spel_id}/ws       WebSocket met alle meldingen van het spel
+//   POST /spellen/{spel_id}/icm      {"prijzen": [500, 300, 200]}
+//                                    de ICM-equity van elke speler aan tafel voor die prijzen,
+//                                    enkel voor wie zelf aan die tafel zit
+//
+// Over de WebSocket stuurt de server elke `PokbotcomMelding` als JSON (zie `MeldingBericht`)
+// en kan de speler zijn acties terugsturen in hetzelfde formaat als `POST .../acties`. Het
+// formaat van alle berichten staat in `schema/pokbotcom-v1.schema.json`. Elke JSON-body mag, net
+// als over de WebSocket, een `versie` meegeven; een onbekende versie wordt geweigerd (zie
+// `Bericht`). Een client die meer dan `MELDINGEN_BUFFER` meldingen achterloopt, wordt afgesloten
+// met code 1013 en moet opnieuw verbinden en de toestand opvragen.
+// Omdat een browser geen headers kan meegeven bij een WebSocket, mag het token daar ook als
+// `?sessie=<token>` in de URL staan.
+//
+// Een speler die zich registreert met een `callback`, wordt zelf door de server aangeroepen
+// wanneer hij aan de beurt is (zie `webhook`).
+//
+// Met een `Opslag` (zie `router_met_opslag`) bewaart de server na elke wijziging wat er
+// veranderde, samen met de sessies, en gaat hij na een herstart verder waar hij was.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{FromRequest, FromRequestParts, Path, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rand::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::kaart::Kaart;
+use crate::kanaal::MeldingKanaal;
+use crate::opslag::{BewaardeSessie, Opslag};
+use crate::wire;
+pub use webhook::{BeurtVerzoek, WebhookInstellingen};
+
+use crate::{
+    Actie, BetStructuur, BlindNiveau, Centrale, PokbotcomError, PokbotcomMelding, SpelId,
+    SpelStatus, Speler, SpelerId, VerplichteInzetten,
+};
+
+// hoeveel meldingen een trage WebSocket-client mag achterlopen voor hij er mist
+const MELDINGEN_BUFFER: usize = 256;
+
+mod webhook;
+
+#[derive(Debug, Default)]
+pub struct ServerStaat {
+    pub centrale: Centrale,
+    pub webhook: WebhookInstellingen,
+    sessies: HashMap<String, SpelerId>,
+    meldingen: HashMap<SpelerId, broadcast::Sender<(Option<SpelId>, PokbotcomMelding)>>,
+    opslag: Option<Box<dyn Opslag + Send>>,
+}
+
+impl ServerStaat {
+    // Bewaart deze spelers en spellen, en eventueel een nieuwe sessie, als er een opslag is.
+    fn bewaar(
+        &mut self,
+        spelers: &[SpelerId],
+        spellen: &[SpelId],
+        sessie: Option<&BewaardeSessie>,
+    ) -> Result<(), ApiFout> {
+        let Some(opslag) = self.opslag.as_mut() else {
+            return Ok(());
+        };
+        let centrale = &self.centrale;
+        opslag.in_transactie(&mut |opslag| {
+            centrale.bewaar_deel_in_db(opslag, spelers, spellen)?;
+            match sessie {
+                Some(sessie) => opslag.bewaar_sessie(sessie),
+                None => Ok(()),
+            }
+        })?;
+        Ok(())
+    }
+
+    // bewaart een spel met iedereen die eraan meespeelt
+    fn bewaar_spel(&mut self, spel_id: SpelId) -> Result<(), ApiFout> {
+        let spelers = self.centrale.get_spel(spel_id).spelers.clone();
+        self.bewaar(&spelers, &[spel_id], None)
+    }
+}
+
+// Het kanaal van elke speler die zich via de API registreerde. Meldingen gaan, met het spel
+// waaruit ze komen, naar zijn WebSockets; elke WebSocket laat enkel die van zijn eigen spel
+// door. Zonder verbonden WebSocket gaan ze verloren, maar de toestand blijft opvraagbaar via
+// `GET /spellen/{spel_id}`. Heeft de speler een callback, dan wordt die bij elke beurt
+// aangeroepen.
+#[derive(Debug)]
+struct SpelerKanaal {
+    meldingen: broadcast::Sender<(Option<SpelId>, PokbotcomMelding)>,
+    beurten: Option<mpsc::UnboundedSender<SpelId>>, // in welk spel de speler aan de beurt is
+}
+
+impl MeldingKanaal for SpelerKanaal {
+    // een melding die niet bij een spel hoort, gaat naar al zijn WebSockets
+    fn stuur(&self, melding: PokbotcomMelding) {
+        let _ = self.meldingen.send((None, melding));
+    }
+
+    fn stuur_voor(&self, spel_id: SpelId, melding: PokbotcomMelding) {
+        if let (PokbotcomMelding::AanDeBeurt(_), Some(beurten)) = (&melding, &self.beurten) {
+            let _ = beurten.send(spel_id);
+        }
+        let _ = self.meldingen.send((Some(spel_id), melding));
+    }
+}
+
+pub type GedeeldeStaat = Arc<Mutex<ServerStaat>>;
+
+pub fn router(centrale: Centrale) -> Router {
+    router_met_webhooks(centrale, WebhookInstellingen::default())
+}
+
+pub fn router_met_webhooks(centrale: Centrale, webhook: WebhookInstellingen) -> Router {
+    maak_router(Arc::new(Mutex::new(ServerStaat {
+        centrale,
+        webhook,
+        ..Default::default()
+    })))
+}
+
+// Laadt de spelers, spellen en sessies uit `opslag` en bewaart daar elke wijziging. Wie aan
+// de beurt was, krijgt dat opnieuw te horen, zodat zijn webhook weer aangeroepen wordt.
+pub fn router_met_opslag(
+    opslag: impl Opslag + Send + 'static,
+    webhook: WebhookInstellingen,
+) -> anyhow::Result<Router> {
+    let sessies = opslag.laad_sessies()?;
+    let gedeelde_staat = Arc::new(Mutex::new(ServerStaat {
+        centrale: Centrale::laad_uit_db(&opslag)?,
+        webhook,
+        opslag: Some(Box::new(opslag)),
+        ..Default::default()
+    }));
+
+    {
+        let mut staat = gedeelde_staat.lock().unwrap();
+        for sessie in sessies {
+            let callback = sessie.callback.as_deref().map(reqwest::Url::parse);
+            verbind(
+                &gedeelde_staat,
+                &mut staat,
+                sessie.speler_id,
+                callback.transpose()?,
+            );
+            staat.sessies.insert(sessie.sessie, sessie.speler_id);
+        }
+
+        let centrale = &staat.centrale;
+        for spel in centrale.spellen.iter() {
+            if spel.status == SpelStatus::Lopend {
+                let spel_id = *spel.id.get().unwrap();
+                let speler_id = spel.spelers[spel.aan_de_beurt];
+                let legaal = centrale.legale_acties(spel_id, speler_id)?;
+                centrale
+                    .get_speler(speler_id)
+                    .stuur_bericht(spel_id, PokbotcomMelding::AanDeBeurt(legaal));
+            }
+        }
+    }
+
+    Ok(maak_router(gedeelde_staat))
+}
+
+fn maak_router(staat: GedeeldeStaat) -> Router {
+    Router::new()
+        .route("/spelers", post(registreer))
+        .route("/spellen", post(maak_spel))
+        .route("/spellen/{spel_id}", get(toestand))
+        .route("/spellen/{spel_id}/start", post(start_spel))
+        .route("/spellen/{spel_id}/acties", post(actie))
+        .route("/spellen/{spel_id}/ws", get(abonneer))
+        .route("/spellen/{spel_id}/icm", post(icm))
+        .with_state(staat)
+}
+
+#[derive(Debug)]
+pub struct ApiFout(StatusCode, String);
+
+impl IntoResponse for ApiFout {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Fout {
+            fout: String,
+        }
+
+        (self.0, Json(Fout { fout: self.1 })).into_response()
+    }
+}
+
+impl From<PokbotcomError> for ApiFout {
+    fn from(fout: PokbotcomError) -> Self {
+        let status = match fout {
+            PokbotcomError::NietJouwBeurt
+            | PokbotcomError::SpelerAlGeregistreerd(_)
+            | PokbotcomError::SpelNietBezig(_)
+            | PokbotcomError::SpelAlBegonnen(_)
+            | PokbotcomError::SpeeltAl(..) => StatusCode::CONFLICT,
+            PokbotcomError::OnbekendSpel(_) | PokbotcomError::OnbekendeSpeler(_) => {
+                StatusCode::NOT_FOUND
+            }
+            PokbotcomError::NietInSpel(..) => StatusCode::FORBIDDEN,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        ApiFout(status, fout.to_string())
+    }
+}
+
+impl From<anyhow::Error> for ApiFout {
+    fn from(fout: anyhow::Error) -> Self {
+        match fout.downcast::<PokbotcomError>() {
+            Ok(fout) => fout.into(),
+            Err(fout) => ApiFout(StatusCode::INTERNAL_SERVER_ERROR, fout.to_string()),
+        }
+    }
+}
+
+// De speler die bij het meegestuurde sessietoken hoort.
+pub struct Sessie(pub SpelerId);
+
+impl FromRequestParts<GedeeldeStaat> for Sessie {
+    type Rejection = ApiFout;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        staat: &GedeeldeStaat,
+    ) -> Result<Self, Self::Rejection> {
+        let geen_sessie = || {
+            ApiFout(
+                StatusCode::UNAUTHORIZED,
+                "Geen geldig sessietoken meegegeven.".to_string(),
+            )
+        };
+
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .or_else(|| {
+                parts
+                    .uri
+                    .query()?
+                    .split('&')
+                    .find_map(|p| p.strip_prefix("sessie="))
+            })
+            .ok_or_else(geen_sessie)?;
+
+        let staat = staat.lock().unwrap();
+        staat
+            .sessies
+            .get(token)
+            .map(|id| Sessie(*id))
+            .ok_or_else(geen_sessie)
+    }
+}
+
+// Een JSON-body in een `Envelop`, gelezen met `wire::lees_json`, zodat een onbekende versie
+// hier op dezelfde manier geweigerd wordt als over de WebSocket.
+pub struct Bericht<T>(pub T);
+
+impl<T: DeserializeOwned, S: Send + Sync> FromRequest<S> for Bericht<T> {
+    type Rejection = ApiFout;
+
+    async fn from_request(verzoek: Request, staat: &S) -> Result<Self, Self::Rejection> {
+        let tekst = String::from_request(verzoek, staat)
+            .await
+            .map_err(|fout| ApiFout(StatusCode::BAD_REQUEST, fout.body_text()))?;
+        Ok(Bericht(wire::lees_json(&tekst)?))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegistratieVerzoek {
+    pub naam: String,
+    // waar de server de bot aanroept wanneer hij aan de beurt is
+    #[serde(default)]
+    pub callback: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegistratieAntwoord {
+    pub speler_id: u64,
+    pub sessie: String,
+}
+
+async fn registreer(
+    State(gedeelde_staat): State<GedeeldeStaat>,
+    Bericht(verzoek): Bericht<RegistratieVerzoek>,
+) -> Result<Json<RegistratieAntwoord>, ApiFout> {
+    let callback = verzoek
+        .callback
+        .map(|url| {
+            reqwest::Url::parse(&url).map_err(|fout| {
+                ApiFout(
+                    StatusCode::BAD_REQUEST,
+                    format!("\"{url}\" is geen geldige callback: {fout}."),
+                )
+            })
+        })
+        .transpose()?;
+
+    let mut staat = gedeelde_staat.lock().unwrap();
+    let speler_id = staat
+        .centrale
+        .registreer_speler(Speler::new_zonder_id(verzoek.naam))?;
+    let sessie = BewaardeSessie {
+        sessie: format!("{:032x}", thread_rng().gen::<u128>()),
+        speler_id,
+        callback: callback.as_ref().map(|url| url.to_string()),
+    };
+    verbind(&gedeelde_staat, &mut staat, speler_id, callback);
+    staat.sessies.insert(sessie.sessie.clone(), speler_id);
+    staat.bewaar(&[speler_id], &[], Some(&sessie))?;
+
+    Ok(Json(RegistratieAntwoord {
+        speler_id: speler_id.0,
+        sessie: sessie.sessie,
+    }))
+}
+
+// Geeft de speler een `SpelerKanaal` en bedient zijn callback, als hij er een heeft.
+fn verbind(
+    gedeelde_staat: &GedeeldeStaat,
+    staat: &mut ServerStaat,
+    speler_id: SpelerId,
+    callback: Option<reqwest::Url>,
+) {
+    let (meldingen, _) = broadcast::channel(MELDINGEN_BUFFER);
+    let beurten = callback.map(|url| {
+        let (beurten, ontvanger) = mpsc::unbounded_channel();
+        tokio::spawn(webhook::bedien(
+            Arc::downgrade(gedeelde_staat),
+            speler_id,
+            url,
+            ontvanger,
+        ));
+        beurten
+    });
+    let kanaal = SpelerKanaal {
+        meldingen: meldingen.clone(),
+        beurten,
+    };
+
+    staat.centrale.get_mut_speler(speler_id).kanaal = Some(Arc::new(kanaal));
+    staat.meldingen.insert(speler_id, meldingen);
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpelVerzoek {
+    pub spelers: Vec<u64>,
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
+    #[serde(default)]
+    pub bet_structuur: BetStructuur,
+    #[serde(default)]
+    pub niveau: BlindNiveau,
+    #[serde(default)]
+    pub verplichte_inzetten: VerplichteInzetten,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpelAntwoord {
+    pub spel_id: u64,
+}
+
+async fn maak_spel(
+    State(staat): State<GedeeldeStaat>,
+    Sessie(_): Sessie,
+    Bericht(verzoek): Bericht<SpelVerzoek>,
+) -> Result<Json<SpelAntwoord>, ApiFout> {
+    // eerst controleren, zodat er geen spel achterblijft met instellingen die niet kunnen
+    verzoek.bet_structuur.controleer()?;
+    verzoek.niveau.controleer()?;
+    let mut staat = staat.lock().unwrap();
+    let spelers = verzoek
+        .spelers
+        .into_iter()
+        .map(SpelerId)
+        .collect::<Vec<_>>();
+
+    let spel_id = staat.centrale.maak_spel(spelers, verzoek.rng_seed)?;
+    staat
+        .centrale
+        .zet_bet_structuur(spel_id, verzoek.bet_structuur)?;
+    staat
+        .centrale
+        .zet_verplichte_inzetten(spel_id, verzoek.niveau, verzoek.verplichte_inzetten)?;
+    staat.bewaar(&[], &[spel_id], None)?;
+    Ok(Json(SpelAntwoord { spel_id: spel_id.0 }))
+}
+
+fn controleer_spel(staat: &ServerStaat, spel_id: SpelId) -> Result<SpelStatus, ApiFout> {
+    staat
+        .centrale
+        .spellen
+        .iter()
+        .find(|s| s.id.get() == Some(&spel_id))
+        .map(|s| s.status)
+        .ok_or_else(|| PokbotcomError::OnbekendSpel(spel_id).into())
+}
+
+async fn start_spel(
+    State(staat): State<GedeeldeStaat>,
+    Sessie(_): Sessie,
+    Path(spel_id): Path<u64>,
+) -> Result<StatusCode, ApiFout> {
+    let mut staat = staat.lock().unwrap();
+    let spel_id = SpelId(spel_id);
+    if controleer_spel(&staat, spel_id)? != SpelStatus::Wachtend {
+        return Err(ApiFout(
+            StatusCode::CONFLICT,
+            format!("Spel {spel_id:?} is al gestart."),
+        ));
+    }
+
+    staat.centrale.start_spel(spel_id)?;
+    staat.bewaar_spel(spel_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn voer_actie_uit(
+    staat: &mut ServerStaat,
+    spel_id: SpelId,
+    speler_id: SpelerId,
+    actie: Actie,
+) -> Result<(), ApiFout> {
+    if controleer_spel(staat, spel_id)? != SpelStatus::Lopend {
+        return Err(PokbotcomError::SpelNietBezig(spel_id).into());
+    }
+
+    staat.centrale.ontvang_actie(spel_id, speler_id, actie)?;
+    staat.bewaar_spel(spel_id)
+}
+
+async fn actie(
+    State(staat): State<GedeeldeStaat>,
+    Sessie(speler_id): Sessie,
+    Path(spel_id): Path<u64>,
+    Bericht(actie): Bericht<Actie>,
+) -> Result<StatusCode, ApiFout> {
+    let mut staat = staat.lock().unwrap();
+    voer_actie_uit(&mut staat, SpelId(spel_id), speler_id, actie)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IcmVerzoek {
+    pub prijzen: Vec<u64>, // per plaats, vanaf de winnaar
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IcmEquity {
+    pub speler_id: u64,
+    pub chips: u64, // inclusief wat hij in deze hand al ingezet heeft
+    pub equity: f64,
+}
+
+async fn icm(
+    State(staat): State<GedeeldeStaat>,
+    Sessie(speler_id): Sessie,
+    Path(spel_id): Path<u64>,
+    Bericht(verzoek): Bericht<IcmVerzoek>,
+) -> Result<Json<Vec<IcmEquity>>, ApiFout> {
+    let spel_id = SpelId(spel_id);
+    let staat = staat.lock().unwrap();
+    controleer_spel(&staat, spel_id)?;
+    if !staat
+        .centrale
+        .get_spel(spel_id)
+        .spelers
+        .contains(&speler_id)
+    {
+        return Err(PokbotcomError::NietInSpel(speler_id, spel_id).into());
+    }
+    let equity = staat.centrale.icm(spel_id, &verzoek.prijzen)?;
+    Ok(Json(
+        equity
+            .into_iter()
+            .map(|(speler_id, equity)| {
+                let speler = staat.centrale.get_speler(speler_id);
+                IcmEquity {
+                    speler_id: speler_id.0,
+                    chips: speler.chips + speler.inzet,
+                    equity,
+                }
+            })
+            .collect(),
+    ))
+}
+
+// Wat de server over de WebSocket stuurt: een melding uit het spel, of het antwoord op een
+// actie die geweigerd werd.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MeldingBericht {
+    Melding(PokbotcomMelding),
+    Fout { fout: String },
+}
+
+async fn abonneer(
+    State(staat): State<GedeeldeStaat>,
+    Sessie(speler_id): Sessie,
+    Path(spel_id): Path<u64>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiFout> {
+    let spel_id = SpelId(spel_id);
+    let meldingen = {
+        let staat = staat.lock().unwrap();
+        controleer_spel(&staat, spel_id)?;
+        if !staat
+            .centrale
+            .get_spel(spel_id)
+            .spelers
+            .contains(&speler_id)
+        {
+            return Err(PokbotcomError::NietInSpel(speler_id, spel_id).into());
+        }
+        staat.meldingen[&speler_id].subscribe()
+    };
+
+    Ok(ws.on_upgrade(move |socket| verbinding(socket, staat, spel_id, speler_id, meldingen)))
+}
+
+async fn verstuur(socket: &mut WebSocket, bericht: MeldingBericht) -> bool {
+    let json = wire::naar_json(bericht);
+    socket.send(Message::Text(json.into())).await.is_ok()
+}
+
+async fn verbinding(
+    mut socket: WebSocket,
+    staat: GedeeldeStaat,
+    spel_id: SpelId,
+    speler_id: SpelerId,
+    mut meldingen: broadcast::Receiver<(Option<SpelId>, PokbotcomMelding)>,
+) {
+    loop {
+        tokio::select! {
+            melding = meldingen.recv() => match melding {
+                Ok((van, melding)) if van.is_none_or(|van| van == spel_id) => {
+                    if !verstuur(&mut socket, MeldingBericht::Melding(melding)).await {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                // een client die meldingen gemist heeft, zou misschien nooit horen dat hij aan
+                // de beurt is: hij moet opnieuw verbinden en de toestand opvragen
+                Err(broadcast::error::RecvError::Lagged(gemist)) => {
+                    let sluiten = CloseFrame {
+                        code: close_code::AGAIN,
+                        reason: format!("{gemist} meldingen gemist").into(),
+                    };
+                    let _ = socket.send(Message::Close(Some(sluiten))).await;
+                    break;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            bericht = socket.recv() => match bericht {
+                Some(Ok(Message::Text(tekst))) => {
+                    let res = match wire::lees_json::<Actie>(&tekst) {
+                        Ok(actie) => {
+                            let mut staat = staat.lock().unwrap();
+                            voer_actie_uit(&mut staat, spel_id, speler_id, actie)
+                        }
+                        Err(fout) => Err(fout.into()),
+                    };
+                    if let Err(ApiFout(_, fout)) = res {
+                        if !verstuur(&mut socket, MeldingBericht::Fout { fout }).await {
+                            break;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+// Wat een speler van een spel mag zien: zijn eigen hand, maar niet die van de anderen.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpelToestand {
+    pub spel_id: u64,
+    pub status: SpelStatus,
+    pub bet_structuur: BetStructuur,
+    pub spelers: Vec<u64>,
+    pub aan_de_beurt: u64,
+    pub hand: Option<[Kaart; 2]>,
+    pub tafel: Vec<Kaart>,
+    pub pot: u64,
+    pub huidige_inzet: u64,
+    pub chips: u64,
+    pub inzet: u64,
+}
+
+async fn toestand(
+    State(staat): State<GedeeldeStaat>,
+    Sessie(speler_id): Sessie,
+    Path(spel_id): Path<u64>,
+) -> Result<Json<SpelToestand>, ApiFout> {
+    let staat = staat.lock().unwrap();
+    let spel_id = SpelId(spel_id);
+    controleer_spel(&staat, spel_id)?;
+
+    Ok(Json(toestand_voor(&staat, spel_id, speler_id)))
+}
+
+fn toestand_voor(staat: &ServerStaat, spel_id: SpelId, speler_id: SpelerId) -> SpelToestand {
+    let spel = staat
+        .centrale
+        .spellen
+        .iter()
+        .find(|s| s.id.get() == Some(&spel_id))
+        .unwrap();
+    let speler = staat
+        .centrale
+        .spelers
+        .iter()
+        .find(|s| s.id.get() == Some(&speler_id))
+        .unwrap();
+
+    let mut tafel = Vec::new();
+    if let Some((a, b, c)) = spel.tafel.0 {
+        tafel.extend([a, b, c]);
+    }
+    tafel.extend(spel.tafel.1);
+    tafel.extend(spel.tafel.2);
+
+    SpelToestand {
+        spel_id: spel_id.0,
+        status: spel.status,
+        bet_structuur: spel.bet_structuur,
+        spelers: spel.spelers.iter().map(|id| id.0).collect(),
+        aan_de_beurt: spel.spelers[spel.aan_de_beurt].0,
+        hand: speler
+            .hand
+            .filter(|_| spel.spelers.contains(&speler_id))
+            .map(|(a, b)| [a, b]),
+        tafel,
+        pot: spel.pot(),
+        huidige_inzet: spel.huidige_inzet,
+        chips: speler.chips,
+        inzet: speler.inzet,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use serde::de::DeserializeOwned;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::wire::tests::volgens_schema;
+    use crate::{LegaleActies, CHIPS_PER_SPELER};
+
+    pub(super) async fn verzoek(
+        app: &Router,
+        methode: &str,
+        pad: &str,
+        sessie: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> Response {
+        let mut verzoek = Request::builder().method(methode).uri(pad);
+        if let Some(sessie) = sessie {
+            verzoek = verzoek.header(header::AUTHORIZATION, format!("Bearer {sessie}"));
+        }
+        let body = match body {
+            Some(json) => {
+                verzoek = verzoek.header(header::CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+        app.clone()
+            .oneshot(verzoek.body(body).unwrap())
+            .await
+            .unwrap()
+    }
+
+    pub(super) async fn json<T: DeserializeOwned>(antwoord: Response) -> T {
+        let bytes = antwoord.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    pub(super) async fn registreer_met(
+        app: &Router,
+        body: serde_json::Value,
+    ) -> RegistratieAntwoord {
+        let antwoord = verzoek(app, "POST", "/spelers", None, Some(body)).await;
+        assert_eq!(antwoord.status(), StatusCode::OK);
+        json(antwoord).await
+    }
+
+    async fn registreer(app: &Router, naam: &str) -> RegistratieAntwoord {
+        registreer_met(app, serde_json::json!({ "naam": naam })).await
+    }
+
+    #[tokio::test]
+    async fn spel_via_http() {
+        let app = router(Centrale::new());
+        let aart = registreer(&app, "Aart").await;
+        let bart = registreer(&app, "Bart").await;
+        assert_ne!(aart.sessie, bart.sessie);
+
+        let zonder_sessie = verzoek(
+            &app,
+            "POST",
+            "/spellen",
+            None,
+            Some(serde_json::json!({ "spelers": [0, 1] })),
+        )
+        .await;
+        assert_eq!(zonder_sessie.status(), StatusCode::UNAUTHORIZED);
+
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            "/spellen",
+            Some(&aart.sessie),
+            Some(serde_json::json!({ "spelers": [aart.speler_id, bart.speler_id], "rng_seed": 0 })),
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::OK);
+        let SpelAntwoord { spel_id } = json(antwoord).await;
+
+        let niet_gestart = verzoek(
+            &app,
+            "POST",
+            &format!("/spellen/{spel_id}/acties"),
+            Some(&aart.sessie),
+            Some(serde_json::json!({ "soort": "call" })),
+        )
+        .await;
+        assert_eq!(niet_gestart.status(), StatusCode::CONFLICT);
+
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            &format!("/spellen/{spel_id}/start"),
+            Some(&aart.sessie),
+            None,
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::NO_CONTENT);
+
+        let toestand: serde_json::Value = json(
+            verzoek(
+                &app,
+                "GET",
+                &format!("/spellen/{spel_id}"),
+                Some(&aart.sessie),
+                None,
+            )
+            .await,
+        )
+        .await;
+        volgens_schema("spel_toestand", &toestand).unwrap();
+        let toestand: SpelToestand = serde_json::from_value(toestand).unwrap();
+        assert_eq!(toestand.status, SpelStatus::Lopend);
+        assert!(toestand.hand.is_some());
+        assert!(toestand.tafel.is_empty());
+
+        let (aan_de_beurt, wacht) = if toestand.aan_de_beurt == aart.speler_id {
+            (&aart, &bart)
+        } else {
+            (&bart, &aart)
+        };
+
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            &format!("/spellen/{spel_id}/acties"),
+            Some(&wacht.sessie),
+            Some(serde_json::json!({ "soort": "call" })),
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::CONFLICT);
+
+        // een actie in een onbekende versie van het formaat wordt geweigerd
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            &format!("/spellen/{spel_id}/acties"),
+            Some(&aan_de_beurt.sessie),
+            Some(serde_json::json!({ "versie": 2, "soort": "call" })),
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::BAD_REQUEST);
+        volgens_schema("fout", &json(antwoord).await).unwrap();
+
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            &format!("/spellen/{spel_id}/acties"),
+            Some(&aan_de_beurt.sessie),
+            Some(serde_json::json!({ "versie": 1, "soort": "call" })),
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::NO_CONTENT);
+
+        let equity: serde_json::Value = json(
+            verzoek(
+                &app,
+                "POST",
+                &format!("/spellen/{spel_id}/icm"),
+                Some(&aart.sessie),
+                Some(serde_json::json!({ "prijzen": [700, 300] })),
+            )
+            .await,
+        )
+        .await;
+        for e in equity.as_array().unwrap() {
+            volgens_schema("icm_equity", e).unwrap();
+        }
+        let equity: Vec<IcmEquity> = serde_json::from_value(equity).unwrap();
+        assert_eq!(equity.len(), 2);
+        assert!(equity.iter().all(|e| e.chips == CHIPS_PER_SPELER));
+        assert!(equity.iter().all(|e| (e.equity - 500.0).abs() < 1e-9));
+        let onbekend = verzoek(
+            &app,
+            "POST",
+            "/spellen/99/icm",
+            Some(&aart.sessie),
+            Some(serde_json::json!({ "prijzen": [100] })),
+        )
+        .await;
+        assert_eq!(onbekend.status(), StatusCode::NOT_FOUND);
+        let toeschouwer = registreer(&app, "Cart").await;
+        let niet_aan_tafel = verzoek(
+            &app,
+            "POST",
+            &format!("/spellen/{spel_id}/icm"),
+            Some(&toeschouwer.sessie),
+            Some(serde_json::json!({ "prijzen": [700, 300] })),
+        )
+        .await;
+        assert_eq!(niet_aan_tafel.status(), StatusCode::FORBIDDEN);
+    }
+
+    type Client = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    async fn ontvang(client: &mut Client) -> MeldingBericht {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        loop {
+            let bericht = tokio::time::timeout(std::time::Duration::from_secs(5), client.next())
+                .await
+                .expect("geen melding ontvangen")
+                .unwrap()
+                .unwrap();
+            if let Message::Text(tekst) = bericht {
+                return wire::lees_json(&tekst).unwrap();
+            }
+        }
+    }
+
+    async fn stuur(client: &mut Client, actie: serde_json::Value) {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        client
+            .send(Message::Text(actie.to_string().into()))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn meldingen_via_websocket() {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+        let app = router(Centrale::new());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let adres = listener.local_addr().unwrap();
+        let app_kopie = app.clone();
+        tokio::spawn(async move { axum::serve(listener, app_kopie).await });
+
+        let aart = registreer(&app, "Aart").await;
+        let bart = registreer(&app, "Bart").await;
+        let cart = registreer(&app, "Cart").await;
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            "/spellen",
+            Some(&aart.sessie),
+            Some(serde_json::json!({ "spelers": [bart.speler_id, aart.speler_id], "rng_seed": 0 })),
+        )
+        .await;
+        let SpelAntwoord { spel_id } = json(antwoord).await;
+        let url = format!("ws://{adres}/spellen/{spel_id}/ws");
+
+        // wie niet meespeelt, kan niet meekijken
+        let fout = tokio_tungstenite::connect_async(format!("{url}?sessie={}", cart.sessie))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&fout, tokio_tungstenite::tungstenite::Error::Http(a) if a.status() == StatusCode::FORBIDDEN),
+            "{fout}"
+        );
+
+        let mut met_header = url.as_str().into_client_request().unwrap();
+        met_header.headers_mut().insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", aart.sessie).parse().unwrap(),
+        );
+        let (mut ws_aart, _) = tokio_tungstenite::connect_async(met_header).await.unwrap();
+        let (mut ws_bart, _) =
+            tokio_tungstenite::connect_async(format!("{url}?sessie={}", bart.sessie))
+                .await
+                .unwrap();
+
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            &format!("/spellen/{spel_id}/start"),
+            Some(&aart.sessie),
+            None,
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::NO_CONTENT);
+
+        for ws in [&mut ws_aart, &mut ws_bart] {
+            assert!(matches!(
+                ontvang(ws).await,
+                MeldingBericht::Melding(PokbotcomMelding::Hand(..))
+            ));
+        }
+
+        // Bart is de dealer, zit dus op de small blind en is als eerste aan de beurt
+        assert!(matches!(
+            ontvang(&mut ws_bart).await,
+            MeldingBericht::Melding(PokbotcomMelding::AanDeBeurt(LegaleActies {
+                te_callen: 10,
+                ..
+            }))
+        ));
+        stuur(&mut ws_aart, serde_json::json!({ "soort": "check" })).await;
+        assert!(matches!(
+            ontvang(&mut ws_aart).await,
+            MeldingBericht::Fout { .. }
+        ));
+
+        stuur(&mut ws_bart, serde_json::json!({ "soort": "call" })).await;
+        assert_eq!(
+            ontvang(&mut ws_aart).await,
+            MeldingBericht::Melding(PokbotcomMelding::SpelerActie(
+                SpelerId(bart.speler_id),
+                Actie::Call
+            ))
+        );
+        assert_eq!(
+            ontvang(&mut ws_aart).await,
+            MeldingBericht::Melding(PokbotcomMelding::AanDeBeurt(LegaleActies {
+                fold: true,
+                check: true,
+                bet: true,
+                min_bet: 20,
+                max_bet: 980,
+                all_in: true,
+                ..Default::default()
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn meldingen_per_spel() {
+        use futures_util::StreamExt;
+
+        let app = router(Centrale::new());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let adres = listener.local_addr().unwrap();
+        let app_kopie = app.clone();
+        tokio::spawn(async move { axum::serve(listener, app_kopie).await });
+
+        let aart = registreer(&app, "Aart").await;
+        let bart = registreer(&app, "Bart").await;
+        let cart = registreer(&app, "Cart").await;
+        let mut spel_ids = Vec::new();
+        for spelers in [
+            [bart.speler_id, aart.speler_id],
+            [aart.speler_id, cart.speler_id],
+        ] {
+            let antwoord = verzoek(
+                &app,
+                "POST",
+                "/spellen",
+                Some(&aart.sessie),
+                Some(serde_json::json!({ "spelers": spelers })),
+            )
+            .await;
+            let SpelAntwoord { spel_id } = json(antwoord).await;
+            spel_ids.push(spel_id);
+        }
+        let verbind = |spel_id: u64, sessie: &str| {
+            let url = format!("ws://{adres}/spellen/{spel_id}/ws?sessie={sessie}");
+            async move { tokio_tungstenite::connect_async(url).await.unwrap().0 }
+        };
+        let mut ws_aart = verbind(spel_ids[0], &aart.sessie).await;
+        let mut ws_bart = verbind(spel_ids[0], &bart.sessie).await;
+        let mut ws_aart_ander = verbind(spel_ids[1], &aart.sessie).await;
+        let mut ws_cart = verbind(spel_ids[1], &cart.sessie).await;
+
+        verzoek(
+            &app,
+            "POST",
+            &format!("/spellen/{}/start", spel_ids[0]),
+            Some(&aart.sessie),
+            None,
+        )
+        .await;
+        for ws in [&mut ws_aart, &mut ws_bart] {
+            assert!(matches!(
+                ontvang(ws).await,
+                MeldingBericht::Melding(PokbotcomMelding::Hand(..))
+            ));
+        }
+        stuur(&mut ws_bart, serde_json::json!({ "soort": "call" })).await;
+        assert!(matches!(
+            ontvang(&mut ws_aart).await,
+            MeldingBericht::Melding(PokbotcomMelding::SpelerActie(_, Actie::Call))
+        ));
+
+        // niets uit het eerste spel komt terecht op de WebSockets van het tweede
+        for ws in [&mut ws_aart_ander, &mut ws_cart] {
+            let stil = std::time::Duration::from_millis(200);
+            assert!(tokio::time::timeout(stil, ws.next()).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn onbekende_spellen_en_spelers() {
+        let app = router(Centrale::new());
+        let aart = registreer(&app, "Aart").await;
+
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            "/spellen",
+            Some(&aart.sessie),
+            Some(serde_json::json!({ "spelers": [aart.speler_id, 7] })),
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::NOT_FOUND);
+
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            "/spellen",
+            Some(&aart.sessie),
+            Some(serde_json::json!({ "spelers": [aart.speler_id, aart.speler_id] })),
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::BAD_REQUEST);
+
+        let antwoord = verzoek(&app, "GET", "/spellen/3", Some(&aart.sessie), None).await;
+        assert_eq!(antwoord.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn ongeldige_bedragen() {
+        let app = router(Centrale::new());
+        let aart = registreer(&app, "Aart").await;
+        let bart = registreer(&app, "Bart").await;
+        let spelers = [aart.speler_id, bart.speler_id];
+
+        for extra in [
+            serde_json::json!({ "niveau": { "small_blind": 0, "big_blind": 0 } }),
+            serde_json::json!({ "niveau": { "small_blind": 50, "big_blind": 20 } }),
+            serde_json::json!({
+                "bet_structuur": {
+                    "soort": "fixed_limit", "kleine_bet": 0, "grote_bet": 40, "max_verhogingen": 4,
+                },
+            }),
+            serde_json::json!({
+                "bet_structuur": {
+                    "soort": "fixed_limit", "kleine_bet": 40, "grote_bet": 20, "max_verhogingen": 4,
+                },
+            }),
+        ] {
+            let mut body = serde_json::json!({ "spelers": spelers });
+            body.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            let antwoord = verzoek(&app, "POST", "/spellen", Some(&aart.sessie), Some(body)).await;
+            assert_eq!(antwoord.status(), StatusCode::BAD_REQUEST, "{extra}");
+        }
+
+        // er bleef geen spel achter
+        let antwoord = verzoek(&app, "GET", "/spellen/0", Some(&aart.sessie), None).await;
+        assert_eq!(antwoord.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn herstarten_met_opslag() {
+        use crate::opslag::sqlite::SqliteOpslag;
+
+        let pad = std::env::temp_dir().join(format!("pokbotcom-server-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&pad);
+        let start = || {
+            router_met_opslag(
+                SqliteOpslag::open(&pad).unwrap(),
+                WebhookInstellingen::default(),
+            )
+            .unwrap()
+        };
+        let toestand_van = |app: Router, sessie: String| async move {
+            let antwoord = verzoek(&app, "GET", "/spellen/0", Some(&sessie), None).await;
+            assert_eq!(antwoord.status(), StatusCode::OK);
+            json::<SpelToestand>(antwoord).await
+        };
+
+        let app = start();
+        let aart = registreer(&app, "Aart").await;
+        let bart = registreer(&app, "Bart").await;
+        let body =
+            serde_json::json!({ "spelers": [aart.speler_id, bart.speler_id], "rng_seed": 0 });
+        verzoek(&app, "POST", "/spellen", Some(&aart.sessie), Some(body)).await;
+        verzoek(&app, "POST", "/spellen/0/start", Some(&aart.sessie), None).await;
+        let voor = toestand_van(app.clone(), aart.sessie.clone()).await;
+        let sessie_van = |id| {
+            if id == aart.speler_id {
+                &aart.sessie
+            } else {
+                &bart.sessie
+            }
+        };
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            "/spellen/0/acties",
+            Some(sessie_van(voor.aan_de_beurt)),
+            Some(serde_json::json!({ "soort": "call" })),
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::NO_CONTENT);
+        let voor = toestand_van(app.clone(), aart.sessie.clone()).await;
+
+        // de sessies blijven geldig en het spel gaat verder waar het was
+        let app = start();
+        let na = toestand_van(app.clone(), aart.sessie.clone()).await;
+        assert_eq!(
+            serde_json::to_value(&na).unwrap(),
+            serde_json::to_value(&voor).unwrap()
+        );
+        let antwoord = verzoek(
+            &app,
+            "POST",
+            "/spellen/0/acties",
+            Some(sessie_van(na.aan_de_beurt)),
+            Some(serde_json::json!({ "soort": "check" })),
+        )
+        .await;
+        assert_eq!(antwoord.status(), StatusCode::NO_CONTENT);
+        assert_eq!(registreer(&app, "Cart").await.speler_id, 2);
+        let voor = toestand_van(app, bart.sessie.clone()).await;
+
+        let app = start();
+        std::fs::remove_file(&pad).unwrap();
+        let na = toestand_van(app, bart.sessie.clone()).await;
+        assert_eq!(
+            serde_json::to_value(&na).unwrap(),
+            serde_json::to_value(&voor).unwrap()
+        );
+    }
+}
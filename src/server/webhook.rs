@@ -0,0 +1,391 @@
+// Webhookmodus: in plaats van zelf acties te sturen, laat een bot zich door de server aanroepen.
+// Telkens hij aan de beurt komt, krijgt zijn callback een `POST` met een `BeurtVerzoek` en
+// antwoordt hij met een actie in hetzelfde formaat als `POST /spellen/{spel_id}/acties`, in een
+// `Envelop` zoals het verzoek zelf.
+//
+// Een mislukte poging (geen verbinding, een foutcode, een onleesbaar antwoord, een onbekende
+// versie of een actie die niet mag) wordt herhaald. Heeft de bot na alle pogingen of bij het
+// verstrijken van de deadline nog niet gehandeld, dan checkt de server voor hem als dat kan en
+// foldt hij anders.
+
+use std::sync::{Mutex, Weak};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use super::{toestand_voor, voer_actie_uit, ServerStaat, SpelToestand};
+use crate::wire::{self, Envelop};
+use crate::{Actie, LegaleActies, SpelId, SpelStatus, SpelerId};
+
+#[derive(Clone, Debug)]
+pub struct WebhookInstellingen {
+    pub pogingen: u32,
+    pub timeout: Duration,  // per poging
+    pub deadline: Duration, // voor alle pogingen samen
+}
+
+impl Default for WebhookInstellingen {
+    fn default() -> Self {
+        WebhookInstellingen {
+            pogingen: 3,
+            timeout: Duration::from_secs(2),
+            deadline: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BeurtVerzoek {
+    pub spel_id: u64,
+    pub speler_id: u64,
+    pub toestand: SpelToestand,
+    pub acties: Vec<String>, // de toegelaten waarden voor `soort`
+    pub legale_acties: LegaleActies,
+}
+
+// Wat de speler mag doen als hij in dit spel nog aan de beurt is.
+fn beurt(staat: &ServerStaat, spel_id: SpelId, speler_id: SpelerId) -> Option<LegaleActies> {
+    let spel = staat
+        .centrale
+        .spellen
+        .iter()
+        .find(|s| s.id.get() == Some(&spel_id))?;
+    if spel.status != SpelStatus::Lopend || spel.spelers[spel.aan_de_beurt] != speler_id {
+        return None;
+    }
+    staat.centrale.legale_acties(spel_id, speler_id).ok()
+}
+
+fn soorten(legaal: &LegaleActies) -> Vec<String> {
+    [
+        (legaal.fold, "fold"),
+        (legaal.check, "check"),
+        (legaal.call, "call"),
+        (legaal.bet, "bet"),
+        (legaal.all_in, "all_in"),
+    ]
+    .into_iter()
+    .filter(|(mag, _)| *mag)
+    .map(|(_, soort)| soort.to_string())
+    .collect()
+}
+
+pub(super) async fn bedien(
+    staat: Weak<Mutex<ServerStaat>>,
+    speler_id: SpelerId,
+    url: reqwest::Url,
+    mut beurten: mpsc::UnboundedReceiver<SpelId>,
+) {
+    let client = reqwest::Client::new();
+
+    while let Some(spel_id) = beurten.recv().await {
+        let Some(staat) = staat.upgrade() else {
+            break;
+        };
+
+        let (verzoek, instellingen) = {
+            let staat = staat.lock().unwrap();
+            // intussen kan de speler al op een andere manier gehandeld hebben
+            let Some(legaal) = beurt(&staat, spel_id, speler_id) else {
+                continue;
+            };
+            let verzoek = BeurtVerzoek {
+                spel_id: spel_id.0,
+                speler_id: speler_id.0,
+                toestand: toestand_voor(&staat, spel_id, speler_id),
+                acties: soorten(&legaal),
+                legale_acties: legaal,
+            };
+            (verzoek, staat.webhook.clone())
+        };
+
+        let vraag = vraag_actie(&client, &url, &verzoek, &instellingen, &staat);
+        if tokio::time::timeout(instellingen.deadline, vraag)
+            .await
+            .unwrap_or(false)
+        {
+            continue;
+        }
+
+        let mut staat = staat.lock().unwrap();
+        if let Some(legaal) = beurt(&staat, spel_id, speler_id) {
+            eprintln!("[SERV] {url} antwoordde niet op tijd voor {speler_id:?}");
+            let actie = if legaal.check {
+                Actie::Check
+            } else {
+                Actie::Fold
+            };
+            let _ = voer_actie_uit(&mut staat, spel_id, speler_id, actie);
+        }
+    }
+}
+
+// Geeft `true` terug zodra de bot een geldige actie gestuurd heeft.
+async fn vraag_actie(
+    client: &reqwest::Client,
+    url: &reqwest::Url,
+    verzoek: &BeurtVerzoek,
+    instellingen: &WebhookInstellingen,
+    staat: &Mutex<ServerStaat>,
+) -> bool {
+    let spel_id = SpelId(verzoek.spel_id);
+    let speler_id = SpelerId(verzoek.speler_id);
+
+    for poging in 0..instellingen.pogingen {
+        if poging > 0 {
+            tokio::time::sleep(Duration::from_millis(100 * u64::from(poging))).await;
+        }
+
+        let antwoord = client
+            .post(url.clone())
+            .timeout(instellingen.timeout)
+            .json(&Envelop::new(verzoek))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        let actie = match antwoord {
+            Ok(antwoord) => match antwoord.text().await {
+                Ok(tekst) => wire::lees_json::<Actie>(&tekst).map_err(|fout| fout.to_string()),
+                Err(fout) => Err(fout.to_string()),
+            },
+            Err(fout) => Err(fout.to_string()),
+        };
+
+        let fout = match actie {
+            Ok(actie) => {
+                let mut staat = staat.lock().unwrap();
+                if beurt(&staat, spel_id, speler_id).is_none() {
+                    return true;
+                }
+                match voer_actie_uit(&mut staat, spel_id, speler_id, actie) {
+                    Ok(()) => return true,
+                    Err(fout) => fout.1,
+                }
+            }
+            Err(fout) => fout,
+        };
+        eprintln!("[SERV] poging {} naar {url} mislukt: {fout}", poging + 1);
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    use super::*;
+    use crate::server::tests::{json, registreer_met, verzoek};
+    use crate::server::{router_met_webhooks, SpelAntwoord};
+    use crate::Centrale;
+
+    // Een bot die de eerste `fouten` beurten met een 500 antwoordt en daarna checkt of callt,
+    // in een envelop met versie `versie` of de huidige.
+    #[derive(Clone, Default)]
+    struct StubBot {
+        oproepen: Arc<Mutex<Vec<BeurtVerzoek>>>,
+        fouten: Arc<AtomicU32>,
+        traag: bool,
+        versie: Option<u32>,
+    }
+
+    async fn stub(State(bot): State<StubBot>, Json(verzoek): Json<BeurtVerzoek>) -> Response {
+        let actie = if verzoek.acties.contains(&"check".to_string()) {
+            Actie::Check
+        } else {
+            Actie::Call
+        };
+        bot.oproepen.lock().unwrap().push(verzoek);
+
+        if bot.traag {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        }
+        if bot
+            .fouten
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+        let mut antwoord = Envelop::new(actie);
+        antwoord.versie = bot.versie.unwrap_or(wire::VERSIE);
+        Json(antwoord).into_response()
+    }
+
+    async fn start_stub(bot: StubBot) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let adres = listener.local_addr().unwrap();
+        let app = Router::new().route("/", post(stub)).with_state(bot);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{adres}/")
+    }
+
+    // Registreert Aart met een callback en Bart zonder, start een spel met de twee en laat
+    // Bart (de dealer, op de small blind) callen, zodat Aart aan de beurt komt. Geeft de sessie van
+    // Bart terug.
+    async fn speel_tot_aart(app: &Router, callback: String) -> String {
+        let aart = registreer_met(
+            app,
+            serde_json::json!({ "naam": "Aart", "callback": callback }),
+        )
+        .await;
+        let bart = registreer_met(app, serde_json::json!({ "naam": "Bart" })).await;
+
+        let antwoord = verzoek(
+            app,
+            "POST",
+            "/spellen",
+            Some(&bart.sessie),
+            Some(serde_json::json!({ "spelers": [bart.speler_id, aart.speler_id], "rng_seed": 0 })),
+        )
+        .await;
+        let SpelAntwoord { spel_id } = json(antwoord).await;
+        assert_eq!(spel_id, 0);
+        for (pad, body) in [
+            ("start", None),
+            ("acties", Some(serde_json::json!({ "soort": "call" }))),
+        ] {
+            let antwoord = verzoek(
+                app,
+                "POST",
+                &format!("/spellen/{spel_id}/{pad}"),
+                Some(&bart.sessie),
+                body,
+            )
+            .await;
+            assert_eq!(antwoord.status(), StatusCode::NO_CONTENT);
+        }
+        bart.sessie
+    }
+
+    async fn wacht_tot(mut voorwaarde: impl FnMut() -> bool) {
+        for _ in 0..500 {
+            if voorwaarde() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("de voorwaarde werd nooit waar");
+    }
+
+    async fn toestand(app: &Router, sessie: &str) -> SpelToestand {
+        json(verzoek(app, "GET", "/spellen/0", Some(sessie), None).await).await
+    }
+
+    #[tokio::test]
+    async fn bot_wordt_aangeroepen_en_opnieuw_geprobeerd() {
+        let bot = StubBot {
+            fouten: Arc::new(AtomicU32::new(1)),
+            ..Default::default()
+        };
+        let app = router_met_webhooks(Centrale::new(), WebhookInstellingen::default());
+        let bart = speel_tot_aart(&app, start_stub(bot.clone()).await).await;
+
+        wacht_tot(|| bot.oproepen.lock().unwrap().len() >= 2).await;
+        {
+            let oproepen = bot.oproepen.lock().unwrap();
+            assert_eq!(oproepen[0].speler_id, 0);
+            assert_eq!(oproepen[0].toestand.aan_de_beurt, 0);
+            assert!(oproepen[0].toestand.hand.is_some());
+            assert_eq!(oproepen[0].acties, ["fold", "check", "bet", "all_in"]);
+            assert_eq!(oproepen[0].legale_acties.max_bet, 980);
+            let json = serde_json::to_value(&oproepen[0]).unwrap();
+            wire::tests::volgens_schema("beurt_verzoek", &json).unwrap();
+        }
+
+        // de check van Aart op de big blind sluit de ronde: de flop ligt op tafel
+        for _ in 0..100 {
+            if toestand(&app, &bart).await.tafel.len() == 3 {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("de actie van de bot werd niet uitgevoerd");
+    }
+
+    #[test]
+    fn beurt_in_het_gevraagde_spel() {
+        let mut staat = ServerStaat::default();
+        let [aart, bart, cart] = ["Aart", "Bart", "Cart"].map(|naam| {
+            staat
+                .centrale
+                .registreer_speler(crate::Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let spel_id = staat.centrale.maak_spel(vec![aart, bart], None).unwrap();
+        let ander_spel = staat.centrale.maak_spel(vec![cart, aart], None).unwrap();
+        staat.centrale.start_spel(spel_id).unwrap();
+
+        // Aart is de dealer en als eerste aan de beurt, maar enkel in het gestarte spel
+        assert!(beurt(&staat, spel_id, aart).is_some());
+        assert!(beurt(&staat, spel_id, bart).is_none());
+        assert!(beurt(&staat, ander_spel, aart).is_none());
+        assert!(beurt(&staat, SpelId(9), aart).is_none());
+    }
+
+    #[tokio::test]
+    async fn trage_bot_checkt_na_de_deadline() {
+        let bot = StubBot {
+            traag: true,
+            ..Default::default()
+        };
+        let instellingen = WebhookInstellingen {
+            pogingen: 2,
+            timeout: Duration::from_millis(50),
+            deadline: Duration::from_millis(300),
+        };
+        let app = router_met_webhooks(Centrale::new(), instellingen);
+        let bart = speel_tot_aart(&app, start_stub(bot.clone()).await).await;
+
+        wacht_tot(|| bot.oproepen.lock().unwrap().len() >= 2).await;
+        for _ in 0..100 {
+            let toestand = toestand(&app, &bart).await;
+            if toestand.tafel.len() == 3 {
+                // preflop werd de bot twee keer aangeroepen, daarna checkte de server
+                let oproepen = bot.oproepen.lock().unwrap();
+                let preflop = oproepen.iter().filter(|o| o.toestand.tafel.is_empty());
+                assert_eq!(preflop.count(), 2);
+                assert_eq!((toestand.pot, toestand.aan_de_beurt), (40, 0));
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("de server handelde niet in naam van de trage bot");
+    }
+
+    #[tokio::test]
+    async fn antwoord_met_onbekende_versie_wordt_geweigerd() {
+        let bot = StubBot {
+            versie: Some(wire::VERSIE + 1),
+            ..Default::default()
+        };
+        let instellingen = WebhookInstellingen {
+            pogingen: 2,
+            timeout: Duration::from_millis(50),
+            deadline: Duration::from_millis(300),
+        };
+        let app = router_met_webhooks(Centrale::new(), instellingen);
+        let bart = speel_tot_aart(&app, start_stub(bot.clone()).await).await;
+
+        // beide antwoorden worden geweigerd, dus checkt de server zelf na de deadline
+        wacht_tot(|| bot.oproepen.lock().unwrap().len() >= 2).await;
+        for _ in 0..100 {
+            if toestand(&app, &bart).await.tafel.len() == 3 {
+                let oproepen = bot.oproepen.lock().unwrap();
+                let preflop = oproepen.iter().filter(|o| o.toestand.tafel.is_empty());
+                assert_eq!(preflop.count(), 2);
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("de server handelde niet in naam van de bot");
+    }
+}
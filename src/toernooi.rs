@@ -0,0 +1,852 @@
+// Een pokertoernooi: iedereen begint met evenveel chips, de blinds gaan omhoog volgens een
+// schema van niveaus, en wie geen chips meer heeft, ligt eruit. Het schema gaat na een aantal
+// handen of na een bepaalde tijd naar het volgende niveau; na het laatste niveau blijven de
+// blinds gelijk. Een nieuw niveau geldt vanaf de volgende hand.
+//
+// Er wordt gespeeld aan gewone tafels van `Centrale`, met hoogstens `max_per_tafel` spelers per
+// tafel. Na elke hand worden de spelers zonder chips uitgeschakeld met
+// `PokbotcomMelding::Uitgeschakeld` en hun plaats; wie in dezelfde hand uitgeschakeld wordt,
+// eindigt hoger naarmate hij aan het begin van die hand meer chips had. Wanneer er nog één speler
+// over is, krijgen alle deelnemers `PokbotcomMelding::ToernooiUitslag`.
+//
+// Met meerdere tafels worden spelers verplaatst tussen twee handen van hun tafel: zodra de
+// overblijvers aan een tafel minder kunnen zitten, wordt een tafel opgeheven, en een tafel met
+// twee spelers meer dan de kleinste geeft er een af. Wie verplaatst wordt, krijgt
+// `PokbotcomMelding::Verplaatst` en schuift aan bij de volgende hand van zijn nieuwe tafel. Een
+// tafel met minder dan twee spelers wacht op een volgende speler; zo eindigt het toernooi aan
+// één finaletafel.
+//
+// De prijzenpot wordt volgens `uitbetaling` over de plaatsen verdeeld. Voor een deal tussen de
+// overblijvers rekent `Centrale::icm_deal` hun stacks om naar de prijzen die nog open liggen.
+
+pub mod uitbetaling;
+
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use itertools::Itertools;
+
+use self::uitbetaling::{icm, Uitbetaling};
+use crate::{
+    BetStructuur, BlindNiveau, Centrale, PokbotcomError, PokbotcomMelding, Spel, SpelId,
+    SpelStatus, SpelerId, VerplichteInzetten, CHIPS_PER_SPELER,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ToernooiId(pub u64);
+
+// Hoe lang een niveau duurt: een aantal handen of een tijd.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NiveauDuur {
+    Handen(u64),
+    Tijd(Duration),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToernooiInstellingen {
+    pub start_chips: u64,
+    pub niveaus: Vec<BlindNiveau>,
+    pub niveau_duur: NiveauDuur,
+    pub bet_structuur: BetStructuur,
+    pub verplichte_inzetten: VerplichteInzetten,
+    pub max_per_tafel: usize,
+    pub prijzenpot: u64,
+    pub uitbetaling: Uitbetaling,
+}
+
+impl Default for ToernooiInstellingen {
+    fn default() -> Self {
+        let niveaus = [
+            (10, 20, 0),
+            (15, 30, 0),
+            (25, 50, 0),
+            (50, 100, 0),
+            (75, 150, 25),
+            (100, 200, 25),
+            (150, 300, 50),
+            (200, 400, 50),
+            (300, 600, 75),
+            (500, 1000, 100),
+        ];
+        ToernooiInstellingen {
+            start_chips: CHIPS_PER_SPELER,
+            niveaus: niveaus
+                .map(|(small, big, ante)| BlindNiveau::new(small, big, ante))
+                .to_vec(),
+            niveau_duur: NiveauDuur::Handen(10),
+            bet_structuur: BetStructuur::NoLimit,
+            verplichte_inzetten: VerplichteInzetten::default(),
+            max_per_tafel: 9,
+            prijzenpot: 0,
+            uitbetaling: Uitbetaling::WinnaarKrijgtAlles,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Toernooi {
+    pub id: ToernooiId,
+    pub instellingen: ToernooiInstellingen,
+    pub spelers: Vec<SpelerId>, // iedereen die meedoet, ook wie al uitgeschakeld is
+    pub spellen: Vec<SpelId>,   // de tafels waaraan gespeeld wordt
+    pub niveau: usize,          // index in `instellingen.niveaus`
+    pub uitgeschakeld: Vec<SpelerId>, // in de volgorde waarin ze eruit gingen
+    pub onderweg: Vec<(SpelerId, SpelId)>, // verplaatst, maar nog niet aan de nieuwe tafel
+    pub status: SpelStatus,
+    pub(crate) handen_in_niveau: u64,
+    pub(crate) niveau_sinds: Option<Instant>,
+}
+
+impl Toernooi {
+    pub fn huidig_niveau(&self) -> BlindNiveau {
+        let niveaus = &self.instellingen.niveaus;
+        niveaus[self.niveau.min(niveaus.len() - 1)]
+    }
+
+    // wie nog niet uitgeschakeld is, in de volgorde van inschrijving
+    pub fn overblijvers(&self) -> Vec<SpelerId> {
+        self.spelers
+            .iter()
+            .filter(|id| !self.uitgeschakeld.contains(id))
+            .copied()
+            .collect()
+    }
+
+    // De plaats van een uitgeschakelde speler, of 1 voor de winnaar.
+    pub fn plaats(&self, speler_id: SpelerId) -> Option<usize> {
+        if let Some(i) = self.uitgeschakeld.iter().position(|id| *id == speler_id) {
+            return Some(self.spelers.len() - i);
+        }
+        let overblijvers = self.overblijvers();
+        (self.status == SpelStatus::Gestopt && overblijvers == [speler_id]).then_some(1)
+    }
+
+    // Iedereen die al een plaats heeft, vanaf de beste.
+    pub fn uitslag(&self) -> Vec<SpelerId> {
+        let mut uitslag = match self.status {
+            SpelStatus::Gestopt => self.overblijvers(),
+            _ => Vec::new(),
+        };
+        uitslag.extend(self.uitgeschakeld.iter().rev());
+        uitslag
+    }
+
+    // Wat elke speler met een plaats wint, vanaf de winnaar. Wie buiten de prijzen valt, staat
+    // er niet bij.
+    pub fn prijzen(&self) -> Vec<(SpelerId, u64)> {
+        let prijzen = self
+            .instellingen
+            .uitbetaling
+            .prijzen(self.instellingen.prijzenpot);
+        let uitslag = self.uitslag();
+        // `uitslag` begint pas bij de winnaar als het toernooi gedaan is
+        let open = self.spelers.len() - uitslag.len();
+        uitslag
+            .into_iter()
+            .zip(1 + open..)
+            .filter_map(|(id, plaats)| Some((id, *prijzen.get(plaats - 1)?)))
+            .collect()
+    }
+
+    pub fn is_finaletafel(&self) -> bool {
+        self.spellen.len() == 1
+    }
+
+    fn niveau_voorbij(&self) -> bool {
+        match self.instellingen.niveau_duur {
+            NiveauDuur::Handen(handen) => self.handen_in_niveau >= handen,
+            NiveauDuur::Tijd(duur) => self
+                .niveau_sinds
+                .is_some_and(|sinds| sinds.elapsed() >= duur),
+        }
+    }
+}
+
+impl Spel {
+    // Haalt spelers weg van de tafel. De button blijft bij de dichtstbijzijnde speler die er
+    // voor de dealer zat, zodat hij na de hand naar de eerstvolgende overblijver gaat.
+    pub(crate) fn verwijder_spelers(&mut self, weg: &[SpelerId]) {
+        let aantal = self.spelers.len();
+        let dealer = (0..aantal)
+            .map(|i| self.spelers[(self.huidige_dealer + aantal - i) % aantal])
+            .find(|id| !weg.contains(id));
+        self.spelers.retain(|id| !weg.contains(id));
+        self.huidige_dealer = dealer
+            .and_then(|dealer| self.spelers.iter().position(|id| *id == dealer))
+            .unwrap_or(0);
+        self.aan_de_beurt = self.aan_de_beurt.min(self.spelers.len().saturating_sub(1));
+    }
+}
+
+impl Centrale {
+    // Een toernooi met de gegeven spelers, zo gelijk mogelijk verdeeld over zo weinig mogelijk
+    // tafels. Aan elke tafel zitten ze in de volgorde van `spelers`.
+    pub fn maak_toernooi(
+        &mut self,
+        spelers: Vec<SpelerId>,
+        instellingen: ToernooiInstellingen,
+        rng_seed: Option<u64>,
+    ) -> Result<ToernooiId> {
+        self.controleer_spelers(&spelers, None)?;
+        if instellingen.niveaus.is_empty() {
+            return Err(PokbotcomError::GeenBlindNiveaus.into());
+        }
+        for niveau in &instellingen.niveaus {
+            niveau.controleer()?;
+        }
+        instellingen.bet_structuur.controleer()?;
+        if instellingen.max_per_tafel < 2 {
+            return Err(PokbotcomError::TeWeinigSpelers.into());
+        }
+        instellingen.uitbetaling.controleer()?;
+
+        let id = ToernooiId(
+            self.volgende_geldige_toernooi_id
+                .fetch_add(1, Ordering::Relaxed),
+        );
+        let tafels = spelers.len().div_ceil(instellingen.max_per_tafel);
+        let mut spellen = Vec::new();
+        for tafel in 0..tafels {
+            let aan_tafel = spelers
+                .iter()
+                .skip(tafel)
+                .step_by(tafels)
+                .copied()
+                .collect();
+            let seed = rng_seed.map(|seed| seed.wrapping_add(tafel as u64));
+            // met twee spelers per tafel kan er een tafel met één speler zijn, die wacht
+            let spel_id = self.nieuw_spel(aan_tafel, seed);
+            let spel = self.get_mut_spel(spel_id);
+            spel.toernooi = Some(id);
+            spel.niveau = instellingen.niveaus[0];
+            spel.bet_structuur = instellingen.bet_structuur;
+            spel.verplichte_inzetten = instellingen.verplichte_inzetten;
+            spellen.push(spel_id);
+        }
+
+        self.toernooien.push(Toernooi {
+            id,
+            instellingen,
+            spelers,
+            spellen,
+            niveau: 0,
+            uitgeschakeld: Vec::new(),
+            onderweg: Vec::new(),
+            status: SpelStatus::Wachtend,
+            handen_in_niveau: 0,
+            niveau_sinds: None,
+        });
+        Ok(id)
+    }
+
+    // Geeft iedereen de startchips en deelt de eerste hand aan elke tafel.
+    pub fn start_toernooi(&mut self, toernooi_id: ToernooiId) -> Result<()> {
+        let toernooi = self
+            .toernooien
+            .iter_mut()
+            .find(|t| t.id == toernooi_id)
+            .ok_or(PokbotcomError::OnbekendToernooi(toernooi_id))?;
+        if toernooi.status != SpelStatus::Wachtend {
+            return Err(PokbotcomError::ToernooiAlBegonnen(toernooi_id).into());
+        }
+        toernooi.status = SpelStatus::Lopend;
+        toernooi.niveau_sinds = Some(Instant::now());
+
+        let (spelers, spellen) = (toernooi.spelers.clone(), toernooi.spellen.clone());
+        let start_chips = toernooi.instellingen.start_chips;
+        for speler_id in spelers {
+            let speler = self.get_mut_speler(speler_id);
+            speler.chips = start_chips;
+            speler.inzet = 0;
+        }
+        for spel_id in spellen {
+            if self.get_spel(spel_id).spelers.len() >= 2 {
+                self.start_spel(spel_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn toernooi(&self, toernooi_id: ToernooiId) -> Option<&Toernooi> {
+        self.toernooien.iter().find(|t| t.id == toernooi_id)
+    }
+
+    // De ICM-equity van elke speler aan een tafel, met zijn chips en wat hij al ingezet heeft,
+    // voor de gegeven prijzen vanaf de winnaar.
+    pub fn icm(&self, spel_id: SpelId, prijzen: &[u64]) -> Result<Vec<(SpelerId, f64)>> {
+        let spel = self
+            .spellen
+            .iter()
+            .find(|s| s.id.get() == Some(&spel_id))
+            .ok_or(PokbotcomError::OnbekendSpel(spel_id))?;
+        self.icm_van(&spel.spelers, prijzen)
+    }
+
+    // Een deal waarbij de overblijvers de prijzen die nog open liggen volgens ICM verdelen. De
+    // bedragen zijn afgerond; wat overschiet, gaat naar de grootste stack.
+    pub fn icm_deal(&self, toernooi_id: ToernooiId) -> Result<Vec<(SpelerId, u64)>> {
+        let toernooi = self
+            .toernooi(toernooi_id)
+            .ok_or(PokbotcomError::OnbekendToernooi(toernooi_id))?;
+        let overblijvers = toernooi.overblijvers();
+        let prijzen = toernooi
+            .instellingen
+            .uitbetaling
+            .prijzen(toernooi.instellingen.prijzenpot)
+            .into_iter()
+            .take(overblijvers.len())
+            .collect_vec();
+
+        let mut deal = self
+            .icm_van(&overblijvers, &prijzen)?
+            .into_iter()
+            .map(|(id, equity)| (id, equity.floor() as u64))
+            .collect_vec();
+        let rest = prijzen
+            .iter()
+            .sum::<u64>()
+            .saturating_sub(deal.iter().map(|(_, prijs)| prijs).sum());
+        if let Some((_, prijs)) = deal.iter_mut().max_by_key(|(id, _)| {
+            let speler = self.get_speler(*id);
+            speler.chips + speler.inzet
+        }) {
+            *prijs += rest;
+        }
+        Ok(deal)
+    }
+
+    fn icm_van(&self, spelers: &[SpelerId], prijzen: &[u64]) -> Result<Vec<(SpelerId, f64)>> {
+        let stacks = spelers
+            .iter()
+            .map(|id| {
+                let speler = self.get_speler(*id);
+                speler.chips + speler.inzet
+            })
+            .collect_vec();
+        Ok(spelers
+            .iter()
+            .copied()
+            .zip(icm(&stacks, prijzen)?)
+            .collect())
+    }
+
+    // Na elke hand aan een tafel van het toernooi: schakelt de spelers zonder chips uit, laat
+    // wie naar deze tafel verplaatst werd aanschuiven, gaat zo nodig naar het volgende niveau,
+    // verdeelt de spelers opnieuw over de tafels en sluit het toernooi af als er een winnaar
+    // is. Geeft terug of er aan deze tafel verder gespeeld wordt.
+    pub(crate) fn na_toernooi_hand(
+        &mut self,
+        toernooi_id: ToernooiId,
+        spel_id: SpelId,
+    ) -> Result<bool> {
+        let t = self
+            .toernooien
+            .iter()
+            .position(|t| t.id == toernooi_id)
+            .ok_or(PokbotcomError::OnbekendToernooi(toernooi_id))?;
+
+        let spel = self.get_spel(spel_id);
+        let start_chips = |id: &SpelerId| {
+            spel.historie
+                .last()
+                .and_then(|hand| hand.stoelen.iter().find(|s| s.speler_id == *id))
+                .map_or(0, |s| s.chips)
+        };
+        let weg = spel
+            .spelers
+            .iter()
+            .filter(|id| self.get_speler(**id).chips == 0)
+            .sorted_by_key(|id| start_chips(id))
+            .copied()
+            .collect_vec();
+
+        for id in &weg {
+            let toernooi = &mut self.toernooien[t];
+            toernooi.uitgeschakeld.push(*id);
+            let plaats = toernooi.spelers.len() - toernooi.uitgeschakeld.len() + 1;
+            self.stuur_naar_alle_spelers(spel_id, PokbotcomMelding::Uitgeschakeld(*id, plaats));
+            self.get_mut_speler(*id).hand = None;
+        }
+        self.get_mut_spel(spel_id).verwijder_spelers(&weg);
+
+        let toernooi = &mut self.toernooien[t];
+        let (aanschuivers, onderweg) = toernooi
+            .onderweg
+            .iter()
+            .partition::<Vec<_>, _>(|(_, naar)| *naar == spel_id);
+        toernooi.onderweg = onderweg;
+        let spel = self.get_mut_spel(spel_id);
+        spel.spelers
+            .extend(aanschuivers.into_iter().map(|(speler_id, _)| speler_id));
+
+        let toernooi = &mut self.toernooien[t];
+        toernooi.handen_in_niveau += 1;
+        if toernooi.niveau_voorbij() {
+            toernooi.niveau += 1;
+            toernooi.handen_in_niveau = 0;
+            toernooi.niveau_sinds = Some(Instant::now());
+        }
+        let niveau = toernooi.huidig_niveau();
+        for spel_id in toernooi.spellen.clone() {
+            self.get_mut_spel(spel_id).niveau = niveau;
+        }
+
+        let toernooi = &mut self.toernooien[t];
+        if toernooi.overblijvers().len() > 1 {
+            self.verdeel_spelers(t, spel_id)?;
+            let spel = self.get_mut_spel(spel_id);
+            if spel.status != SpelStatus::Lopend {
+                return Ok(false);
+            }
+            // met minder dan twee spelers wacht de tafel tot er iemand naartoe verplaatst wordt
+            if spel.spelers.len() < 2 {
+                spel.status = SpelStatus::Wachtend;
+                return Ok(false);
+            }
+            return Ok(true);
+        }
+        toernooi.status = SpelStatus::Gestopt;
+        let (spelers, uitslag) = (toernooi.spelers.clone(), toernooi.uitslag());
+        for spel_id in toernooi.spellen.clone() {
+            self.get_mut_spel(spel_id).status = SpelStatus::Gestopt;
+        }
+        for speler_id in spelers {
+            self.get_speler(speler_id)
+                .stuur_bericht(spel_id, PokbotcomMelding::ToernooiUitslag(uitslag.clone()));
+        }
+        Ok(false)
+    }
+
+    // Het aantal spelers dat aan een tafel zit of er onderweg naartoe is.
+    fn tafelgrootte(&self, t: usize, spel_id: SpelId) -> usize {
+        let onderweg = self.toernooien[t]
+            .onderweg
+            .iter()
+            .filter(|(_, naar)| *naar == spel_id)
+            .count();
+        self.get_spel(spel_id).spelers.len() + onderweg
+    }
+
+    // Verplaatst spelers na een hand aan tafel `spel_id`. Enkel die tafel en tafels die op
+    // spelers wachten zitten tussen twee handen; aan de andere tafels wordt misschien nog
+    // gespeeld, dus daar komen spelers enkel bij.
+    fn verdeel_spelers(&mut self, t: usize, spel_id: SpelId) -> Result<()> {
+        // hef tafels op zolang de overblijvers aan minder tafels passen
+        loop {
+            let toernooi = &self.toernooien[t];
+            let nodig = toernooi
+                .overblijvers()
+                .len()
+                .div_ceil(toernooi.instellingen.max_per_tafel);
+            if toernooi.spellen.len() <= nodig {
+                break;
+            }
+            let Some(weg) = toernooi
+                .spellen
+                .iter()
+                .filter(|id| **id == spel_id || self.get_spel(**id).status == SpelStatus::Wachtend)
+                .min_by_key(|id| self.tafelgrootte(t, **id))
+                .copied()
+            else {
+                break;
+            };
+
+            let toernooi = &mut self.toernooien[t];
+            toernooi.spellen.retain(|id| *id != weg);
+            let (verhuizers, onderweg) = toernooi
+                .onderweg
+                .iter()
+                .partition::<Vec<_>, _>(|(_, naar)| *naar == weg);
+            toernooi.onderweg = onderweg;
+            let spel = self.get_mut_spel(weg);
+            spel.status = SpelStatus::Gestopt;
+            let mut spelers = std::mem::take(&mut spel.spelers);
+            spel.huidige_dealer = 0;
+            spel.aan_de_beurt = 0;
+            spelers.extend(verhuizers.into_iter().map(|(speler_id, _)| speler_id));
+            for speler_id in spelers {
+                let naar = self.kleinste_tafel(t, None);
+                self.verplaats(t, speler_id, weg, naar)?;
+            }
+        }
+
+        // geef telkens de speler die de volgende big blind zou betalen af aan de kleinste tafel
+        if !self.toernooien[t].spellen.contains(&spel_id) {
+            return Ok(());
+        }
+        loop {
+            let naar = self.kleinste_tafel(t, Some(spel_id));
+            if self.tafelgrootte(t, spel_id) < self.tafelgrootte(t, naar) + 2 {
+                return Ok(());
+            }
+            let spel = self.get_mut_spel(spel_id);
+            let big_blind = spel.spelers[(spel.huidige_dealer + 3) % spel.spelers.len()];
+            spel.verwijder_spelers(&[big_blind]);
+            self.verplaats(t, big_blind, spel_id, naar)?;
+        }
+    }
+
+    fn kleinste_tafel(&self, t: usize, behalve: Option<SpelId>) -> SpelId {
+        self.toernooien[t]
+            .spellen
+            .iter()
+            .filter(|id| Some(**id) != behalve)
+            .min_by_key(|id| self.tafelgrootte(t, **id))
+            .copied()
+            .unwrap_or_else(|| behalve.unwrap())
+    }
+
+    // Een wachtende tafel krijgt de speler meteen en begint opnieuw te delen zodra er twee
+    // zitten; anders schuift hij aan bij de volgende hand. De melding komt nog van zijn oude
+    // tafel.
+    fn verplaats(
+        &mut self,
+        t: usize,
+        speler_id: SpelerId,
+        van: SpelId,
+        naar: SpelId,
+    ) -> Result<()> {
+        self.get_speler(speler_id)
+            .stuur_bericht(van, PokbotcomMelding::Verplaatst(naar));
+        let spel = self.get_mut_spel(naar);
+        if spel.status != SpelStatus::Wachtend {
+            self.toernooien[t].onderweg.push((speler_id, naar));
+            return Ok(());
+        }
+        spel.spelers.push(speler_id);
+        if spel.spelers.len() < 2 {
+            return Ok(());
+        }
+        spel.status = SpelStatus::Lopend;
+        spel.huidige_dealer = (spel.huidige_dealer + 1) % spel.spelers.len();
+        self.deel_hand(naar, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kanaal::Wachtrij;
+    use crate::{Actie, Speler};
+
+    fn inschrijven(centrale: &mut Centrale, namen: &[&str]) -> Vec<SpelerId> {
+        namen
+            .iter()
+            .map(|naam| {
+                centrale
+                    .registreer_speler(Speler::new_zonder_id(naam.to_string()))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn blinds_gaan_omhoog() {
+        let mut centrale = Centrale::new();
+        let ids = inschrijven(&mut centrale, &["Aart", "Bart", "Cart"]);
+        let instellingen = ToernooiInstellingen {
+            start_chips: 500,
+            niveaus: vec![BlindNiveau::new(10, 20, 0), BlindNiveau::new(25, 50, 5)],
+            niveau_duur: NiveauDuur::Handen(1),
+            ..Default::default()
+        };
+        let toernooi_id = centrale
+            .maak_toernooi(ids.clone(), instellingen, Some(1))
+            .unwrap();
+        assert!(centrale
+            .maak_toernooi(vec![ids[0]], Default::default(), None)
+            .is_err());
+        centrale.start_toernooi(toernooi_id).unwrap();
+        assert!(centrale.start_toernooi(toernooi_id).is_err());
+        let spel_id = centrale.toernooi(toernooi_id).unwrap().spellen[0];
+        assert!(ids.iter().all(|id| {
+            let speler = centrale.get_speler(*id);
+            speler.chips + speler.inzet == 500
+        }));
+
+        // iedereen callt en checkt tot het einde: de volgende hand is op het tweede niveau
+        while centrale.hand_historieen(spel_id).len() < 2 {
+            let spel = centrale.get_spel(spel_id);
+            let id = spel.spelers[spel.aan_de_beurt];
+            let actie = match centrale.legale_acties(spel_id, id).unwrap() {
+                legaal if legaal.check => Actie::Check,
+                _ => Actie::Call,
+            };
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+        }
+
+        let hand = centrale.hand_historie(spel_id, 2).unwrap();
+        assert_eq!(hand.niveau, BlindNiveau::new(25, 50, 5));
+        assert_eq!(hand.antes.len(), 3);
+        assert_eq!(centrale.get_spel(spel_id).pot(), 15);
+        assert_eq!(centrale.toernooi(toernooi_id).unwrap().niveau, 1);
+        assert_eq!(centrale.get_spel(spel_id).huidige_inzet, 50);
+    }
+
+    #[test]
+    fn uitschakelen_tot_er_een_winnaar_is() {
+        let mut centrale = Centrale::new();
+        let ids = inschrijven(&mut centrale, &["Aart", "Bart", "Cart", "Dart"]);
+        let instellingen = ToernooiInstellingen {
+            niveau_duur: NiveauDuur::Tijd(Duration::ZERO),
+            prijzenpot: 1000,
+            uitbetaling: Uitbetaling::Top3,
+            ..Default::default()
+        };
+        let toernooi_id = centrale
+            .maak_toernooi(ids.clone(), instellingen, Some(4))
+            .unwrap();
+        centrale.start_toernooi(toernooi_id).unwrap();
+        let spel_id = centrale.toernooi(toernooi_id).unwrap().spellen[0];
+
+        // iedereen gaat all-in tot er nog één speler over is
+        let mut handen = 0;
+        while centrale.get_spel(spel_id).status == SpelStatus::Lopend {
+            let spel = centrale.get_spel(spel_id);
+            let id = spel.spelers[spel.aan_de_beurt];
+            let actie = match centrale.legale_acties(spel_id, id).unwrap() {
+                legaal if legaal.all_in => Actie::AllIn,
+                _ => Actie::Call,
+            };
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+            handen = centrale.hand_historieen(spel_id).len();
+            assert!(handen < 100, "het toernooi eindigt niet");
+        }
+
+        let toernooi = centrale.toernooi(toernooi_id).unwrap();
+        assert_eq!(toernooi.status, SpelStatus::Gestopt);
+        assert_eq!(toernooi.uitgeschakeld.len(), 3);
+        let uitslag = toernooi.uitslag();
+        assert_eq!(
+            uitslag.iter().sorted().collect_vec(),
+            ids.iter().collect_vec()
+        );
+        for (i, id) in uitslag.iter().enumerate() {
+            assert_eq!(toernooi.plaats(*id), Some(i + 1));
+        }
+        let winnaar = centrale.get_speler(uitslag[0]);
+        assert_eq!(winnaar.chips, 4 * CHIPS_PER_SPELER);
+        assert_eq!(
+            toernooi.prijzen(),
+            [(uitslag[0], 500), (uitslag[1], 300), (uitslag[2], 200)]
+        );
+
+        // met een niveau van nul seconden gaan de blinds na elke hand omhoog
+        assert_eq!(toernooi.niveau, handen);
+        assert_eq!(centrale.get_spel(spel_id).spelers, [uitslag[0]]);
+    }
+
+    #[test]
+    fn meerdere_tafels() {
+        let mut centrale = Centrale::new();
+        let wachtrijen = (0..10).map(|_| Wachtrij::new()).collect_vec();
+        let ids = wachtrijen
+            .iter()
+            .enumerate()
+            .map(|(i, wachtrij)| {
+                let speler = Speler::new_zonder_id(format!("Speler {i}"));
+                centrale
+                    .registreer_speler(speler.met_kanaal(wachtrij.clone()))
+                    .unwrap()
+            })
+            .collect_vec();
+        let instellingen = ToernooiInstellingen {
+            max_per_tafel: 4,
+            ..Default::default()
+        };
+        let toernooi_id = centrale
+            .maak_toernooi(ids.clone(), instellingen, Some(10))
+            .unwrap();
+        let spellen = centrale.toernooi(toernooi_id).unwrap().spellen.clone();
+        let groottes = |centrale: &Centrale, spellen: &[SpelId]| {
+            spellen
+                .iter()
+                .map(|id| centrale.get_spel(*id).spelers.len())
+                .collect_vec()
+        };
+        assert_eq!(groottes(&centrale, &spellen), [4, 3, 3]);
+        centrale.start_toernooi(toernooi_id).unwrap();
+
+        // iedereen gaat all-in, telkens aan de eerste tafel waar gespeeld wordt
+        let mut acties = 0;
+        while centrale.toernooi(toernooi_id).unwrap().status == SpelStatus::Lopend {
+            let toernooi = centrale.toernooi(toernooi_id).unwrap();
+            let spel_id = *toernooi
+                .spellen
+                .iter()
+                .find(|id| centrale.get_spel(**id).status == SpelStatus::Lopend)
+                .expect("geen enkele tafel speelt nog");
+
+            // elke overblijver zit aan precies één tafel of is er onderweg naartoe
+            let mut geplaatst = toernooi
+                .spellen
+                .iter()
+                .flat_map(|id| centrale.get_spel(*id).spelers.clone())
+                .chain(toernooi.onderweg.iter().map(|(id, _)| *id))
+                .collect_vec();
+            geplaatst.sort();
+            assert_eq!(geplaatst, toernooi.overblijvers());
+            assert!(groottes(&centrale, &toernooi.spellen)
+                .iter()
+                .all(|grootte| *grootte <= 4));
+
+            let spel = centrale.get_spel(spel_id);
+            let id = spel.spelers[spel.aan_de_beurt];
+            let actie = match centrale.legale_acties(spel_id, id).unwrap() {
+                legaal if legaal.all_in => Actie::AllIn,
+                _ => Actie::Call,
+            };
+            centrale.ontvang_actie(spel_id, id, actie).unwrap();
+            acties += 1;
+            assert!(acties < 10_000, "het toernooi eindigt niet");
+        }
+
+        let toernooi = centrale.toernooi(toernooi_id).unwrap();
+        assert!(toernooi.is_finaletafel());
+        assert_eq!(toernooi.uitslag().len(), 10);
+        let winnaar = toernooi.uitslag()[0];
+        assert_eq!(centrale.get_speler(winnaar).chips, 10 * CHIPS_PER_SPELER);
+        let finaletafel = toernooi.spellen[0];
+        assert_eq!(centrale.get_spel(finaletafel).spelers, [winnaar]);
+        assert!(spellen
+            .iter()
+            .all(|id| centrale.get_spel(*id).status == SpelStatus::Gestopt));
+
+        // wie van tafel veranderde, kreeg te horen waarheen
+        let verplaatst = wachtrijen
+            .iter()
+            .flat_map(|wachtrij| wachtrij.meldingen())
+            .filter_map(|melding| match melding {
+                PokbotcomMelding::Verplaatst(naar) => Some(naar),
+                _ => None,
+            })
+            .collect_vec();
+        assert!(!verplaatst.is_empty());
+        assert!(verplaatst.iter().all(|naar| spellen.contains(naar)));
+        let winnaar_index = ids.iter().position(|id| *id == winnaar).unwrap();
+        let aan_finaletafel = wachtrijen[winnaar_index]
+            .meldingen()
+            .iter()
+            .rev()
+            .find_map(|melding| match melding {
+                PokbotcomMelding::Verplaatst(naar) => Some(*naar),
+                _ => None,
+            })
+            .unwrap_or(spellen[winnaar_index % 3]);
+        assert_eq!(aan_finaletafel, finaletafel);
+    }
+
+    #[test]
+    fn tafel_zonder_toernooi() {
+        let mut centrale = Centrale::new();
+        let ids = inschrijven(&mut centrale, &["Aart", "Bart"]);
+        let toernooi_id = centrale
+            .maak_toernooi(ids, Default::default(), Some(1))
+            .unwrap();
+        centrale.start_toernooi(toernooi_id).unwrap();
+        let spel_id = centrale.toernooi(toernooi_id).unwrap().spellen[0];
+
+        // een tafel waarvan het toernooi zoek is, speelt niet stilletjes verder of stopt
+        centrale.toernooien.clear();
+        let spel = centrale.get_spel(spel_id);
+        let eerste = spel.spelers[spel.aan_de_beurt];
+        let fout = centrale
+            .ontvang_actie(spel_id, eerste, Actie::Fold)
+            .unwrap_err();
+        assert_eq!(
+            fout.downcast::<PokbotcomError>().unwrap(),
+            PokbotcomError::OnbekendToernooi(toernooi_id)
+        );
+    }
+
+    #[test]
+    fn deal_volgens_icm() {
+        let mut centrale = Centrale::new();
+        let ids = inschrijven(&mut centrale, &["Aart", "Bart", "Cart", "Dart"]);
+        let instellingen = ToernooiInstellingen {
+            prijzenpot: 1000,
+            uitbetaling: Uitbetaling::Percentages {
+                percentages: vec![50, 30, 15, 5],
+            },
+            ..Default::default()
+        };
+        let fout = ToernooiInstellingen {
+            uitbetaling: Uitbetaling::Percentages {
+                percentages: vec![50, 30],
+            },
+            ..Default::default()
+        };
+        assert!(centrale.maak_toernooi(ids.clone(), fout, None).is_err());
+        let fout = ToernooiInstellingen {
+            niveaus: vec![BlindNiveau::new(10, 20, 0), BlindNiveau::new(50, 0, 0)],
+            ..Default::default()
+        };
+        assert_eq!(
+            centrale
+                .maak_toernooi(ids.clone(), fout, None)
+                .unwrap_err()
+                .downcast::<PokbotcomError>()
+                .unwrap(),
+            PokbotcomError::GeenBigBlind
+        );
+        let fout = ToernooiInstellingen {
+            bet_structuur: BetStructuur::FixedLimit {
+                kleine_bet: 0,
+                grote_bet: 0,
+                max_verhogingen: 4,
+            },
+            ..Default::default()
+        };
+        assert!(centrale.maak_toernooi(ids.clone(), fout, None).is_err());
+        let toernooi_id = centrale
+            .maak_toernooi(ids.clone(), instellingen, None)
+            .unwrap();
+        assert!(centrale.toernooi(toernooi_id).unwrap().prijzen().is_empty());
+
+        // Dart ligt eruit en heeft de vierde prijs; de anderen verdelen de rest
+        let t = centrale
+            .toernooien
+            .iter()
+            .position(|t| t.id == toernooi_id)
+            .unwrap();
+        centrale.toernooien[t].uitgeschakeld.push(ids[3]);
+        for (id, chips) in ids.iter().zip([2000, 1000, 1000, 0]) {
+            centrale.get_mut_speler(*id).chips = chips;
+        }
+        assert_eq!(
+            centrale.toernooi(toernooi_id).unwrap().prijzen(),
+            [(ids[3], 50)]
+        );
+
+        let spel_id = centrale.toernooi(toernooi_id).unwrap().spellen[0];
+        let equity = centrale.icm(spel_id, &[500, 300, 150]).unwrap();
+        assert_eq!(equity[3], (ids[3], 0.0));
+        assert!(equity[0].1 > equity[1].1);
+        assert_eq!(equity[1].1, equity[2].1);
+
+        let deal = centrale.icm_deal(toernooi_id).unwrap();
+        assert_eq!(deal.iter().map(|(id, _)| *id).collect_vec(), &ids[..3]);
+        assert_eq!(deal.iter().map(|(_, prijs)| prijs).sum::<u64>(), 950);
+        assert!(deal
+            .iter()
+            .zip(&equity)
+            .all(|((_, prijs), (_, equity))| { (*prijs as f64 - equity).abs() <= 1.0 }));
+    }
+
+    #[test]
+    fn button_na_uitschakeling() {
+        let mut spel = Spel::new(SpelId(0), Some(0));
+        spel.spelers = (0..5).map(SpelerId).collect();
+        spel.huidige_dealer = 2;
+
+        // de dealer zelf ligt eruit: de button blijft bij de speler ervoor
+        spel.verwijder_spelers(&[SpelerId(2), SpelerId(3)]);
+        assert_eq!(spel.spelers, [SpelerId(0), SpelerId(1), SpelerId(4)]);
+        assert_eq!(spel.spelers[spel.huidige_dealer], SpelerId(1));
+
+        spel.verwijder_spelers(&[SpelerId(0)]);
+        assert_eq!(spel.spelers[spel.huidige_dealer], SpelerId(1));
+    }
+}
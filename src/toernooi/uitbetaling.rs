@@ -0,0 +1,170 @@
+// Hoe de prijzenpot van een toernooi over de plaatsen verdeeld wordt, en het Independent Chip
+// Model (ICM) dat stacks omrekent naar hun verwachte prijs.
+//
+// ICM gaat ervan uit dat een speler het toernooi wint met een kans evenredig met zijn chips, en
+// dat van de anderen daarna op dezelfde manier de volgende plaats bepaald wordt. We rekenen dat
+// exact uit over alle mogelijke volgordes van de betaalde plaatsen. Dat groeit exponentieel met
+// het aantal spelers en betaalde plaatsen, dus boven `MAX_ICM_TOESTANDEN` weigeren we.
+
+use std::collections::HashMap;
+
+use crate::PokbotcomError;
+
+// Hoeveel groepjes spelers op de betaalde plaatsen `icm` hoogstens mag bijhouden. Met tien spelers
+// en tien betaalde plaatsen zijn dat er 1023, met zestien spelers en plaatsen 65535.
+pub const MAX_ICM_TOESTANDEN: u64 = 1 << 16;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "soort", rename_all = "snake_case")
+)]
+pub enum Uitbetaling {
+    #[default]
+    WinnaarKrijgtAlles,
+    Top3, // 50%, 30% en 20%
+    Percentages {
+        percentages: Vec<u32>,
+    }, // per plaats, vanaf de winnaar
+}
+
+impl Uitbetaling {
+    pub fn percentages(&self) -> Vec<u32> {
+        match self {
+            Uitbetaling::WinnaarKrijgtAlles => vec![100],
+            Uitbetaling::Top3 => vec![50, 30, 20],
+            Uitbetaling::Percentages { percentages } => percentages.clone(),
+        }
+    }
+
+    pub fn controleer(&self) -> Result<(), PokbotcomError> {
+        let som = self
+            .percentages()
+            .iter()
+            .try_fold(0u32, |som, percentage| som.checked_add(*percentage));
+        match som {
+            Some(100) => Ok(()),
+            _ => Err(PokbotcomError::OngeldigeUitbetaling),
+        }
+    }
+
+    // De prijs van elke betaalde plaats. Wat bij het afronden overschiet, gaat naar de winnaar.
+    pub fn prijzen(&self, prijzenpot: u64) -> Vec<u64> {
+        let mut prijzen = self
+            .percentages()
+            .iter()
+            .map(|percentage| prijzenpot * *percentage as u64 / 100)
+            .collect::<Vec<_>>();
+        let rest = prijzenpot.saturating_sub(prijzen.iter().sum());
+        if let Some(eerste) = prijzen.first_mut() {
+            *eerste += rest;
+        }
+        prijzen
+    }
+}
+
+// De verwachte prijs van elke stack, met `prijzen` de prijs per plaats vanaf de winnaar. Een
+// stack zonder chips krijgt niets; prijzen voor meer plaatsen dan er stacks met chips zijn,
+// worden niet meer uitgedeeld. Geeft een fout als de berekening te groot zou worden.
+pub fn icm(stacks: &[u64], prijzen: &[u64]) -> Result<Vec<f64>, PokbotcomError> {
+    let mut equity = vec![0.0; stacks.len()];
+    let totaal = stacks.iter().sum::<u64>();
+    let met_chips = stacks.iter().filter(|chips| **chips > 0).count();
+    if aantal_toestanden(met_chips, prijzen.len().min(met_chips)) > MAX_ICM_TOESTANDEN {
+        return Err(PokbotcomError::IcmTeGroot);
+    }
+
+    // per niveau: de kans dat precies deze spelers de plaatsen daarboven bezetten
+    let mut kansen = HashMap::from([(Vec::<usize>::new(), 1.0)]);
+    for prijs in prijzen.iter().take(met_chips) {
+        let mut volgende = HashMap::<Vec<usize>, f64>::new();
+        for (geplaatst, kans) in &kansen {
+            let over = totaal - geplaatst.iter().map(|i| stacks[*i]).sum::<u64>();
+            for (i, chips) in stacks.iter().enumerate() {
+                if *chips == 0 || geplaatst.contains(&i) {
+                    continue;
+                }
+                let kans = kans * *chips as f64 / over as f64;
+                equity[i] += kans * *prijs as f64;
+                let mut sleutel = geplaatst.clone();
+                let plek = sleutel.partition_point(|j| *j < i);
+                sleutel.insert(plek, i);
+                *volgende.entry(sleutel).or_default() += kans;
+            }
+        }
+        kansen = volgende;
+    }
+    Ok(equity)
+}
+
+// Hoeveel groepjes van minder dan `plaatsen` spelers uit `spelers` er zijn: de toestanden die
+// `icm` overloopt. Houdt op met tellen zodra het meer dan `MAX_ICM_TOESTANDEN` zijn.
+fn aantal_toestanden(spelers: usize, plaatsen: usize) -> u64 {
+    let mut totaal = 0u64;
+    let mut groepjes = 1u64; // spelers kies k
+    for k in 0..plaatsen as u64 {
+        totaal += groepjes;
+        if totaal > MAX_ICM_TOESTANDEN {
+            break;
+        }
+        groepjes = groepjes * (spelers as u64 - k) / (k + 1);
+    }
+    totaal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prijzen() {
+        assert_eq!(Uitbetaling::WinnaarKrijgtAlles.prijzen(1000), [1000]);
+        assert_eq!(Uitbetaling::Top3.prijzen(1001), [501, 300, 200]);
+        let schema = Uitbetaling::Percentages {
+            percentages: vec![40, 25, 15, 10, 10],
+        };
+        assert!(schema.controleer().is_ok());
+        assert_eq!(schema.prijzen(200), [80, 50, 30, 20, 20]);
+        let fout = Uitbetaling::Percentages {
+            percentages: vec![60, 30],
+        };
+        assert!(fout.controleer().is_err());
+        let overloop = Uitbetaling::Percentages {
+            percentages: vec![u32::MAX, 101],
+        };
+        assert!(overloop.controleer().is_err());
+    }
+
+    #[test]
+    fn icm_berekenen() {
+        let gelijk = |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9);
+
+        // winnaar krijgt alles: de equity is evenredig met de chips
+        assert!(gelijk(&icm(&[3000, 1000], &[100]).unwrap(), &[75.0, 25.0]));
+        // gelijke stacks delen alles gelijk
+        assert!(gelijk(
+            &icm(&[500; 3], &[50, 30, 20]).unwrap(),
+            &[100.0 / 3.0; 3]
+        ));
+
+        // de grootste stack is minder waard dan zijn aandeel in de chips, de kleinste meer
+        let equity = icm(&[5000, 3000, 2000], &[500, 300, 200]).unwrap();
+        assert!(
+            gelijk(&equity, &[5375.0 / 14.0, 327.5, 2020.0 / 7.0]),
+            "{equity:?}"
+        );
+        assert!((equity.iter().sum::<f64>() - 1000.0).abs() < 1e-9);
+
+        // wie geen chips heeft, krijgt niets, en de prijs voor de derde plaats blijft liggen
+        let equity = icm(&[1000, 0, 1000], &[70, 30, 10]).unwrap();
+        assert!(gelijk(&equity, &[50.0, 0.0, 50.0]), "{equity:?}");
+
+        // zestien spelers en plaatsen gaan nog, zeventien niet meer; met weinig betaalde
+        // plaatsen mogen er wel veel spelers zijn
+        assert_eq!(aantal_toestanden(16, 16), 65535);
+        assert!(icm(&[1000; 17], &[1; 17]).is_err());
+        let equity = icm(&[1000; 100], &[50, 30, 20]).unwrap();
+        assert!(gelijk(&equity, &[1.0; 100]), "{equity:?}");
+    }
+}
@@ -0,0 +1,923 @@
+// Het JSON-formaat van alles wat de engine verstuurt en ontvangt, achter de feature `serde`.
+// Het contract staat in `schema/pokbotcom-v1.schema.json`; een wijziging waarmee bestaande
+// clients niet meer overweg kunnen, verhoogt `VERSIE` en krijgt een nieuw schema.
+//
+// Kaarten worden geschreven in ASCII-notatie (`"As"`, `"Td"`), een hand als lijst van vijf
+// kaarten en acties en meldingen als object met een veld `soort`, bv.
+// `{"soort": "bet", "chips": 50}` of `{"soort": "turn", "kaart": "7h"}`.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::herhaling::GelogdeActie;
+use crate::historie::HandHistorie;
+use crate::kaart::{Hand, Kaart, Kleur, Waarde};
+use crate::toernooi::uitbetaling::Uitbetaling;
+use crate::toernooi::{NiveauDuur, Toernooi, ToernooiId, ToernooiInstellingen};
+use crate::{
+    Actie, BetStructuur, BlindNiveau, LegaleActies, PokbotcomError, PokbotcomMelding, Pot, Spel,
+    SpelId, SpelStatus, SpelerId, VerplichteInzetten, BIG_BLIND,
+};
+
+pub const VERSIE: u32 = 1;
+
+fn huidige_versie() -> u32 {
+    VERSIE
+}
+
+fn big_blind() -> u64 {
+    BIG_BLIND
+}
+
+// Een bericht samen met de versie van het formaat, bv. `{"versie": 1, "soort": "ronde_over"}`.
+// Een bericht zonder versie wordt gelezen als een van de huidige versie.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Envelop<T> {
+    #[serde(default = "huidige_versie")]
+    pub versie: u32,
+    #[serde(flatten)]
+    pub bericht: T,
+}
+
+impl<T> Envelop<T> {
+    pub fn new(bericht: T) -> Self {
+        Envelop {
+            versie: VERSIE,
+            bericht,
+        }
+    }
+}
+
+pub fn naar_json<T: Serialize>(bericht: T) -> String {
+    serde_json::to_string(&Envelop::new(bericht)).unwrap()
+}
+
+pub fn lees_json<T: DeserializeOwned>(json: &str) -> Result<T, PokbotcomError> {
+    let envelop: Envelop<T> = serde_json::from_str(json)
+        .map_err(|fout| PokbotcomError::OngeldigBericht(fout.to_string()))?;
+    if envelop.versie != VERSIE {
+        return Err(PokbotcomError::OnbekendeVersie(envelop.versie));
+    }
+    Ok(envelop.bericht)
+}
+
+fn als_tekst<S: Serializer>(waarde: impl Display, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{waarde:#}"))
+}
+
+fn uit_tekst<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = PokbotcomError>,
+{
+    let tekst = String::deserialize(deserializer)?;
+    tekst.parse().map_err(de::Error::custom)
+}
+
+macro_rules! als_tekst {
+    ($($type:ty),*) => {$(
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                als_tekst(self, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                uit_tekst(deserializer)
+            }
+        }
+    )*};
+}
+
+als_tekst!(Kleur, Waarde, Kaart);
+
+impl Serialize for Hand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.kaarten().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let kaarten = <[Kaart; 5]>::deserialize(deserializer)?;
+        if let Some(dubbel) = kaarten.iter().duplicates().next() {
+            return Err(de::Error::custom(PokbotcomError::DubbeleKaart(*dubbel)));
+        }
+        Ok(Hand::new(kaarten))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "soort", rename_all = "snake_case")]
+enum ActieWire {
+    Fold,
+    Check,
+    Call,
+    Bet { chips: u64 },
+    AllIn,
+}
+
+impl Serialize for Actie {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Actie::Fold => ActieWire::Fold,
+            Actie::Check => ActieWire::Check,
+            Actie::Call => ActieWire::Call,
+            Actie::Bet(chips) => ActieWire::Bet { chips },
+            Actie::AllIn => ActieWire::AllIn,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Actie {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match ActieWire::deserialize(deserializer)? {
+            ActieWire::Fold => Actie::Fold,
+            ActieWire::Check => Actie::Check,
+            ActieWire::Call => Actie::Call,
+            ActieWire::Bet { chips } => Actie::Bet(chips),
+            ActieWire::AllIn => Actie::AllIn,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Winnaar {
+    speler_id: SpelerId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hand: Option<Hand>, // niet getoond als er geen showdown was
+    chips: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "soort", rename_all = "snake_case")]
+enum MeldingWire {
+    Hand {
+        kaarten: [Kaart; 2],
+    },
+    Flop {
+        kaarten: [Kaart; 3],
+    },
+    Turn {
+        kaart: Kaart,
+    },
+    River {
+        kaart: Kaart,
+    },
+    SpelerActie {
+        speler_id: SpelerId,
+        actie: Actie,
+    },
+    AanDeBeurt {
+        #[serde(default)]
+        legale_acties: LegaleActies,
+    },
+    RondeOver,
+    Gewonnen {
+        winnaars: Vec<Winnaar>,
+    },
+    Uitgeschakeld {
+        speler_id: SpelerId,
+        plaats: usize,
+    },
+    ToernooiUitslag {
+        uitslag: Vec<SpelerId>,
+    },
+    Verplaatst {
+        spel_id: SpelId,
+    },
+}
+
+impl Serialize for PokbotcomMelding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.clone() {
+            PokbotcomMelding::Hand(a, b) => MeldingWire::Hand { kaarten: [a, b] },
+            PokbotcomMelding::Flop(a, b, c) => MeldingWire::Flop { kaarten: [a, b, c] },
+            PokbotcomMelding::Turn(kaart) => MeldingWire::Turn { kaart },
+            PokbotcomMelding::River(kaart) => MeldingWire::River { kaart },
+            PokbotcomMelding::SpelerActie(speler_id, actie) => {
+                MeldingWire::SpelerActie { speler_id, actie }
+            }
+            PokbotcomMelding::AanDeBeurt(legale_acties) => {
+                MeldingWire::AanDeBeurt { legale_acties }
+            }
+            PokbotcomMelding::RondeOver => MeldingWire::RondeOver,
+            PokbotcomMelding::Gewonnen(winnaars) => MeldingWire::Gewonnen {
+                winnaars: winnaars
+                    .into_iter()
+                    .map(|(speler_id, hand, chips)| Winnaar {
+                        speler_id,
+                        hand,
+                        chips,
+                    })
+                    .collect(),
+            },
+            PokbotcomMelding::Uitgeschakeld(speler_id, plaats) => {
+                MeldingWire::Uitgeschakeld { speler_id, plaats }
+            }
+            PokbotcomMelding::ToernooiUitslag(uitslag) => MeldingWire::ToernooiUitslag { uitslag },
+            PokbotcomMelding::Verplaatst(spel_id) => MeldingWire::Verplaatst { spel_id },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PokbotcomMelding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match MeldingWire::deserialize(deserializer)? {
+            MeldingWire::Hand { kaarten: [a, b] } => PokbotcomMelding::Hand(a, b),
+            MeldingWire::Flop { kaarten: [a, b, c] } => PokbotcomMelding::Flop(a, b, c),
+            MeldingWire::Turn { kaart } => PokbotcomMelding::Turn(kaart),
+            MeldingWire::River { kaart } => PokbotcomMelding::River(kaart),
+            MeldingWire::SpelerActie { speler_id, actie } => {
+                PokbotcomMelding::SpelerActie(speler_id, actie)
+            }
+            MeldingWire::AanDeBeurt { legale_acties } => {
+                PokbotcomMelding::AanDeBeurt(legale_acties)
+            }
+            MeldingWire::RondeOver => PokbotcomMelding::RondeOver,
+            MeldingWire::Gewonnen { winnaars } => PokbotcomMelding::Gewonnen(
+                winnaars
+                    .into_iter()
+                    .map(|w| (w.speler_id, w.hand, w.chips))
+                    .collect(),
+            ),
+            MeldingWire::Uitgeschakeld { speler_id, plaats } => {
+                PokbotcomMelding::Uitgeschakeld(speler_id, plaats)
+            }
+            MeldingWire::ToernooiUitslag { uitslag } => PokbotcomMelding::ToernooiUitslag(uitslag),
+            MeldingWire::Verplaatst { spel_id } => PokbotcomMelding::Verplaatst(spel_id),
+        })
+    }
+}
+
+// Een spel met alles erop en eraan, ook het deck en de toestand van de random generator: dit
+// is bedoeld om een spel te bewaren en niet om naar spelers te sturen. Een spel zonder `rng`
+// krijgt bij het inlezen een nieuwe.
+#[derive(Serialize, Deserialize)]
+struct SpelWire {
+    id: SpelId,
+    spelers: Vec<SpelerId>,
+    potten: Vec<Pot>,
+    tafel: Vec<Kaart>, // de open kaarten: geen, de flop, tot en met de turn of tot en met de river
+    huidige_dealer: usize,
+    aan_de_beurt: usize,
+    deck: Vec<Kaart>,
+    huidige_inzet: u64,
+    laatste_actionabele_speler: Option<SpelerId>,
+    #[serde(default = "big_blind")]
+    laatste_verhoging: u64,
+    #[serde(default)]
+    gehandeld: Vec<SpelerId>,
+    #[serde(default)]
+    verhogingen: u32,
+    #[serde(default)]
+    bet_structuur: BetStructuur,
+    #[serde(default)]
+    niveau: BlindNiveau,
+    #[serde(default)]
+    verplichte_inzetten: VerplichteInzetten,
+    #[serde(default)]
+    antes: Vec<(SpelerId, u64)>,
+    #[serde(default)]
+    toernooi: Option<ToernooiId>,
+    status: SpelStatus,
+    #[serde(default)]
+    historie: Vec<HandHistorie>,
+    #[serde(default)]
+    log: Vec<GelogdeActie>,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    rng: Option<RngWire>,
+}
+
+// De positie in de stroom past niet in een JSON-getal en wordt dus als tekst geschreven.
+#[derive(Serialize, Deserialize)]
+struct RngWire {
+    seed: String, // hexadecimaal
+    stroom: u64,
+    positie: String,
+}
+
+impl From<&ChaCha12Rng> for RngWire {
+    fn from(rng: &ChaCha12Rng) -> Self {
+        RngWire {
+            seed: rng.get_seed().iter().map(|b| format!("{b:02x}")).collect(),
+            stroom: rng.get_stream(),
+            positie: rng.get_word_pos().to_string(),
+        }
+    }
+}
+
+impl TryFrom<RngWire> for ChaCha12Rng {
+    type Error = String;
+
+    fn try_from(wire: RngWire) -> Result<Self, Self::Error> {
+        let fout = || format!("ongeldige random generator: {}", wire.seed);
+        let mut seed = [0; 32];
+        if wire.seed.len() != 64 || !wire.seed.is_ascii() {
+            return Err(fout());
+        }
+        for (i, byte) in seed.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&wire.seed[2 * i..2 * i + 2], 16).map_err(|_| fout())?;
+        }
+
+        let mut rng = ChaCha12Rng::from_seed(seed);
+        rng.set_stream(wire.stroom);
+        rng.set_word_pos(wire.positie.parse().map_err(|_| fout())?);
+        Ok(rng)
+    }
+}
+
+impl Serialize for Spel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tafel = Vec::new();
+        if let Some((a, b, c)) = self.tafel.0 {
+            tafel.extend([a, b, c]);
+        }
+        tafel.extend(self.tafel.1);
+        tafel.extend(self.tafel.2);
+
+        SpelWire {
+            id: *self.id.get().unwrap(),
+            spelers: self.spelers.clone(),
+            potten: self.potten.clone(),
+            tafel,
+            huidige_dealer: self.huidige_dealer,
+            aan_de_beurt: self.aan_de_beurt,
+            deck: self.deck.clone(),
+            huidige_inzet: self.huidige_inzet,
+            laatste_actionabele_speler: self.laatste_actionabele_speler,
+            laatste_verhoging: self.laatste_verhoging,
+            gehandeld: self.gehandeld.clone(),
+            verhogingen: self.verhogingen,
+            bet_structuur: self.bet_structuur,
+            niveau: self.niveau,
+            verplichte_inzetten: self.verplichte_inzetten,
+            antes: self.antes.clone(),
+            toernooi: self.toernooi,
+            status: self.status,
+            historie: self.historie.clone(),
+            log: self.log.clone(),
+            seed: Some(self.seed),
+            rng: Some(RngWire::from(&self.rng)),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Spel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = SpelWire::deserialize(deserializer)?;
+        let tafel = match wire.tafel[..] {
+            [] => (None, None, None),
+            [a, b, c] => (Some((a, b, c)), None, None),
+            [a, b, c, d] => (Some((a, b, c)), Some(d), None),
+            [a, b, c, d, e] => (Some((a, b, c)), Some(d), Some(e)),
+            _ => {
+                return Err(de::Error::invalid_length(
+                    wire.tafel.len(),
+                    &"0, 3, 4 of 5 kaarten",
+                ))
+            }
+        };
+        if wire.aan_de_beurt >= wire.spelers.len().max(1)
+            || wire.huidige_dealer >= wire.spelers.len().max(1)
+        {
+            return Err(de::Error::custom("plaats buiten de tafel"));
+        }
+
+        let mut spel = Spel::new(wire.id, None);
+        spel.spelers = wire.spelers;
+        spel.potten = wire.potten;
+        spel.tafel = tafel;
+        spel.huidige_dealer = wire.huidige_dealer;
+        spel.aan_de_beurt = wire.aan_de_beurt;
+        spel.deck = wire.deck;
+        spel.huidige_inzet = wire.huidige_inzet;
+        spel.laatste_actionabele_speler = wire.laatste_actionabele_speler;
+        spel.laatste_verhoging = wire.laatste_verhoging;
+        spel.gehandeld = wire.gehandeld;
+        spel.verhogingen = wire.verhogingen;
+        spel.bet_structuur = wire.bet_structuur;
+        spel.niveau = wire.niveau;
+        spel.verplichte_inzetten = wire.verplichte_inzetten;
+        spel.antes = wire.antes;
+        spel.toernooi = wire.toernooi;
+        spel.status = wire.status;
+        spel.historie = wire.historie;
+        spel.log = wire.log;
+        if let Some(seed) = wire.seed {
+            spel.seed = seed;
+        }
+        if let Some(rng) = wire.rng {
+            spel.rng = rng.try_into().map_err(de::Error::custom)?;
+        }
+        Ok(spel)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "soort", rename_all = "snake_case")]
+enum NiveauDuurWire {
+    Handen { handen: u64 },
+    Tijd { milliseconden: u64 },
+}
+
+#[derive(Serialize, Deserialize)]
+struct ToernooiInstellingenWire {
+    start_chips: u64,
+    niveaus: Vec<BlindNiveau>,
+    niveau_duur: NiveauDuurWire,
+    bet_structuur: BetStructuur,
+    verplichte_inzetten: VerplichteInzetten,
+    max_per_tafel: usize,
+    prijzenpot: u64,
+    uitbetaling: Uitbetaling,
+}
+
+// Een toernooi zoals het bewaard wordt. Een `Instant` kan niet bewaard worden, dus van een
+// niveau dat op tijd loopt, schrijven we hoe lang het al bezig is; bij het inlezen loopt het
+// vanaf daar verder.
+#[derive(Serialize, Deserialize)]
+struct ToernooiWire {
+    id: ToernooiId,
+    instellingen: ToernooiInstellingenWire,
+    spelers: Vec<SpelerId>,
+    spellen: Vec<SpelId>,
+    niveau: usize,
+    handen_in_niveau: u64,
+    #[serde(default)]
+    niveau_bezig_ms: Option<u64>,
+    uitgeschakeld: Vec<SpelerId>,
+    onderweg: Vec<(SpelerId, SpelId)>,
+    status: SpelStatus,
+}
+
+impl Serialize for Toernooi {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let instellingen = &self.instellingen;
+        ToernooiWire {
+            id: self.id,
+            instellingen: ToernooiInstellingenWire {
+                start_chips: instellingen.start_chips,
+                niveaus: instellingen.niveaus.clone(),
+                niveau_duur: match instellingen.niveau_duur {
+                    NiveauDuur::Handen(handen) => NiveauDuurWire::Handen { handen },
+                    NiveauDuur::Tijd(duur) => NiveauDuurWire::Tijd {
+                        milliseconden: duur.as_millis() as u64,
+                    },
+                },
+                bet_structuur: instellingen.bet_structuur,
+                verplichte_inzetten: instellingen.verplichte_inzetten,
+                max_per_tafel: instellingen.max_per_tafel,
+                prijzenpot: instellingen.prijzenpot,
+                uitbetaling: instellingen.uitbetaling.clone(),
+            },
+            spelers: self.spelers.clone(),
+            spellen: self.spellen.clone(),
+            niveau: self.niveau,
+            handen_in_niveau: self.handen_in_niveau,
+            niveau_bezig_ms: self
+                .niveau_sinds
+                .map(|sinds| sinds.elapsed().as_millis() as u64),
+            uitgeschakeld: self.uitgeschakeld.clone(),
+            onderweg: self.onderweg.clone(),
+            status: self.status,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Toernooi {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = ToernooiWire::deserialize(deserializer)?;
+        let instellingen = wire.instellingen;
+        if instellingen.niveaus.is_empty() {
+            return Err(de::Error::custom(PokbotcomError::GeenBlindNiveaus));
+        }
+        Ok(Toernooi {
+            id: wire.id,
+            instellingen: ToernooiInstellingen {
+                start_chips: instellingen.start_chips,
+                niveaus: instellingen.niveaus,
+                niveau_duur: match instellingen.niveau_duur {
+                    NiveauDuurWire::Handen { handen } => NiveauDuur::Handen(handen),
+                    NiveauDuurWire::Tijd { milliseconden } => {
+                        NiveauDuur::Tijd(Duration::from_millis(milliseconden))
+                    }
+                },
+                bet_structuur: instellingen.bet_structuur,
+                verplichte_inzetten: instellingen.verplichte_inzetten,
+                max_per_tafel: instellingen.max_per_tafel,
+                prijzenpot: instellingen.prijzenpot,
+                uitbetaling: instellingen.uitbetaling,
+            },
+            spelers: wire.spelers,
+            spellen: wire.spellen,
+            niveau: wire.niveau,
+            uitgeschakeld: wire.uitgeschakeld,
+            onderweg: wire.onderweg,
+            status: wire.status,
+            handen_in_niveau: wire.handen_in_niveau,
+            niveau_sinds: wire.niveau_bezig_ms.map(|bezig| {
+                let nu = Instant::now();
+                nu.checked_sub(Duration::from_millis(bezig)).unwrap_or(nu)
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::kaart::notatie::lees_kaarten;
+
+    fn kaarten<const N: usize>(tekst: &str) -> [Kaart; N] {
+        lees_kaarten(tekst).unwrap().try_into().unwrap()
+    }
+
+    fn heen_en_terug<T>(waarde: &T, verwacht: serde_json::Value) -> T
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let json = serde_json::to_value(waarde).unwrap();
+        assert_eq!(json, verwacht);
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn alle_meldingen() -> Vec<PokbotcomMelding> {
+        let [a, b, c, d, e] = kaarten("Ah Kh Qh Jh Th");
+        vec![
+            PokbotcomMelding::Hand(a, b),
+            PokbotcomMelding::Flop(a, b, c),
+            PokbotcomMelding::Turn(d),
+            PokbotcomMelding::River(e),
+            PokbotcomMelding::SpelerActie(SpelerId(2), Actie::Bet(40)),
+            PokbotcomMelding::AanDeBeurt(LegaleActies {
+                fold: true,
+                call: true,
+                te_callen: 20,
+                bet: true,
+                min_bet: 1,
+                max_bet: 960,
+                all_in: true,
+                ..Default::default()
+            }),
+            PokbotcomMelding::RondeOver,
+            PokbotcomMelding::Gewonnen(vec![(SpelerId(1), Some(Hand::new([a, b, c, d, e])), 120)]),
+            PokbotcomMelding::Uitgeschakeld(SpelerId(2), 3),
+            PokbotcomMelding::ToernooiUitslag(vec![SpelerId(1), SpelerId(0), SpelerId(2)]),
+            PokbotcomMelding::Verplaatst(SpelId(4)),
+        ]
+    }
+
+    #[test]
+    fn kaarten_en_handen() {
+        let [a, b, c, d, e] = kaarten("As Td 2c 7h 9s");
+        assert_eq!(heen_en_terug(&a, json!("As")), a);
+        assert_eq!(heen_en_terug(&b, json!("Td")), b);
+        for kaart in Kaart::maak_deck() {
+            assert_eq!(
+                serde_json::from_value::<Kaart>(serde_json::to_value(kaart).unwrap()).unwrap(),
+                kaart
+            );
+        }
+
+        let hand = Hand::new([a, b, c, d, e]);
+        let terug = heen_en_terug(&hand, json!(["As", "Td", "9s", "7h", "2c"]));
+        assert_eq!(terug.kaarten(), hand.kaarten());
+
+        assert!(serde_json::from_value::<Kaart>(json!("Ax")).is_err());
+        assert!(serde_json::from_value::<Hand>(json!(["As", "As", "9s", "7h", "2c"])).is_err());
+        assert!(serde_json::from_value::<Hand>(json!(["As", "Td"])).is_err());
+    }
+
+    #[test]
+    fn acties() {
+        let verwacht = [
+            (Actie::Fold, json!({ "soort": "fold" })),
+            (Actie::Check, json!({ "soort": "check" })),
+            (Actie::Call, json!({ "soort": "call" })),
+            (Actie::Bet(50), json!({ "soort": "bet", "chips": 50 })),
+            (Actie::AllIn, json!({ "soort": "all_in" })),
+        ];
+        for (actie, json) in verwacht {
+            assert_eq!(heen_en_terug(&actie, json), actie);
+        }
+        assert!(serde_json::from_value::<Actie>(json!({ "soort": "bet" })).is_err());
+    }
+
+    #[test]
+    fn meldingen() {
+        let verwacht = [
+            json!({ "soort": "hand", "kaarten": ["Ah", "Kh"] }),
+            json!({ "soort": "flop", "kaarten": ["Ah", "Kh", "Qh"] }),
+            json!({ "soort": "turn", "kaart": "Jh" }),
+            json!({ "soort": "river", "kaart": "Th" }),
+            json!({ "soort": "speler_actie", "speler_id": 2, "actie": { "soort": "bet", "chips": 40 } }),
+            json!({
+                "soort": "aan_de_beurt",
+                "legale_acties": {
+                    "fold": true, "check": false, "call": true, "te_callen": 20,
+                    "bet": true, "min_bet": 1, "max_bet": 960, "all_in": true,
+                },
+            }),
+            json!({ "soort": "ronde_over" }),
+            json!({
+                "soort": "gewonnen",
+                "winnaars": [{ "speler_id": 1, "hand": ["Ah", "Kh", "Qh", "Jh", "Th"], "chips": 120 }],
+            }),
+            json!({ "soort": "uitgeschakeld", "speler_id": 2, "plaats": 3 }),
+            json!({ "soort": "toernooi_uitslag", "uitslag": [1, 0, 2] }),
+            json!({ "soort": "verplaatst", "spel_id": 4 }),
+        ];
+        for (melding, json) in alle_meldingen().into_iter().zip(verwacht) {
+            assert_eq!(heen_en_terug(&melding, json), melding);
+        }
+
+        // zonder showdown toont de winnaar zijn hand niet
+        let zonder_showdown = PokbotcomMelding::Gewonnen(vec![(SpelerId(2), None, 30)]);
+        let json = json!({ "soort": "gewonnen", "winnaars": [{ "speler_id": 2, "chips": 30 }] });
+        assert_eq!(heen_en_terug(&zonder_showdown, json), zonder_showdown);
+    }
+
+    #[test]
+    fn versies() {
+        let json = naar_json(Actie::Bet(50));
+        assert_eq!(json, r#"{"versie":1,"soort":"bet","chips":50}"#);
+        assert_eq!(lees_json::<Actie>(&json), Ok(Actie::Bet(50)));
+        let melding = PokbotcomMelding::AanDeBeurt(LegaleActies::default());
+        assert_eq!(
+            lees_json::<PokbotcomMelding>(&naar_json(&melding)),
+            Ok(melding)
+        );
+
+        // zonder versie wordt de huidige aangenomen
+        assert_eq!(lees_json::<Actie>(r#"{"soort":"call"}"#), Ok(Actie::Call));
+        assert_eq!(
+            lees_json::<Actie>(r#"{"versie":2,"soort":"call"}"#),
+            Err(PokbotcomError::OnbekendeVersie(2))
+        );
+        assert!(matches!(
+            lees_json::<Actie>(r#"{"soort":"raise"}"#),
+            Err(PokbotcomError::OngeldigBericht(_))
+        ));
+    }
+
+    #[test]
+    fn spellen() {
+        let mut spel = Spel::new(SpelId(3), Some(0));
+        spel.spelers = vec![SpelerId(0), SpelerId(1), SpelerId(2)];
+        spel.potten = vec![Pot {
+            chips: 60,
+            gerechtigden: vec![SpelerId(0), SpelerId(2)],
+        }];
+        let [a, b, c, d] = kaarten("Ah Kh Qh 2c");
+        spel.tafel = (Some((a, b, c)), Some(d), None);
+        spel.aan_de_beurt = 2;
+        spel.deck.truncate(3);
+        spel.status = SpelStatus::Lopend;
+
+        let json = serde_json::to_value(&spel).unwrap();
+        assert_eq!(json["id"], 3);
+        assert_eq!(json["tafel"], json!(["Ah", "Kh", "Qh", "2c"]));
+        assert_eq!(json["status"], "lopend");
+        assert_eq!(
+            json["potten"],
+            json!([{ "chips": 60, "gerechtigden": [0, 2] }])
+        );
+
+        let mut terug: Spel = lees_json(&naar_json(&spel)).unwrap();
+        assert_eq!(serde_json::to_value(&terug).unwrap(), json);
+        assert_eq!(terug.tafel, spel.tafel);
+
+        // de random generator gaat verder waar hij gebleven was
+        use rand::RngCore;
+        assert_eq!(terug.rng.next_u64(), spel.rng.next_u64());
+
+        let mut kapot = json;
+        kapot["tafel"] = json!(["Ah", "Kh"]);
+        assert!(serde_json::from_value::<Spel>(kapot).is_err());
+    }
+
+    #[test]
+    fn toernooien() {
+        let mut centrale = crate::Centrale::new();
+        let ids = ["Aart", "Bart"].map(|naam| {
+            centrale
+                .registreer_speler(crate::Speler::new_zonder_id(naam.into()))
+                .unwrap()
+        });
+        let instellingen = ToernooiInstellingen {
+            niveau_duur: NiveauDuur::Tijd(Duration::from_secs(600)),
+            uitbetaling: Uitbetaling::Top3,
+            ..Default::default()
+        };
+        let toernooi_id = centrale
+            .maak_toernooi(ids.to_vec(), instellingen, Some(1))
+            .unwrap();
+        centrale.start_toernooi(toernooi_id).unwrap();
+        let toernooi = centrale.toernooi(toernooi_id).unwrap();
+
+        let json = serde_json::to_value(toernooi).unwrap();
+        assert_eq!(
+            json["instellingen"]["niveau_duur"],
+            json!({ "soort": "tijd", "milliseconden": 600_000 })
+        );
+        assert_eq!(
+            json["instellingen"]["uitbetaling"],
+            json!({ "soort": "top3" })
+        );
+        assert_eq!(json["status"], "lopend");
+
+        // het niveau loopt verder vanaf waar het was
+        let terug: Toernooi = lees_json(&naar_json(toernooi)).unwrap();
+        assert_eq!(terug.instellingen, toernooi.instellingen);
+        assert_eq!(
+            (&terug.spelers, &terug.spellen, terug.status),
+            (&toernooi.spelers, &toernooi.spellen, toernooi.status)
+        );
+        assert!(terug.niveau_sinds.unwrap().elapsed() < Duration::from_secs(60));
+
+        let mut kapot = json;
+        kapot["instellingen"]["niveaus"] = json!([]);
+        assert!(serde_json::from_value::<Toernooi>(kapot).is_err());
+    }
+
+    fn pokbotcom_schema() -> Value {
+        serde_json::from_str(include_str!("../schema/pokbotcom-v1.schema.json")).unwrap()
+    }
+
+    // Controleert `waarde` tegen de definitie `naam` uit het schema. Dit kent enkel de
+    // sleutelwoorden die het schema gebruikt, en weigert velden die het schema niet noemt.
+    pub(crate) fn volgens_schema(naam: &str, waarde: &Value) -> Result<(), String> {
+        let schema = pokbotcom_schema();
+        controleer(&schema, &schema["$defs"][naam], waarde, naam)
+    }
+
+    fn controleer(wortel: &Value, schema: &Value, waarde: &Value, pad: &str) -> Result<(), String> {
+        let fout = |reden: String| Err(format!("{pad}: {reden} in {waarde}"));
+        if let Some(verwijzing) = schema["$ref"].as_str() {
+            let naam = verwijzing.strip_prefix("#/$defs/").unwrap();
+            controleer(wortel, &wortel["$defs"][naam], waarde, pad)?;
+        }
+        if let Some(opties) = schema["oneOf"].as_array() {
+            let passend = opties
+                .iter()
+                .filter(|optie| controleer(wortel, optie, waarde, pad).is_ok())
+                .count();
+            if passend != 1 {
+                return fout(format!("past bij {passend} van de opties"));
+            }
+        }
+        if !schema["const"].is_null() && schema["const"] != *waarde {
+            return fout(format!("verwacht {}", schema["const"]));
+        }
+        if let Some(mogelijk) = schema["enum"].as_array() {
+            if !mogelijk.contains(waarde) {
+                return fout(format!("niet een van {}", schema["enum"]));
+            }
+        }
+        if let Some(soort) = schema["type"].as_str() {
+            let klopt = match soort {
+                "object" => waarde.is_object(),
+                "array" => waarde.is_array(),
+                "integer" => waarde.is_u64() || waarde.is_i64(),
+                "number" => waarde.is_number(),
+                "string" => waarde.is_string(),
+                "boolean" => waarde.is_boolean(),
+                "null" => waarde.is_null(),
+                _ => return fout(format!("onbekend type {soort}")),
+            };
+            if !klopt {
+                return fout(format!("geen {soort}"));
+            }
+        }
+        if let Some(minimum) = schema["minimum"].as_f64() {
+            if waarde.as_f64().is_some_and(|w| w < minimum) {
+                return fout(format!("kleiner dan {minimum}"));
+            }
+        }
+        // het enige patroon in het schema is dat van een kaart
+        if schema["pattern"].is_string() {
+            let tekst = waarde.as_str().unwrap_or_default();
+            if tekst
+                .parse::<Kaart>()
+                .map(|k| format!("{k:#}"))
+                .ok()
+                .as_deref()
+                != Some(tekst)
+            {
+                return fout("geen kaart".to_string());
+            }
+        }
+        if let Some(lijst) = waarde.as_array() {
+            let aantal = lijst.len() as u64;
+            if schema["minItems"].as_u64().is_some_and(|min| aantal < min)
+                || schema["maxItems"].as_u64().is_some_and(|max| aantal > max)
+            {
+                return fout(format!("{aantal} elementen"));
+            }
+            if !schema["items"].is_null() {
+                for (i, element) in lijst.iter().enumerate() {
+                    controleer(wortel, &schema["items"], element, &format!("{pad}[{i}]"))?;
+                }
+            }
+        }
+        if let (Some(velden), Some(object)) = (schema["properties"].as_object(), waarde.as_object())
+        {
+            for (veld, inhoud) in object {
+                match velden.get(veld) {
+                    Some(veld_schema) => {
+                        controleer(wortel, veld_schema, inhoud, &format!("{pad}.{veld}"))?
+                    }
+                    None => return fout(format!("onbekend veld {veld}")),
+                }
+            }
+            for veld in schema["required"].as_array().into_iter().flatten() {
+                if !object.contains_key(veld.as_str().unwrap()) {
+                    return fout(format!("veld {veld} ontbreekt"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Het gepubliceerde schema en de code mogen niet uit elkaar lopen.
+    #[test]
+    fn schema() {
+        let schema = pokbotcom_schema();
+        assert_eq!(schema["properties"]["versie"]["const"], VERSIE);
+
+        let soorten = |naam: &str| {
+            schema["$defs"][naam]["oneOf"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|s| {
+                    s["properties"]["soort"]["const"]
+                        .as_str()
+                        .unwrap()
+                        .to_string()
+                })
+                .collect_vec()
+        };
+        let meldingen = alle_meldingen()
+            .iter()
+            .map(|m| {
+                serde_json::to_value(m).unwrap()["soort"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect_vec();
+        assert_eq!(soorten("melding"), meldingen);
+        assert_eq!(soorten("actie"), ["fold", "check", "call", "bet", "all_in"]);
+
+        // en elk bericht heeft de velden die het schema beschrijft, in de juiste vorm
+        let zonder_showdown = PokbotcomMelding::Gewonnen(vec![(SpelerId(2), None, 30)]);
+        for melding in alle_meldingen().iter().chain([&zonder_showdown]) {
+            let json = serde_json::to_value(melding).unwrap();
+            volgens_schema("melding", &json).unwrap();
+        }
+        for actie in [
+            Actie::Fold,
+            Actie::Check,
+            Actie::Call,
+            Actie::Bet(50),
+            Actie::AllIn,
+        ] {
+            volgens_schema("actie", &serde_json::to_value(actie).unwrap()).unwrap();
+        }
+        for structuur in [
+            BetStructuur::NoLimit,
+            BetStructuur::PotLimit,
+            BetStructuur::FixedLimit {
+                kleine_bet: 20,
+                grote_bet: 40,
+                max_verhogingen: 4,
+            },
+        ] {
+            volgens_schema("bet_structuur", &serde_json::to_value(structuur).unwrap()).unwrap();
+        }
+
+        // een veld met de verkeerde vorm of een onbekend veld valt op
+        assert!(volgens_schema("melding", &json!({ "soort": "turn", "kaart": "Jx" })).is_err());
+        assert!(volgens_schema("actie", &json!({ "soort": "bet", "chips": -5 })).is_err());
+        assert!(volgens_schema("actie", &json!({ "soort": "bet" })).is_err());
+        assert!(volgens_schema("actie", &json!({ "soort": "call", "chips": 5 })).is_err());
+    }
+}